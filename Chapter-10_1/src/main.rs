@@ -1,3 +1,5 @@
+// The book builds every list with vec!, even where an array would do.
+#[allow(clippy::useless_vec)]
 fn main() {
 
 
@@ -120,25 +122,45 @@ fn main() {
         println!("The largest char is {}", result);
     */ }
     {
+        // The generic version above doesn't compile because not every T can be compared with >.
+        // With a T: PartialOrd bound it does, and it lives in the learning crate, where an empty
+        // list gives None instead of panicking on list[0].
+        let number_list = vec![34, 50, 25, 100, 65];
+
+        if let Some(result) = learning::largest(&number_list) {
+            println!("The largest number is {}", result);
+        }
+
+        let char_list = vec!['y', 'm', 'a', 'q'];
+
+        if let Some(result) = learning::largest(&char_list) {
+            println!("The largest char is {}", result);
+        }
+    }
+    {
+        #[allow(dead_code)]
         struct Point<T> {
             x: T, 
             y: T,
         }
 
-        let integer = Point { x: 5, y: 10};
-        let float = Point {x: 1.0, y: 4.0};
+        let _integer = Point { x: 5, y: 10};
+        let _float = Point {x: 1.0, y: 4.0};
     }
     {
+        #[allow(dead_code)]
         struct Point <T, U> {
             x: T, 
             y: U, 
         }
 
-        let both_integer = Point { x: 5, y: 10 }; 
-        let both_float = Point {x: 1.0, y: 4.0}; 
-        let integer_and_float = Point {x: 5, y: 4.0};
+        let _both_integer = Point { x: 5, y: 10 }; 
+        let _both_float = Point {x: 1.0, y: 4.0}; 
+        let _integer_and_float = Point {x: 5, y: 4.0};
     }
     {
+        // Only here to show how the standard library declares it.
+        #[allow(dead_code)]
         enum Option<T> {
             Some(T), 
             None,
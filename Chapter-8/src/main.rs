@@ -1,3 +1,5 @@
+// The book builds its vectors with vec! and push to show both, even where an array would do.
+#[allow(clippy::useless_vec, clippy::vec_init_then_push)]
 fn main() {
     
     {
@@ -27,10 +29,21 @@ fn main() {
 
        // let does_not_exist = &v[100];
         let does_not_exist = v.get(100);
-        if(does_not_exist == None){
+        if does_not_exist.is_none() {
             println!("None");
         }
     }
+    {
+        use learning::access::CheckedAccess;
+
+        let v = vec![1,2,3,4,5];
+
+        // try_get explains what went wrong instead of returning a bare None.
+        match v.try_get(100) {
+            Ok(element) => println!("The element is {element}"),
+            Err(e) => println!("{e}"),
+        }
+    }
     {
         let v = vec![100, 32, 57];
         for i in &v {
@@ -44,5 +57,18 @@ fn main() {
             println!("{i}");
         }
     }
+    {
+        use learning::stats;
+
+        let v = vec![100, 32, 57];
+
+        // These return Option, so an empty Vec gives None instead of a panic.
+        if let (Some(mean), Some(median)) = (stats::mean(&v), stats::median(&v)) {
+            println!("mean {mean:.2}, median {median}");
+        }
+
+        let empty: Vec<i32> = Vec::new();
+        println!("{:?}", stats::mean(&empty));
+    }
     
 }
@@ -0,0 +1,63 @@
+// Shows a file's bytes next to the text they decode to.
+//
+//     cargo run -p Chapter-8_2 --bin hexdump -- notes.txt
+//     cargo run -p Chapter-8_2 --bin hexdump -- --encoding windows-1252 < legacy.csv
+//
+// Without --encoding, a byte order mark picks the encoding, and UTF-8 is assumed otherwise.
+
+use learning::encoding::{self, Encoding, Mode};
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::process;
+
+const USAGE: &str = "usage: hexdump [--encoding <utf-8 | latin-1 | windows-1252 | utf-16le | utf-16be>] [file]";
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let mut chosen = None;
+    let mut path = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--encoding" | "-e" => match args.next().map(|label| label.parse::<Encoding>()) {
+                Some(Ok(encoding)) => chosen = Some(encoding),
+                Some(Err(e)) => fail(&e.to_string()),
+                None => fail(USAGE),
+            },
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if path.is_none() && !arg.starts_with('-') => path = Some(arg),
+            _ => fail(USAGE),
+        }
+    }
+
+    let bytes = match &path {
+        Some(path) => fs::read(path).unwrap_or_else(|e| fail(&format!("couldn't read {}: {}", path, e))),
+        None => {
+            let mut bytes = Vec::new();
+            if let Err(e) = io::stdin().read_to_end(&mut bytes) {
+                fail(&format!("couldn't read stdin: {}", e));
+            }
+            bytes
+        }
+    };
+
+    let (encoding, source) = match (chosen, encoding::detect_bom(&bytes)) {
+        (Some(encoding), _) => (encoding, "chosen"),
+        (None, Some((encoding, _))) => (encoding, "from the byte order mark"),
+        (None, None) => (Encoding::Utf8, "default"),
+    };
+    println!("{} bytes, {} ({})", bytes.len(), encoding, source);
+    print!("{}", encoding::hexdump(&bytes, encoding));
+
+    if let Err(e) = encoding::decode(&bytes, encoding, Mode::Strict) {
+        println!("{}", e);
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(2);
+}
@@ -0,0 +1,48 @@
+// Reads text on stdin and writes it back transformed, one line at a time.
+//
+//     cargo run -p Chapter-8_2 --bin transform -- pig-latin < notes.txt
+//     echo "Hello, world" | cargo run -p Chapter-8_2 --bin transform -- caesar 3
+
+use learning::text;
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::process;
+
+const USAGE: &str = "usage: transform <pig-latin | rot13 | caesar <shift> | snake | camel | kebab | title | reverse>";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+    let transform: Box<dyn Fn(&str) -> String> = match args[..] {
+        ["pig-latin"] => Box::new(text::pig_latin),
+        ["rot13"] => Box::new(text::rot13),
+        ["caesar", shift] => match shift.parse::<i32>() {
+            Ok(shift) => Box::new(move |line| text::caesar(line, shift)),
+            Err(_) => fail(&format!("caesar needs a whole-number shift, not '{}'", shift)),
+        },
+        ["snake"] => Box::new(text::to_snake_case),
+        ["camel"] => Box::new(text::to_camel_case),
+        ["kebab"] => Box::new(text::to_kebab_case),
+        ["title"] => Box::new(text::to_title_case),
+        ["reverse"] => Box::new(text::reverse),
+        _ => fail(USAGE),
+    };
+
+    let stdin = io::stdin();
+    let mut stdout = io::stdout().lock();
+    for line in stdin.lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => fail(&format!("couldn't read stdin: {}", e)),
+        };
+        // A closed pipe (e.g. `| head`) just means we can stop.
+        if writeln!(stdout, "{}", transform(&line)).is_err() {
+            return;
+        }
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(2);
+}
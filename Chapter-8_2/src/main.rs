@@ -1,8 +1,8 @@
 fn main() {
-    let s = String::new();
+    let _s = String::new();
 
     let data = "initial contents";
-    let s = data.to_string();
+    let _s = data.to_string();
 
     //let s= "initial contents".to_string();
     {
@@ -38,6 +38,17 @@ fn main() {
         let s = format!("{s1}-{s2}-{s3}"); // Doesn't take ownership of any parameters. 
         println!("{s}");
     }
+    {
+        // format! needs the names at compile time. A template can come from a file instead.
+        use learning::template::{self, Value};
+
+        let context = Value::map().with("s1", "tic").with("s2", "tac").with("s3", "toe");
+        println!("{}", template::render("{s1}-{s2}-{s3}", &context).unwrap());
+        match template::render("{s1}-{s2}-{s4}", &context) {
+            Ok(s) => println!("{s}"),
+            Err(e) => println!("template error: {e}"),
+        }
+    }
     {
         let hello = String::from("Hola");
 
@@ -48,4 +59,13 @@ fn main() {
             println!("{b}");
         }
     }
+    {
+        // chars() splits "नमस्ते" into six pieces. The grapheme rules give three, keeping
+        // the conjunct "स्ते" together.
+        let hello = "नमस्ते";
+        println!("{} chars, {} graphemes", hello.chars().count(), learning::grapheme::grapheme_count(hello));
+        for g in learning::grapheme::graphemes(hello) {
+            println!("{g}");
+        }
+    }
 }
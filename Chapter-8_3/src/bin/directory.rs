@@ -0,0 +1,62 @@
+// The chapter 8 employee directory as an interactive prompt. Commands can also be piped in, one
+// per line.
+//
+//     cargo run -p Chapter-8_3 --bin directory
+//     > Add Sally to Engineering
+//     Added Sally to Engineering.
+
+use learning::directory::{Command, Directory};
+use std::io::{self, BufRead, IsTerminal, Write};
+
+const HELP: &str = "Commands:
+  Add Sally to Engineering
+  Remove Sally from Engineering
+  Move Sally from Engineering to Sales   (or Move Sally to Sales)
+  List Engineering
+  List all
+  help
+  quit";
+
+fn main() {
+    let interactive = io::stdin().is_terminal();
+    if interactive {
+        println!("Employee directory. Type 'help' for the commands.");
+    }
+
+    let mut directory = Directory::new();
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        if interactive {
+            print!("> ");
+            io::stdout().flush().ok();
+        }
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            Some(Err(e)) => {
+                eprintln!("couldn't read input: {}", e);
+                return;
+            }
+            None => return,
+        };
+
+        match line.trim().to_lowercase().as_str() {
+            "" => continue,
+            "help" | "?" => {
+                println!("{}", HELP);
+                continue;
+            }
+            "quit" | "exit" => return,
+            _ => {}
+        }
+
+        let result = line
+            .parse::<Command>()
+            .map_err(|e| e.to_string())
+            .and_then(|command| directory.execute(&command).map_err(|e| e.to_string()));
+        match result {
+            Ok(output) => println!("{}", output),
+            Err(message) => println!("error: {}", message),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+// Keeps team scores in a JSON file between runs.
+//
+//     cargo run -p Chapter-8_3 --bin scoreboard -- scores.json new keep-max
+//     cargo run -p Chapter-8_3 --bin scoreboard -- scores.json record Blue 25
+//     cargo run -p Chapter-8_3 --bin scoreboard -- scores.json history Blue
+
+use learning::scoreboard::{Policy, Scoreboard};
+use std::env;
+use std::path::Path;
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const USAGE: &str = "usage: scoreboard FILE new overwrite|keep-max|accumulate
+       scoreboard FILE record TEAM POINTS
+       scoreboard FILE show
+       scoreboard FILE history TEAM
+       scoreboard FILE undo";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+    let (path, command) = match args.as_slice() {
+        [path, command @ ..] if !command.is_empty() => (path.as_str(), command),
+        _ => fail(USAGE),
+    };
+
+    match command {
+        [new, policy] if new == "new" => {
+            if Path::new(path).exists() {
+                fail(&format!("{} already exists", path));
+            }
+            let policy: Policy = policy.parse().unwrap_or_else(|e| fail(&format!("{}", e)));
+            save(&Scoreboard::new(policy), path);
+        }
+        [record, team, points] if record == "record" => {
+            let points: i32 = points.parse().unwrap_or_else(|_| fail("POINTS needs to be a whole number"));
+            let mut scoreboard = load(path);
+            let score = scoreboard.record(team, points);
+            save(&scoreboard, path);
+            println!("{}: {}", team, score);
+        }
+        [show] if show == "show" => {
+            let scoreboard = load(path);
+            if scoreboard.is_empty() {
+                println!("no scores yet ({} policy)", scoreboard.policy());
+            }
+            print!("{}", scoreboard);
+        }
+        [history, team] if history == "history" => {
+            let scoreboard = load(path);
+            let mut any = false;
+            for change in scoreboard.history(team) {
+                any = true;
+                println!("{}  {:>6}  -> {}", utc(change.at), change.points, change.score);
+            }
+            if !any {
+                println!("{} has no scores", team);
+            }
+        }
+        [undo] if undo == "undo" => {
+            let mut scoreboard = load(path);
+            match scoreboard.undo() {
+                Some(change) => {
+                    save(&scoreboard, path);
+                    match scoreboard.score(&change.team) {
+                        Some(score) => println!("undid {} {}; {} is back to {}", change.team, change.points, change.team, score),
+                        None => println!("undid {} {}; {} has no scores now", change.team, change.points, change.team),
+                    }
+                }
+                None => println!("nothing to undo"),
+            }
+        }
+        _ => fail(USAGE),
+    }
+}
+
+// "2024-03-09 14:05:00 UTC", by way of Howard Hinnant's civil_from_days.
+fn utc(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, rest) = ((seconds / 86_400) as i64, seconds % 86_400);
+
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        rest / 3600,
+        rest / 60 % 60,
+        rest % 60
+    )
+}
+
+fn load(path: &str) -> Scoreboard {
+    Scoreboard::load(path).unwrap_or_else(|e| fail(&format!("couldn't read {}: {}", path, e)))
+}
+
+fn save(scoreboard: &Scoreboard, path: &str) {
+    if let Err(e) = scoreboard.save(path) {
+        fail(&format!("couldn't write {}: {}", path, e));
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(2);
+}
@@ -0,0 +1,105 @@
+// Full-text search over files, with the index kept in a text file between runs.
+//
+//     cargo run -p Chapter-8_3 --bin search -- add notes.idx notes/*.md
+//     cargo run -p Chapter-8_3 --bin search -- query notes.idx '"hash map" AND NOT python'
+//     cargo run -p Chapter-8_3 --bin search -- remove notes.idx notes/old.md
+
+use learning::search::Index;
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::process;
+
+const USAGE: &str = "usage: search add INDEX FILE...
+       search remove INDEX FILE...
+       search query INDEX QUERY [--top N]
+       search list INDEX";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+    let (command, path, rest) = match args.as_slice() {
+        [command, path, rest @ ..] => (command.as_str(), path.as_str(), rest),
+        _ => fail(USAGE),
+    };
+
+    match command {
+        "add" => {
+            if rest.is_empty() {
+                fail(USAGE);
+            }
+            let mut index = match Index::load(path) {
+                Ok(index) => index,
+                Err(e) if e.kind() == ErrorKind::NotFound => Index::new(),
+                Err(e) => fail(&format!("couldn't read {}: {}", path, e)),
+            };
+            for file in rest {
+                let bytes = fs::read(file).unwrap_or_else(|e| fail(&format!("couldn't read {}: {}", file, e)));
+                // Adding a file again replaces the old copy.
+                remove_named(&mut index, file);
+                index.add(file, &String::from_utf8_lossy(&bytes));
+            }
+            save(&index, path);
+            println!("{} documents, {} terms", index.len(), index.term_count());
+        }
+        "remove" => {
+            let mut index = load(path);
+            for file in rest {
+                if !remove_named(&mut index, file) {
+                    eprintln!("{} wasn't in the index", file);
+                }
+            }
+            save(&index, path);
+        }
+        "query" => {
+            let index = load(path);
+            let (query, top) = match rest {
+                [query] => (query, usize::MAX),
+                [query, flag, n] if flag == "--top" => match n.parse() {
+                    Ok(n) => (query, n),
+                    Err(_) => fail("--top needs a whole number"),
+                },
+                _ => fail(USAGE),
+            };
+            let hits = index.search(query).unwrap_or_else(|e| fail(&format!("bad query: {}", e)));
+            if hits.is_empty() {
+                println!("no matches");
+            }
+            for hit in hits.iter().take(top) {
+                println!("{:8.3}  {}", hit.score, hit.name);
+            }
+        }
+        "list" if rest.is_empty() => {
+            for (id, name) in load(path).documents() {
+                println!("{:>6}  {}", id, name);
+            }
+        }
+        _ => fail(USAGE),
+    }
+}
+
+fn remove_named(index: &mut Index, name: &str) -> bool {
+    let ids: Vec<_> = index.documents().filter(|&(_, n)| n == name).map(|(id, _)| id).collect();
+    for &id in &ids {
+        index.remove(id);
+    }
+    !ids.is_empty()
+}
+
+fn load(path: &str) -> Index {
+    Index::load(path).unwrap_or_else(|e| fail(&format!("couldn't read {}: {}", path, e)))
+}
+
+fn save(index: &Index, path: &str) {
+    if let Err(e) = index.save(path) {
+        fail(&format!("couldn't write {}: {}", path, e));
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(2);
+}
@@ -0,0 +1,81 @@
+// Word frequencies for files or stdin, read a line at a time.
+//
+//     cargo run --release -p Chapter-8_3 --bin wordfreq -- --top 20 --ngrams 2 server.log
+//     cat notes.txt | cargo run -p Chapter-8_3 --bin wordfreq -- --keep-case --stop-words none
+
+use learning::words::{Analyzer, Options, StopWords};
+use std::env;
+use std::io;
+use std::process;
+
+const USAGE: &str = "usage: wordfreq [--top N] [--ngrams N]... [--min-length N] [--keep-case]
+                [--stop-words english|none|FILE] [file]...";
+
+fn main() {
+    let mut options = Options::default();
+    let mut top = 10;
+    let mut paths = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut number = |name: &str| -> usize {
+            match args.next().map(|value| value.parse::<usize>()) {
+                Some(Ok(n)) => n,
+                _ => fail(&format!("{} needs a whole number", name)),
+            }
+        };
+        match arg.as_str() {
+            "--top" => top = number("--top"),
+            "--ngrams" => options.ngrams.push(number("--ngrams")),
+            "--min-length" => options.min_length = number("--min-length"),
+            "--keep-case" => options.fold_case = false,
+            "--stop-words" => {
+                options.stop_words = match args.next().as_deref() {
+                    Some("english") => StopWords::english(),
+                    Some("none") => StopWords::none(),
+                    Some(path) => StopWords::load(path)
+                        .unwrap_or_else(|e| fail(&format!("couldn't read stop words from {}: {}", path, e))),
+                    None => fail(USAGE),
+                }
+            }
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if arg.starts_with("--") => fail(USAGE),
+            _ => paths.push(arg),
+        }
+    }
+
+    let ngrams = options.ngrams.clone();
+    let mut analyzer = Analyzer::new(options);
+    if paths.is_empty() {
+        if let Err(e) = analyzer.add_reader(io::stdin().lock()) {
+            fail(&format!("couldn't read stdin: {}", e));
+        }
+    }
+    for path in &paths {
+        if let Err(e) = analyzer.add_file(path) {
+            fail(&format!("couldn't read {}: {}", path, e));
+        }
+    }
+
+    println!("{} words, {} distinct", analyzer.total_words(), analyzer.distinct_words());
+    print_top("words", &analyzer.top(top));
+    for size in ngrams {
+        print_top(&format!("{}-grams", size), &analyzer.top_ngrams(size, top));
+    }
+}
+
+fn print_top(title: &str, counts: &[(&str, u64)]) {
+    println!("\n{}", title);
+    let width = counts.iter().map(|(_, count)| count.to_string().len()).max().unwrap_or(0);
+    for (word, count) in counts {
+        println!("  {:>width$}  {}", count, word, width = width);
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(2);
+}
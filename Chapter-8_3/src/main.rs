@@ -8,7 +8,7 @@ fn main() {
         scores.insert(String::from("Yellow"), 50);
 
         let team_name = String::from("Blue");
-        let score = scores.get(&team_name).copied().unwrap_or(0);
+        let _score = scores.get(&team_name).copied().unwrap_or(0);
 
         for (key, value) in &scores {
             println!("{key}: {value}");
@@ -42,15 +42,50 @@ fn main() {
 
         println!("{:?}", scores);
     }
+    {
+        // The same teams on a Scoreboard, where insert-or-keep is a policy and changes can be undone.
+        use learning::scoreboard::{Policy, Scoreboard};
+
+        let mut scoreboard = Scoreboard::new(Policy::KeepMax);
+        scoreboard.record("Blue", 10);
+        scoreboard.join("Yellow", 50);
+        scoreboard.record("Blue", 25);
+        scoreboard.record("Red", 50);
+        scoreboard.record("Blue", 5);
+        scoreboard.record("Green", 60);
+        scoreboard.undo();
+
+        print!("{}", scoreboard);
+    }
+    {
+        // The chapter's closing exercise: an employee directory driven by text commands.
+        use learning::directory::{Command, Directory};
+
+        let mut directory = Directory::new();
+        let commands = [
+            "Add Sally to Engineering",
+            "Add Amir to Sales",
+            "Add Bob to Engineering",
+            "Move Amir to Engineering",
+            "Add Dana to Sales",
+            "List all",
+            "Hire Erin",
+        ];
+        for line in commands {
+            match line.parse::<Command>() {
+                Ok(command) => match directory.execute(&command) {
+                    Ok(output) => println!("{}", output),
+                    Err(e) => println!("error: {}", e),
+                },
+                Err(e) => println!("error: {}", e),
+            }
+        }
+    }
     {
         let text = "hello world wonderful world";
 
-        let mut map = HashMap::new();
-
-        for word in text.split_whitespace() {
-            let count = map.entry(word).or_insert(0);
-            *count += 1;
-        }
+        // The entry().or_insert(0) loop now lives in learning::words.
+        let map = learning::words::word_counts(text);
 
         println!("{:?}", map);
     }
@@ -8,8 +8,9 @@ fn main() {
     // panic!("Crash and burn");
     
     { 
-        let v = vec![1,2,3];
-        //v[99];
+        #[allow(clippy::useless_vec)]
+        let _v = vec![1,2,3];
+        //_v[99];
     }
 
     { /* 
@@ -24,7 +25,7 @@ fn main() {
     {
         let greeting_file_result = File::open("hello.txt");
 
-        let greeting_file = match greeting_file_result {
+        let _greeting_file = match greeting_file_result {
             Ok(file) => file,
             Err(error) => match error.kind() {
                 ErrorKind::NotFound => match File::create("hello.txt"){
@@ -38,9 +39,11 @@ fn main() {
         };
     }
     {
-        let greeting_file = File::open("hello.txt").expect("hello.txt should be included in this project");
+        let _greeting_file = File::open("hello.txt").expect("hello.txt should be included in this project");
     }
     {
+        // The book's steps towards the final version below are never called.
+        #[allow(dead_code, clippy::question_mark)]
         fn read_username_from_file() -> Result<String, io::Error>{
             let username_file_result = File::open("hello.txt");
 
@@ -59,6 +62,7 @@ fn main() {
         }
     }
     {
+        #[allow(dead_code)]
         fn read_username_from_file() -> Result<String, io::Error> {
             let mut username_file = File::open("hello.txt")?;
             let mut username = String::new();
@@ -67,6 +71,7 @@ fn main() {
         }
     }
     {
+        #[allow(dead_code)]
         fn read_username_from_file() -> Result<String, io::Error>{
             let mut username = String::new();
 
@@ -76,16 +81,20 @@ fn main() {
         }
     }
     {
+        #[allow(dead_code)]
         fn read_username_from_file() -> Result<String, io::Error>{
             fs::read_to_string("hello.txt")
         }
     }
     {
-        fn last_char_of_first_line(text: &str) -> Option<char> {
-            text.lines().next()?.chars().last()
+        // The final versions of read_username_from_file and last_char_of_first_line live in
+        // learning::files so other chapters can use them.
+        match learning::files::read_username_from_file("hello.txt") {
+            Ok(username) => println!("username: {:?}", username.trim()),
+            Err(e) => println!("Problem reading hello.txt: {e}"),
         }
 
-        let answer = last_char_of_first_line("Test Words");
+        let answer = learning::files::last_char_of_first_line("Test Words");
         println!("{:?}", answer);
     }
 
@@ -0,0 +1,166 @@
+#!/usr/bin/perl
+# Writes the .txt files next to this script from the copy of the Unicode Character Database that
+# ships with Perl (Unicode::UCD), for machines that can't download the upstream files:
+#
+#     perl learning/data/unicode/export_from_perl.pl
+#
+# The files keep the layout of the upstream ones, so examples/generate_unicode_tables.rs reads
+# either. GraphemeBreakTest.txt isn't written here; see the comment at its top.
+
+use strict;
+use warnings;
+use File::Basename qw(dirname);
+use Unicode::UCD qw(prop_invlist prop_invmap search_invlist);
+
+my $dir = dirname(__FILE__);
+my $version = Unicode::UCD::UnicodeVersion();
+my @source = (
+    "Not the upstream UCD file: exported from the copy of the Unicode Character Database that ships",
+    "with Perl (Unicode::UCD) and pinned at that copy's version.",
+);
+
+sub range_text {
+    my ($start, $end) = @_;
+    return $start == $end ? sprintf("%04X", $start) : sprintf("%04X..%04X", $start, $end);
+}
+
+sub open_with_header {
+    my ($name, $title, $format, @notes) = @_;
+    open my $fh, ">", "$dir/$name" or die "$dir/$name: $!";
+    print $fh "# $title\n#\n";
+    print $fh "# $_\n" for @source, @notes;
+    print $fh "#\n# Unicode $version. Format: $format # number of code points\n\n";
+    return $fh;
+}
+
+# Each value's ranges, in the order given, with a total after each group like upstream.
+sub write_groups {
+    my ($fh, $names, $ranges) = @_;
+    for my $name (@$names) {
+        my $total = 0;
+        for my $range (@{ $ranges->{$name} || [] }) {
+            my ($start, $end) = @$range;
+            printf $fh "%-14s; %s # %d\n", range_text($start, $end), $name, $end - $start + 1;
+            $total += $end - $start + 1;
+        }
+        print $fh "\n# Total code points: $total\n\n";
+    }
+}
+
+# value => [[start, end], ...] for a property, leaving out `skip`.
+sub ranges_by_value {
+    my ($property, $skip) = @_;
+    my ($starts, $values) = prop_invmap($property);
+    my %ranges;
+    for my $i (0 .. $#$starts) {
+        next if $values->[$i] eq $skip;
+        my $end = $i < $#$starts ? $starts->[ $i + 1 ] - 1 : 0x10FFFF;
+        push @{ $ranges{ $values->[$i] } }, [ $starts->[$i], $end ];
+    }
+    return \%ranges;
+}
+
+sub value_of {
+    my ($map, $code) = @_;
+    my ($starts, $values) = @$map;
+    return $values->[ search_invlist($starts, $code) ];
+}
+
+# Adds a code point to a list of ranges, joining it onto the last range where it follows on.
+sub add_code {
+    my ($ranges, $code) = @_;
+    if (@$ranges && $ranges->[-1][1] + 1 == $code) {
+        $ranges->[-1][1] = $code;
+    } else {
+        push @$ranges, [ $code, $code ];
+    }
+}
+
+my @breaks = qw(Prepend CR LF Control Extend Regional_Indicator SpacingMark L V T LV LVT ZWJ);
+my $gcb = ranges_by_value("Grapheme_Cluster_Break", "Other");
+my $fh = open_with_header(
+    "GraphemeBreakProperty.txt",
+    "Grapheme_Cluster_Break property values, in the layout of GraphemeBreakProperty.txt.",
+    "code point or range ; value",
+    "Code points not listed are Other.",
+);
+write_groups($fh, \@breaks, $gcb);
+close $fh;
+
+$fh = open_with_header(
+    "emoji-data.txt",
+    "Emoji properties, in the layout of emoji-data.txt.",
+    "code point or range ; value",
+    "Only the two properties the grapheme and width code use are included.",
+);
+for my $property (qw(Emoji_Presentation Extended_Pictographic)) {
+    my @list = prop_invlist($property);
+    my @ranges;
+    for (my $i = 0; $i < @list; $i += 2) {
+        push @ranges, [ $list[$i], $i + 1 < @list ? $list[ $i + 1 ] - 1 : 0x10FFFF ];
+    }
+    write_groups($fh, [$property], { $property => \@ranges });
+}
+close $fh;
+
+$fh = open_with_header(
+    "EastAsianWidth.txt",
+    "East_Asian_Width property values, in the layout of EastAsianWidth.txt.",
+    "code point or range ; value",
+    "Only Wide (W) and Fullwidth (F) are listed; everything else is narrow for our purposes.",
+);
+my ($width_starts, $width_values) = prop_invmap("East_Asian_Width");
+for my $i (0 .. $#$width_starts - 1) {
+    my $value = $width_values->[$i];
+    next unless $value eq "W" || $value eq "F";
+    my ($start, $end) = ($width_starts->[$i], $width_starts->[ $i + 1 ] - 1);
+    printf $fh "%-14s; %s # %d\n", range_text($start, $end), $value, $end - $start + 1;
+}
+close $fh;
+
+# Indic_Conjunct_Break arrived in Unicode 15.1, after Perl's copy, so it's derived here the way
+# DerivedCoreProperties.txt for 15.1 defines it:
+#
+#   Linker:    Indic_Syllabic_Category=Virama    in the six scripts below
+#   Consonant: Indic_Syllabic_Category=Consonant in the six scripts below
+#   Extend:    Grapheme_Cluster_Break=Extend with a nonzero Canonical_Combining_Class that isn't
+#              a Linker, and ZWJ
+my %conjunct_scripts = map { $_ => 1 } qw(Bengali Devanagari Gujarati Malayalam Oriya Telugu);
+my @script = prop_invmap("Script");
+my @ccc = prop_invmap("Canonical_Combining_Class");
+my $syllabic = ranges_by_value("Indic_Syllabic_Category", "Other");
+my %incb = (Linker => [], Consonant => [], Extend => []);
+for my $category (qw(Virama Consonant)) {
+    my $value = $category eq "Virama" ? "Linker" : "Consonant";
+    for my $range (@{ $syllabic->{$category} }) {
+        for my $code ($range->[0] .. $range->[1]) {
+            add_code($incb{$value}, $code) if $conjunct_scripts{ value_of(\@script, $code) };
+        }
+    }
+}
+my %linker = map { my ($s, $e) = @$_; map { $_ => 1 } $s .. $e } @{ $incb{Linker} };
+my @extend;
+for my $range (@{ $gcb->{Extend} }, @{ $gcb->{ZWJ} }) {
+    for my $code ($range->[0] .. $range->[1]) {
+        push @extend, $code if $code == 0x200D || (value_of(\@ccc, $code) != 0 && !$linker{$code});
+    }
+}
+add_code($incb{Extend}, $_) for sort { $a <=> $b } @extend;
+
+$fh = open_with_header(
+    "DerivedCoreProperties.txt",
+    "Indic_Conjunct_Break property values, in the layout of DerivedCoreProperties.txt.",
+    "code point or range ; InCB; value",
+    "Perl's copy predates Indic_Conjunct_Break (Unicode 15.1), so it is derived from the Unicode 14",
+    "properties with 15.1's definition; see export_from_perl.pl. Code points not listed are None.",
+);
+for my $value (qw(Linker Consonant Extend)) {
+    my $total = 0;
+    for my $range (@{ $incb{$value} }) {
+        my ($start, $end) = @$range;
+        printf $fh "%-14s; InCB; %s # %d\n", range_text($start, $end), $value, $end - $start + 1;
+        $total += $end - $start + 1;
+    }
+    print $fh "\n# Total code points: $total\n\n";
+}
+close $fh;
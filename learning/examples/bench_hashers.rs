@@ -0,0 +1,85 @@
+// Rough timings for the hashers in learning::hashing against std's SipHash, on word counting.
+//
+//     cargo run --release -p learning --example bench_hashers
+//
+// The text is random words from a made-up vocabulary, skewed so a few words are very common and
+// most are rare, as in real text. "count" is the bare entry().or_insert(0) loop over words that
+// are already split; "analyzer" is words::Analyzer, which also tokenizes and folds case.
+
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use learning::hashing::{FnvBuildHasher, FxBuildHasher, SeededState};
+use learning::words::{Analyzer, Options, StopWords};
+
+#[path = "../src/rng.rs"]
+mod rng;
+
+use rng::Rng;
+
+const WORDS: usize = 2_000_000;
+
+fn time<F: FnMut()>(mut f: F) -> Duration {
+    let start = Instant::now();
+    f();
+    start.elapsed()
+}
+
+fn count<S: BuildHasher>(words: &[&str], hasher: S) -> usize {
+    let mut counts = HashMap::with_hasher(hasher);
+    for word in words {
+        *counts.entry(*word).or_insert(0u64) += 1;
+    }
+    counts.len()
+}
+
+fn analyze<S: BuildHasher + Clone>(text: &str, hasher: S) -> usize {
+    let options = Options {
+        stop_words: StopWords::none(),
+        ..Options::default()
+    };
+    let mut analyzer = Analyzer::with_hasher(options, hasher);
+    analyzer.add_text(text);
+    analyzer.distinct_words()
+}
+
+// Times both workloads with one hasher and returns the distinct word counts each found.
+fn run<S>(vocabulary: usize, name: &str, words: &[&str], text: &str, hasher: S) -> (usize, usize)
+where
+    S: BuildHasher + Clone,
+{
+    let mut found = (0, 0);
+    let counting = time(|| found.0 = black_box(count(words, hasher.clone())));
+    let analyzing = time(|| found.1 = black_box(analyze(text, hasher.clone())));
+    println!("{:>10}  {:<10} {:>12?} {:>12?}", vocabulary, name, counting, analyzing);
+    found
+}
+
+fn main() {
+    println!("{:>10}  {:<10} {:>12} {:>12}", "vocabulary", "hasher", "count", "analyzer");
+
+    for vocabulary in [1_000, 100_000] {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        let dictionary: Vec<String> = (0..vocabulary)
+            .map(|_| {
+                let length = 2 + rng.below(9);
+                (0..length).map(|_| (b'a' + rng.below(26) as u8) as char).collect()
+            })
+            .collect();
+        let words: Vec<&str> = (0..WORDS).map(|_| dictionary[rng.skewed(vocabulary)].as_str()).collect();
+        let text = words.join(" ");
+
+        let distinct = [
+            run(vocabulary, "SipHash", &words, &text, RandomState::new()),
+            run(vocabulary, "Fx", &words, &text, FxBuildHasher::default()),
+            run(vocabulary, "FNV-1a", &words, &text, FnvBuildHasher::default()),
+            run(vocabulary, "Seeded", &words, &text, SeededState::new(42)),
+        ];
+
+        // Every hasher should find the same words.
+        assert!(distinct.windows(2).all(|pair| pair[0] == pair[1]));
+    }
+}
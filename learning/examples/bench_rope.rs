@@ -0,0 +1,87 @@
+// Rough timings for Rope against String when editing large text.
+//
+//     cargo run --release -p learning --example bench_rope
+//
+// Each run makes 10,000 small inserts and 10,000 small removals at random places, then one large
+// insert in the middle. The text is ASCII so String's byte offsets and Rope's char offsets match.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use learning::rope::Rope;
+
+#[path = "../src/rng.rs"]
+mod rng;
+
+use rng::Rng;
+
+const EDITS: usize = 10_000;
+
+fn time<F: FnMut()>(mut f: F) -> Duration {
+    let start = Instant::now();
+    f();
+    start.elapsed()
+}
+
+fn main() {
+    println!(
+        "{:>10}  {:<8} {:>12} {:>12} {:>12} {:>12}",
+        "size", "type", "inserts", "removals", "big insert", "to string"
+    );
+
+    let line = "The quick brown fox jumps over the lazy dog.\n";
+    for size in [10_000, 100_000, 1_000_000, 10_000_000] {
+        let document = line.repeat(size / line.len() + 1);
+        let big = line.repeat(100_000 / line.len());
+
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        let mut string = document.clone();
+        let inserts = time(|| {
+            for _ in 0..EDITS {
+                let at = rng.below(string.len() + 1);
+                string.insert_str(at, "edit");
+            }
+        });
+        let removals = time(|| {
+            for _ in 0..EDITS {
+                let at = rng.below(string.len() - 4);
+                string.replace_range(at..at + 4, "");
+            }
+        });
+        let big_insert = time(|| string.insert_str(string.len() / 2, &big));
+        let to_string = time(|| {
+            black_box(string.clone());
+        });
+        report(size, "String", inserts, removals, big_insert, to_string);
+
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        let mut rope = Rope::from(document.as_str());
+        let inserts = time(|| {
+            for _ in 0..EDITS {
+                let at = rng.below(rope.len_chars() + 1);
+                rope.insert(at, "edit");
+            }
+        });
+        let removals = time(|| {
+            for _ in 0..EDITS {
+                let at = rng.below(rope.len_chars() - 4);
+                rope.remove(at..at + 4);
+            }
+        });
+        let big_insert = time(|| rope.insert(rope.len_chars() / 2, &big));
+        let to_string = time(|| {
+            black_box(String::from(&rope));
+        });
+        report(size, "Rope", inserts, removals, big_insert, to_string);
+
+        // Both saw the same edits, so they should hold the same text.
+        assert!(rope == string.as_str());
+    }
+}
+
+fn report(size: usize, name: &str, inserts: Duration, removals: Duration, big: Duration, to_string: Duration) {
+    println!(
+        "{:>10}  {:<8} {:>12?} {:>12?} {:>12?} {:>12?}",
+        size, name, inserts, removals, big, to_string
+    );
+}
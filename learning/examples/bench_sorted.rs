@@ -0,0 +1,94 @@
+// Rough timings for SortedSet against BTreeSet at a few sizes.
+//
+//     cargo run --release -p learning --example bench_sorted
+
+use std::collections::BTreeSet;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use learning::sorted::SortedSet;
+
+#[path = "../src/rng.rs"]
+mod rng;
+
+use rng::Rng;
+
+fn time<F: FnMut()>(mut f: F) -> Duration {
+    let start = Instant::now();
+    f();
+    start.elapsed()
+}
+
+fn main() {
+    println!(
+        "{:>9}  {:<10} {:>12} {:>12} {:>12} {:>12}",
+        "size", "container", "insert", "contains", "range", "iterate"
+    );
+
+    for size in [10, 100, 1_000, 10_000, 100_000] {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        let values: Vec<u64> = (0..size).map(|_| rng.next() % (size as u64 * 4)).collect();
+        let probes: Vec<u64> = (0..10_000).map(|_| rng.next() % (size as u64 * 4)).collect();
+        // Repeat small sizes so the timings are big enough to read.
+        let rounds = (100_000 / size).max(1);
+
+        let mut sorted = SortedSet::new();
+        let insert = time(|| {
+            for _ in 0..rounds {
+                sorted = SortedSet::new();
+                for &v in &values {
+                    sorted.insert(v);
+                }
+            }
+        });
+        let contains = time(|| {
+            for &p in &probes {
+                black_box(sorted.contains(&p));
+            }
+        });
+        let range = time(|| {
+            for &p in &probes {
+                black_box(sorted.range(p..p + 100).len());
+            }
+        });
+        let iterate = time(|| {
+            for _ in 0..rounds {
+                black_box(sorted.iter().sum::<u64>());
+            }
+        });
+        report(size, "SortedSet", insert, contains, range, iterate);
+
+        let mut btree = BTreeSet::new();
+        let insert = time(|| {
+            for _ in 0..rounds {
+                btree = BTreeSet::new();
+                for &v in &values {
+                    btree.insert(v);
+                }
+            }
+        });
+        let contains = time(|| {
+            for &p in &probes {
+                black_box(btree.contains(&p));
+            }
+        });
+        let range = time(|| {
+            for &p in &probes {
+                black_box(btree.range(p..p + 100).count());
+            }
+        });
+        let iterate = time(|| {
+            for _ in 0..rounds {
+                black_box(btree.iter().sum::<u64>());
+            }
+        });
+        report(size, "BTreeSet", insert, contains, range, iterate);
+    }
+}
+
+fn report(size: usize, name: &str, insert: Duration, contains: Duration, range: Duration, iterate: Duration) {
+    println!(
+        "{:>9}  {:<10} {:>12?} {:>12?} {:>12?} {:>12?}",
+        size, name, insert, contains, range, iterate
+    );
+}
@@ -0,0 +1,210 @@
+// Regenerates learning/src/grapheme/tables.rs from the Unicode data files in learning/data/unicode.
+//
+//     cargo run -p learning --example generate_unicode_tables
+//
+// The files there are exported from Perl's copy of the Unicode 14 database by export_from_perl.pl.
+// The upstream GraphemeBreakProperty.txt, emoji-data.txt, EastAsianWidth.txt and
+// DerivedCoreProperties.txt from https://www.unicode.org/Public/ can replace them as they are.
+
+use std::fs;
+use std::path::Path;
+
+// (value in the data file, variant of grapheme::GraphemeBreak)
+const GRAPHEME_BREAK_NAMES: [(&str, &str); 13] = [
+    ("Prepend", "Prepend"),
+    ("CR", "Cr"),
+    ("LF", "Lf"),
+    ("Control", "Control"),
+    ("Extend", "Extend"),
+    ("Regional_Indicator", "RegionalIndicator"),
+    ("SpacingMark", "SpacingMark"),
+    ("L", "L"),
+    ("V", "V"),
+    ("T", "T"),
+    ("LV", "Lv"),
+    ("LVT", "Lvt"),
+    ("ZWJ", "Zwj"),
+];
+
+// (value in the data file, variant of grapheme::IndicConjunctBreak)
+const INDIC_CONJUNCT_BREAK_NAMES: [(&str, &str); 3] = [
+    ("Linker", "Linker"),
+    ("Consonant", "Consonant"),
+    ("Extend", "Extend"),
+];
+
+struct Entry {
+    start: u32,
+    end: u32,
+    value: String,
+}
+
+// The version a header line gives, as x.y.z: "# Unicode 14.0.0. Format: ..." in the exported
+// files, "# GraphemeBreakProperty-15.1.0.txt" or "# Used with Emoji Version 15.1 ..." upstream.
+fn version_in(line: &str) -> Option<String> {
+    let comment = line.strip_prefix("# ")?;
+    let rest = match comment
+        .strip_prefix("Unicode ")
+        .or_else(|| comment.strip_prefix("Used with Emoji Version "))
+    {
+        Some(rest) => rest,
+        None => {
+            let name = comment.strip_suffix(".txt")?;
+            &name[name.rfind('-')? + 1..]
+        }
+    };
+    let number = rest.split(|c: char| c != '.' && !c.is_ascii_digit()).next()?;
+    let mut parts: Vec<&str> = number.split('.').filter(|part| !part.is_empty()).collect();
+    if parts.is_empty() {
+        return None;
+    }
+    parts.resize(3, "0");
+    Some(parts.join("."))
+}
+
+fn parse(path: &Path) -> Result<(String, Vec<Entry>), String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut version = None;
+    let mut entries = Vec::new();
+
+    for (number, line) in text.lines().enumerate() {
+        if version.is_none() {
+            version = version_in(line);
+        }
+        let data = line.split('#').next().unwrap_or("").trim();
+        if data.is_empty() {
+            continue;
+        }
+
+        let bad = |what: &str| format!("{}:{}: {}", path.display(), number + 1, what);
+        let (range, value) = data.split_once(';').ok_or_else(|| bad("missing ';'"))?;
+        let (start, end) = match range.trim().split_once("..") {
+            Some((start, end)) => (start, end),
+            None => (range.trim(), range.trim()),
+        };
+        let hex = |s: &str| u32::from_str_radix(s, 16).map_err(|_| bad("bad code point"));
+        entries.push(Entry {
+            start: hex(start)?,
+            end: hex(end)?,
+            value: value.trim().to_string(),
+        });
+    }
+
+    let version = version.ok_or_else(|| format!("{}: no line saying which Unicode version it is", path.display()))?;
+    entries.sort_by_key(|entry| entry.start);
+    Ok((version, entries))
+}
+
+// Sorted, with neighbouring ranges of the same value joined together.
+fn merged<'a, I: Iterator<Item = &'a Entry>>(entries: I) -> Vec<(u32, u32, &'a str)> {
+    let mut ranges: Vec<(u32, u32, &str)> = Vec::new();
+    for entry in entries {
+        match ranges.last_mut() {
+            Some(last) if last.1 + 1 == entry.start && last.2 == entry.value => last.1 = entry.end,
+            _ => ranges.push((entry.start, entry.end, &entry.value)),
+        }
+    }
+    ranges
+}
+
+fn range_table(out: &mut String, name: &str, entries: &[Entry], value: &str) {
+    let ranges = merged(entries.iter().filter(|entry| entry.value == value));
+    out.push_str(&format!("pub(super) const {}: &[(u32, u32)] = &[\n", name));
+    for (start, end, _) in ranges {
+        out.push_str(&format!("    (0x{:04X}, 0x{:04X}),\n", start, end));
+    }
+    out.push_str("];\n\n");
+}
+
+// Ranges with one of the enum's variants each. Values not in `names` are an error.
+fn value_table(
+    out: &mut String,
+    name: &str,
+    enum_name: &str,
+    names: &[(&str, &str)],
+    entries: &[Entry],
+) -> Result<(), String> {
+    out.push_str(&format!("pub(super) const {}: &[(u32, u32, {})] = &[\n", name, enum_name));
+    for (start, end, value) in merged(entries.iter()) {
+        let variant = names
+            .iter()
+            .find(|(name, _)| *name == value)
+            .map(|(_, variant)| variant)
+            .ok_or_else(|| format!("unknown {} value '{}'", enum_name, value))?;
+        out.push_str(&format!("    (0x{:04X}, 0x{:04X}, {}::{}),\n", start, end, enum_name, variant));
+    }
+    out.push_str("];\n\n");
+    Ok(())
+}
+
+fn run() -> Result<(), String> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let data = root.join("data/unicode");
+    let (version, breaks) = parse(&data.join("GraphemeBreakProperty.txt"))?;
+    let (emoji_version, emoji) = parse(&data.join("emoji-data.txt"))?;
+    let (width_version, widths) = parse(&data.join("EastAsianWidth.txt"))?;
+    let (core_version, core) = parse(&data.join("DerivedCoreProperties.txt"))?;
+    if [&emoji_version, &width_version, &core_version].iter().any(|&other| *other != version) {
+        return Err(format!(
+            "data files disagree on the Unicode version: {}, {}, {}, {}",
+            version, emoji_version, width_version, core_version
+        ));
+    }
+
+    let mut out = String::new();
+    out.push_str("// Generated by `cargo run -p learning --example generate_unicode_tables` from the files in\n");
+    out.push_str("// learning/data/unicode. Don't edit by hand.\n\n");
+    out.push_str("use super::{GraphemeBreak, IndicConjunctBreak};\n\n");
+
+    let numbers: Vec<u32> = version.split('.').map(|n| n.parse().unwrap_or(0)).collect();
+    out.push_str(&format!(
+        "pub const UNICODE_VERSION: (u32, u32, u32) = ({}, {}, {});\n\n",
+        numbers.first().unwrap_or(&0),
+        numbers.get(1).unwrap_or(&0),
+        numbers.get(2).unwrap_or(&0)
+    ));
+
+    value_table(&mut out, "GRAPHEME_BREAK", "GraphemeBreak", &GRAPHEME_BREAK_NAMES, &breaks)?;
+    // Only the Indic_Conjunct_Break lines of DerivedCoreProperties.txt, which look like
+    // "0915..0939 ; InCB; Consonant".
+    let conjunct: Vec<Entry> = core
+        .into_iter()
+        .filter_map(|entry| {
+            let value = entry.value.strip_prefix("InCB;")?.trim().to_string();
+            Some(Entry { value, ..entry })
+        })
+        .collect();
+    value_table(
+        &mut out,
+        "INDIC_CONJUNCT_BREAK",
+        "IndicConjunctBreak",
+        &INDIC_CONJUNCT_BREAK_NAMES,
+        &conjunct,
+    )?;
+
+    range_table(&mut out, "EXTENDED_PICTOGRAPHIC", &emoji, "Extended_Pictographic");
+    range_table(&mut out, "EMOJI_PRESENTATION", &emoji, "Emoji_Presentation");
+
+    // Wide and Fullwidth are both two columns, so they share a table.
+    let wide: Vec<Entry> = widths
+        .into_iter()
+        .filter(|entry| entry.value == "W" || entry.value == "F")
+        .map(|entry| Entry {
+            value: String::from("wide"),
+            ..entry
+        })
+        .collect();
+    range_table(&mut out, "WIDE", &wide, "wide");
+
+    let target = root.join("src/grapheme/tables.rs");
+    fs::write(&target, out.trim_end().to_string() + "\n").map_err(|e| format!("{}: {}", target.display(), e))?;
+    println!("Wrote {} (Unicode {})", target.display(), version);
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,227 @@
+// Bounds-checked access for slices and Vecs.
+//
+// Chapter 8 shows the two built-in choices: `&v[100]` panics and `v.get(100)` returns a bare
+// None. The CheckedAccess methods return an IndexError instead, which remembers what was asked
+// for and how long the collection was, so the message can say what went wrong and what to try.
+
+use std::fmt;
+use std::ops::{Bound, RangeBounds};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexError {
+    OutOfBounds { index: usize, len: usize },
+    /// `end` is exclusive.
+    BadRange { start: usize, end: usize, len: usize },
+    /// Multi-index mutable access asked for the same element twice.
+    Duplicate { index: usize },
+}
+
+impl IndexError {
+    /// Length of the collection at the time of the failed access.
+    pub fn collection_len(&self) -> Option<usize> {
+        match self {
+            IndexError::OutOfBounds { len, .. } | IndexError::BadRange { len, .. } => Some(*len),
+            IndexError::Duplicate { .. } => None,
+        }
+    }
+
+    /// A hint for fixing the access.
+    pub fn suggestion(&self) -> String {
+        match *self {
+            IndexError::OutOfBounds { len: 0, .. } | IndexError::BadRange { len: 0, .. } => {
+                String::from("the collection is empty; check is_empty() first")
+            }
+            IndexError::OutOfBounds { index, len } if index == len => {
+                format!("indexes start at 0, so the last element is at {}", len - 1)
+            }
+            IndexError::OutOfBounds { len, .. } => {
+                format!("use an index from 0 to {}, or get() to handle a missing element", len - 1)
+            }
+            IndexError::BadRange { start, end, .. } if start > end => {
+                format!("the range runs backwards; did you mean {}..{}?", end, start)
+            }
+            IndexError::BadRange { len, .. } => {
+                format!("ranges must stay within 0..{}", len)
+            }
+            IndexError::Duplicate { .. } => {
+                String::from("each index can only be borrowed mutably once")
+            }
+        }
+    }
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IndexError::OutOfBounds { index, len } => {
+                write!(f, "index {} is out of bounds for length {}", index, len)?
+            }
+            IndexError::BadRange { start, end, len } => {
+                write!(f, "range {}..{} is invalid for length {}", start, end, len)?
+            }
+            IndexError::Duplicate { index } => write!(f, "index {} was requested twice", index)?,
+        }
+        write!(f, " ({})", self.suggestion())
+    }
+}
+
+impl std::error::Error for IndexError {}
+
+pub trait CheckedAccess<T> {
+    fn try_get(&self, index: usize) -> Result<&T, IndexError>;
+    fn try_get_mut(&mut self, index: usize) -> Result<&mut T, IndexError>;
+    fn try_range<R: RangeBounds<usize>>(&self, range: R) -> Result<&[T], IndexError>;
+    fn try_range_mut<R: RangeBounds<usize>>(&mut self, range: R) -> Result<&mut [T], IndexError>;
+    /// Several elements at once. Fails on the first index that is out of bounds.
+    fn try_get_many<const N: usize>(&self, indexes: [usize; N]) -> Result<[&T; N], IndexError>;
+    /// Several elements at once, mutably. The indexes must all be different.
+    fn try_get_many_mut<const N: usize>(
+        &mut self,
+        indexes: [usize; N],
+    ) -> Result<[&mut T; N], IndexError>;
+}
+
+impl<T> CheckedAccess<T> for [T] {
+    fn try_get(&self, index: usize) -> Result<&T, IndexError> {
+        let len = self.len();
+        self.get(index).ok_or(IndexError::OutOfBounds { index, len })
+    }
+
+    fn try_get_mut(&mut self, index: usize) -> Result<&mut T, IndexError> {
+        let len = self.len();
+        self.get_mut(index).ok_or(IndexError::OutOfBounds { index, len })
+    }
+
+    fn try_range<R: RangeBounds<usize>>(&self, range: R) -> Result<&[T], IndexError> {
+        let (start, end) = resolve(&range, self.len())?;
+        Ok(&self[start..end])
+    }
+
+    fn try_range_mut<R: RangeBounds<usize>>(&mut self, range: R) -> Result<&mut [T], IndexError> {
+        let (start, end) = resolve(&range, self.len())?;
+        Ok(&mut self[start..end])
+    }
+
+    fn try_get_many<const N: usize>(&self, indexes: [usize; N]) -> Result<[&T; N], IndexError> {
+        for &index in &indexes {
+            self.try_get(index)?;
+        }
+        Ok(indexes.map(|index| &self[index]))
+    }
+
+    fn try_get_many_mut<const N: usize>(
+        &mut self,
+        indexes: [usize; N],
+    ) -> Result<[&mut T; N], IndexError> {
+        for (i, &index) in indexes.iter().enumerate() {
+            self.try_get(index)?;
+            if indexes[..i].contains(&index) {
+                return Err(IndexError::Duplicate { index });
+            }
+        }
+        // Already checked above, so this can't fail.
+        self.get_disjoint_mut(indexes)
+            .map_err(|_| IndexError::Duplicate { index: indexes[0] })
+    }
+}
+
+// Turns any range into start..end, checking it against the length.
+fn resolve<R: RangeBounds<usize>>(range: &R, len: usize) -> Result<(usize, usize), IndexError> {
+    let (start, end) = bounds(range, len);
+    if start > end || end > len {
+        return Err(IndexError::BadRange { start, end, len });
+    }
+    Ok((start, end))
+}
+
+/// Turns any range into start and end offsets for a collection of `len` elements, without
+/// checking them. Bounds saturate, so `..=usize::MAX` ends at usize::MAX instead of overflowing
+/// and the caller's check catches it.
+pub(crate) fn bounds<R: RangeBounds<usize>>(range: &R, len: usize) -> (usize, usize) {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.saturating_add(1),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end.saturating_add(1),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    (start, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ops::Bound;
+
+    #[test]
+    fn messages_say_what_to_try() {
+        let mut v = [10, 20, 30];
+        assert_eq!(
+            v.try_get(3).unwrap_err().to_string(),
+            "index 3 is out of bounds for length 3 (indexes start at 0, so the last element is at 2)"
+        );
+        assert_eq!(
+            v.try_get(7).unwrap_err().to_string(),
+            "index 7 is out of bounds for length 3 (use an index from 0 to 2, or get() to handle a missing element)"
+        );
+        let empty: Vec<i32> = Vec::new();
+        assert_eq!(
+            empty.try_get(0).unwrap_err().to_string(),
+            "index 0 is out of bounds for length 0 (the collection is empty; check is_empty() first)"
+        );
+        assert_eq!(
+            v.try_range((Bound::Included(2), Bound::Excluded(1))).unwrap_err().to_string(),
+            "range 2..1 is invalid for length 3 (the range runs backwards; did you mean 1..2?)"
+        );
+        assert_eq!(
+            v.try_range(1..=3).unwrap_err().to_string(),
+            "range 1..4 is invalid for length 3 (ranges must stay within 0..3)"
+        );
+        assert_eq!(
+            v.try_get_many_mut([1, 1]).unwrap_err().to_string(),
+            "index 1 was requested twice (each index can only be borrowed mutably once)"
+        );
+        assert_eq!(v.try_get(5).unwrap_err().collection_len(), Some(3));
+        assert_eq!(IndexError::Duplicate { index: 0 }.collection_len(), None);
+    }
+
+    #[test]
+    fn range_edges() {
+        let v = [1, 2, 3];
+        assert_eq!(v.try_range(..), Ok(&v[..]));
+        assert_eq!(v.try_range(3..), Ok(&[][..]));
+        assert_eq!(v.try_range(0..0), Ok(&[][..]));
+        assert_eq!(v.try_range(..=2), Ok(&v[..]));
+        assert_eq!(v.try_range((Bound::Excluded(0), Bound::Unbounded)), Ok(&v[1..]));
+        assert_eq!(v.try_range(4..), Err(IndexError::BadRange { start: 4, end: 3, len: 3 }));
+        // Bounds saturate instead of overflowing.
+        assert_eq!(
+            v.try_range(..=usize::MAX),
+            Err(IndexError::BadRange { start: 0, end: usize::MAX, len: 3 })
+        );
+        assert_eq!(
+            v.try_range((Bound::Excluded(usize::MAX), Bound::Unbounded)),
+            Err(IndexError::BadRange { start: usize::MAX, end: 3, len: 3 })
+        );
+    }
+
+    #[test]
+    fn many_at_once() {
+        let mut v = vec![1, 2, 3];
+        assert_eq!(v.try_get_many([2, 0]), Ok([&3, &1]));
+        assert_eq!(v.try_get_many([0, 3]), Err(IndexError::OutOfBounds { index: 3, len: 3 }));
+        let [a, b] = v.try_get_many_mut([0, 2]).unwrap();
+        std::mem::swap(a, b);
+        assert_eq!(v, [3, 2, 1]);
+        // Out of bounds is reported before a duplicate that comes later.
+        assert_eq!(
+            v.try_get_many_mut([5, 5]),
+            Err(IndexError::OutOfBounds { index: 5, len: 3 })
+        );
+        *v.try_get_mut(1).unwrap() = 9;
+        assert_eq!(v.try_range_mut(1..).unwrap(), [9, 1]);
+    }
+}
@@ -0,0 +1,470 @@
+// A Vec with a fixed capacity that lives inline, so it never allocates.
+//
+// The storage is `[MaybeUninit<T>; N]` plus a length. Only the first `len` slots are initialized,
+// and every unsafe block below relies on keeping that true.
+
+use std::fmt;
+use std::mem::{self, MaybeUninit};
+use std::ops::{Deref, DerefMut, RangeBounds};
+use std::ptr;
+
+use crate::access;
+
+/// Returned when pushing or inserting into a full ArrayVec. Holds the element that didn't fit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityError<T>(pub T);
+
+impl<T> fmt::Display for CapacityError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the ArrayVec is full")
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for CapacityError<T> {}
+
+pub struct ArrayVec<T, const N: usize> {
+    items: [MaybeUninit<T>; N],
+    len: usize,
+}
+
+impl<T, const N: usize> ArrayVec<T, N> {
+    pub fn new() -> ArrayVec<T, N> {
+        ArrayVec {
+            items: [const { MaybeUninit::uninit() }; N],
+            len: 0,
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    pub fn remaining_capacity(&self) -> usize {
+        N - self.len
+    }
+
+    pub fn push(&mut self, item: T) -> Result<(), CapacityError<T>> {
+        if self.is_full() {
+            return Err(CapacityError(item));
+        }
+        self.items[self.len].write(item);
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        // SAFETY: slot `len` was initialized, and lowering len first means it won't be read or
+        // dropped again.
+        Some(unsafe { self.items[self.len].assume_init_read() })
+    }
+
+    /// Inserts at `index`, shifting later elements right.
+    ///
+    /// Panics if `index > len`, like `Vec::insert`.
+    pub fn insert(&mut self, index: usize, item: T) -> Result<(), CapacityError<T>> {
+        assert!(index <= self.len, "insertion index {} is past the end ({})", index, self.len);
+        if self.is_full() {
+            return Err(CapacityError(item));
+        }
+
+        // SAFETY: index <= len < N, so both ranges fit in the array. ptr::copy handles overlap.
+        unsafe {
+            let base = self.items.as_mut_ptr();
+            ptr::copy(base.add(index), base.add(index + 1), self.len - index);
+        }
+        self.items[index].write(item);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Removes the element at `index`, shifting later elements left. `None` if out of bounds.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.len {
+            return None;
+        }
+
+        // SAFETY: index < len, so the slot is initialized. After reading it out the tail is moved
+        // down over it and len shrinks by one, so nothing is dropped twice.
+        unsafe {
+            let item = self.items[index].assume_init_read();
+            let base = self.items.as_mut_ptr();
+            ptr::copy(base.add(index + 1), base.add(index), self.len - index - 1);
+            self.len -= 1;
+            Some(item)
+        }
+    }
+
+    /// Removes the element at `index` by moving the last element into its place.
+    pub fn swap_remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.len {
+            return None;
+        }
+        let last = self.len - 1;
+        self.as_mut_slice().swap(index, last);
+        self.pop()
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        while self.len > len {
+            self.pop();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY: the first len slots are initialized, and MaybeUninit<T> has T's layout.
+        unsafe { std::slice::from_raw_parts(self.items.as_ptr() as *const T, self.len) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY: as for as_slice.
+        unsafe { std::slice::from_raw_parts_mut(self.items.as_mut_ptr() as *mut T, self.len) }
+    }
+
+    /// Removes the given range and yields it in order. The drained elements are moved into a
+    /// second ArrayVec first, so this doesn't allocate either.
+    ///
+    /// Panics if the range is out of bounds, like `Vec::drain`.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> IntoIter<T, N> {
+        let (start, end) = access::bounds(&range, self.len);
+        assert!(start <= end && end <= self.len, "drain range {}..{} is out of bounds", start, end);
+
+        let count = end - start;
+        let mut drained = ArrayVec::new();
+        // SAFETY: slots start..end are initialized. They're copied bitwise into `drained`, which
+        // has room since count <= len <= N, then the tail is moved down over the gap and len
+        // shrinks, so every element is owned by exactly one of the two afterwards.
+        unsafe {
+            let base = self.items.as_mut_ptr();
+            ptr::copy_nonoverlapping(base.add(start), drained.items.as_mut_ptr(), count);
+            drained.len = count;
+            ptr::copy(base.add(end), base.add(start), self.len - end);
+        }
+        self.len -= count;
+        drained.into_iter()
+    }
+}
+
+impl<T, const N: usize> Default for ArrayVec<T, N> {
+    fn default() -> ArrayVec<T, N> {
+        ArrayVec::new()
+    }
+}
+
+impl<T, const N: usize> Drop for ArrayVec<T, N> {
+    fn drop(&mut self) {
+        // SAFETY: drops exactly the initialized prefix.
+        unsafe { ptr::drop_in_place(self.as_mut_slice()) }
+    }
+}
+
+impl<T, const N: usize> Deref for ArrayVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, const N: usize> DerefMut for ArrayVec<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for ArrayVec<T, N> {
+    fn clone(&self) -> ArrayVec<T, N> {
+        let mut copy = ArrayVec::new();
+        for item in self.iter() {
+            let _ = copy.push(item.clone());
+        }
+        copy
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for ArrayVec<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for ArrayVec<T, N> {
+    fn eq(&self, other: &ArrayVec<T, N>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for ArrayVec<T, N> {}
+
+/// Panics if the ArrayVec runs out of room, since Extend has no way to report an error. Check
+/// `remaining_capacity` first, or push one at a time.
+impl<T, const N: usize> Extend<T> for ArrayVec<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            if self.push(item).is_err() {
+                panic!("ArrayVec capacity {} exceeded", N);
+            }
+        }
+    }
+}
+
+/// Panics if the iterator yields more than N elements.
+impl<T, const N: usize> FromIterator<T> for ArrayVec<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> ArrayVec<T, N> {
+        let mut array = ArrayVec::new();
+        array.extend(iter);
+        array
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a ArrayVec<T, N> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> std::slice::Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut ArrayVec<T, N> {
+    type Item = &'a mut T;
+    type IntoIter = std::slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> std::slice::IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+impl<T, const N: usize> IntoIterator for ArrayVec<T, N> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(self) -> IntoIter<T, N> {
+        let mut array = mem::ManuallyDrop::new(self);
+        let len = array.len;
+        // SAFETY: ownership of the storage moves into the iterator, and the ArrayVec is never
+        // dropped, so each initialized element is dropped by IntoIter alone.
+        let items = unsafe { ptr::read(&array.items) };
+        array.len = 0;
+        IntoIter {
+            items,
+            front: 0,
+            back: len,
+        }
+    }
+}
+
+/// Owning iterator. Slots `front..back` are still initialized.
+pub struct IntoIter<T, const N: usize> {
+    items: [MaybeUninit<T>; N],
+    front: usize,
+    back: usize,
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.front == self.back {
+            return None;
+        }
+        // SAFETY: front < back, so the slot is initialized, and moving front past it means it
+        // won't be read again.
+        let item = unsafe { self.items[self.front].assume_init_read() };
+        self.front += 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.back - self.front;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        // SAFETY: as for next.
+        Some(unsafe { self.items[self.back].assume_init_read() })
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+
+impl<T, const N: usize> Drop for IntoIter<T, N> {
+    fn drop(&mut self) {
+        for item in &mut self.items[self.front..self.back] {
+            // SAFETY: the remaining front..back slots are initialized and never read again.
+            unsafe { item.assume_init_drop() }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    const N: usize = 16;
+
+    // Counts how many were made and how many dropped, so leaks and double drops both show up.
+    #[derive(Clone, Default)]
+    struct Counts {
+        made: Rc<Cell<usize>>,
+        dropped: Rc<Cell<usize>>,
+    }
+
+    struct Tracked {
+        value: usize,
+        dropped: Rc<Cell<usize>>,
+    }
+
+    impl Counts {
+        fn make(&self, value: usize) -> Tracked {
+            self.made.set(self.made.get() + 1);
+            Tracked { value, dropped: Rc::clone(&self.dropped) }
+        }
+
+        fn live(&self) -> usize {
+            self.made.get() - self.dropped.get()
+        }
+    }
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.dropped.set(self.dropped.get() + 1);
+        }
+    }
+
+    fn values(items: &[Tracked]) -> Vec<usize> {
+        items.iter().map(|item| item.value).collect()
+    }
+
+    #[test]
+    fn random_operations_match_vec() {
+        for seed in 1..=50u64 {
+            let mut rng = Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+            let counts = Counts::default();
+            let mut array: ArrayVec<Tracked, N> = ArrayVec::new();
+            let mut oracle: Vec<usize> = Vec::new();
+
+            for step in 0..500 {
+                let len = oracle.len();
+                match rng.below(8) {
+                    0 | 1 => match array.push(counts.make(step)) {
+                        Ok(()) => oracle.push(step),
+                        Err(CapacityError(item)) => {
+                            assert_eq!(len, N);
+                            assert_eq!(item.value, step);
+                        }
+                    },
+                    2 => assert_eq!(array.pop().map(|item| item.value), oracle.pop()),
+                    3 => {
+                        let index = rng.below(len + 1);
+                        match array.insert(index, counts.make(step)) {
+                            Ok(()) => oracle.insert(index, step),
+                            Err(_) => assert_eq!(len, N),
+                        }
+                    }
+                    4 => {
+                        let index = rng.below(len + 2);
+                        let expected = if index < len { Some(oracle.remove(index)) } else { None };
+                        assert_eq!(array.remove(index).map(|item| item.value), expected);
+                    }
+                    5 => {
+                        let index = rng.below(len + 2);
+                        let expected = if index < len { Some(oracle.swap_remove(index)) } else { None };
+                        assert_eq!(array.swap_remove(index).map(|item| item.value), expected);
+                    }
+                    6 => {
+                        let keep = rng.below(len + 2);
+                        array.truncate(keep);
+                        oracle.truncate(keep);
+                    }
+                    _ => {
+                        let end = rng.below(len + 1);
+                        let start = rng.below(end + 1);
+                        let mut drained = array.drain(start..end);
+                        let expected: Vec<usize> = oracle.drain(start..end).collect();
+                        // Sometimes leave part of it unread, for IntoIter's Drop to clean up.
+                        let take = rng.below(expected.len() + 1);
+                        let got: Vec<usize> = drained.by_ref().take(take).map(|item| item.value).collect();
+                        assert_eq!(got, expected[..take]);
+                    }
+                }
+                assert_eq!(values(&array), oracle);
+                assert_eq!(counts.live(), oracle.len());
+            }
+
+            // Take some from each end and drop the rest unread.
+            let mut iter = array.into_iter();
+            let front = rng.below(oracle.len() + 1);
+            let back = rng.below(oracle.len() - front + 1);
+            for expected in &oracle[..front] {
+                assert_eq!(iter.next().map(|item| item.value), Some(*expected));
+            }
+            for expected in oracle[front..].iter().rev().take(back) {
+                assert_eq!(iter.next_back().map(|item| item.value), Some(*expected));
+            }
+            assert_eq!(iter.len(), oracle.len() - front - back);
+            drop(iter);
+            assert_eq!(counts.live(), 0);
+        }
+    }
+
+    #[test]
+    fn drain_moves_the_tail_down() {
+        let mut array: ArrayVec<u32, 8> = (0..8).collect();
+        let drained: Vec<u32> = array.drain(2..5).collect();
+        assert_eq!(drained, [2, 3, 4]);
+        assert_eq!(array.as_slice(), [0, 1, 5, 6, 7]);
+        assert_eq!(array.drain(..).collect::<Vec<_>>(), [0, 1, 5, 6, 7]);
+        assert!(array.is_empty());
+        assert_eq!(array.drain(0..0).count(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn drain_past_the_end_panics() {
+        let mut array: ArrayVec<u32, 4> = (0..2).collect();
+        array.drain(1..3);
+    }
+
+    #[test]
+    fn dropping_drops_every_element_once() {
+        let counts = Counts::default();
+        {
+            let mut array: ArrayVec<Tracked, 4> = ArrayVec::new();
+            for value in 0..4 {
+                assert!(array.push(counts.make(value)).is_ok());
+            }
+            assert_eq!(values(&array), [0, 1, 2, 3]);
+        }
+        assert_eq!(counts.live(), 0);
+        assert_eq!(counts.made.get(), 4);
+    }
+}
@@ -0,0 +1,784 @@
+// Change making, the other direction of chapter 6's `value_in_cents`.
+//
+// Amounts are whole minor units (cents for USD). A Currency is just a table of denominations, so
+// other currencies only need a different table. A Till holds a limited number of each
+// denomination and hands out change using as few pieces as possible.
+//
+// Most currencies are canonical: taking the largest piece that fits, over and over, already gives
+// the fewest pieces. Currency::new checks for that, and the Till uses it whenever it has enough of
+// each piece, so it only falls back to the exact search, which grows with the amount, when it runs
+// short of something.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::quarters::UsState;
+
+/// Chapter 6's coins, with the state on the back of a quarter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Coin {
+    Penny,
+    Nickel,
+    Dime,
+    Quarter(UsState),
+}
+
+impl Coin {
+    pub fn value_in_cents(self) -> u8 {
+        match self {
+            Coin::Penny => 1,
+            Coin::Nickel => 5,
+            Coin::Dime => 10,
+            Coin::Quarter(_) => 25,
+        }
+    }
+
+    /// The matching entry name in `Currency::usd()`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Coin::Penny => "penny",
+            Coin::Nickel => "nickel",
+            Coin::Dime => "dime",
+            Coin::Quarter(_) => "quarter",
+        }
+    }
+}
+
+/// Totals a bag of US coins in cents.
+pub fn total_cents<I: IntoIterator<Item = Coin>>(coins: I) -> u64 {
+    coins.into_iter().map(|coin| coin.value_in_cents() as u64).sum()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    Coin,
+    Bill,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Denomination {
+    pub name: String,
+    /// Value in minor units.
+    pub value: u64,
+    pub kind: Kind,
+}
+
+impl Denomination {
+    pub fn coin(name: &str, value: u64) -> Denomination {
+        Denomination {
+            name: String::from(name),
+            value,
+            kind: Kind::Coin,
+        }
+    }
+
+    pub fn bill(name: &str, value: u64) -> Denomination {
+        Denomination {
+            name: String::from(name),
+            value,
+            kind: Kind::Bill,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Currency {
+    pub code: String,
+    pub symbol: String,
+    /// Number of minor units in one major unit, e.g. 100 cents in a dollar.
+    pub minor_per_major: u64,
+    denominations: Vec<Denomination>,
+    // Whether taking the largest denomination that fits gives the fewest pieces for every amount.
+    greedy_is_optimal: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeError {
+    /// The till holds enough money, but no combination of it adds up to the amount.
+    ImpossibleExactChange { amount: u64 },
+    InsufficientFunds { amount: u64, available: u64 },
+    UnknownDenomination(String),
+    DuplicateDenomination(String),
+    ZeroDenomination(String),
+    /// Adding `count` more of a denomination would take its count past u32::MAX.
+    CountOverflow { name: String, count: u32 },
+    /// The till is short of some denomination, so planning change needs the exact search, which
+    /// takes time and memory in proportion to the amount divided by the greatest common divisor
+    /// of the denominations on hand, and `steps` is more than MAX_CHANGE_STEPS.
+    AmountTooLarge { amount: u64, steps: u64 },
+}
+
+/// The most steps the exact search in `Till::plan_change` will work through. A step is the
+/// greatest common divisor of the denominations in the till, so with US pennies on hand the limit
+/// is $10,000.00, and without pennies or nickels it is $100,000.00. A till that has enough of each
+/// piece for the largest-first answer doesn't need the search, and has no limit.
+pub const MAX_CHANGE_STEPS: u64 = 1_000_000;
+
+impl fmt::Display for ChangeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChangeError::ImpossibleExactChange { amount } => {
+                write!(f, "can't make exact change for {}", amount)
+            }
+            ChangeError::InsufficientFunds { amount, available } => {
+                write!(f, "need {} but the till only holds {}", amount, available)
+            }
+            ChangeError::UnknownDenomination(name) => write!(f, "unknown denomination '{}'", name),
+            ChangeError::DuplicateDenomination(name) => {
+                write!(f, "denomination '{}' is listed twice", name)
+            }
+            ChangeError::ZeroDenomination(name) => write!(f, "denomination '{}' is worth nothing", name),
+            ChangeError::CountOverflow { name, count } => {
+                write!(f, "can't add {} more of '{}': too many to count", count, name)
+            }
+            ChangeError::AmountTooLarge { amount, steps } => write!(
+                f,
+                "can't plan change for {}: it takes {} steps and the limit is {}",
+                amount, steps, MAX_CHANGE_STEPS
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ChangeError {}
+
+impl Currency {
+    /// Denominations can be given in any order; they are kept largest first.
+    pub fn new(
+        code: &str,
+        symbol: &str,
+        minor_per_major: u64,
+        mut denominations: Vec<Denomination>,
+    ) -> Result<Currency, ChangeError> {
+        let mut seen = HashSet::new();
+        for denomination in &denominations {
+            if denomination.value == 0 {
+                return Err(ChangeError::ZeroDenomination(denomination.name.clone()));
+            }
+            if !seen.insert(denomination.name.as_str()) {
+                return Err(ChangeError::DuplicateDenomination(denomination.name.clone()));
+            }
+        }
+
+        denominations.sort_by_key(|d| std::cmp::Reverse(d.value));
+        let greedy_is_optimal = greedy_is_optimal(&denominations);
+        Ok(Currency {
+            code: String::from(code),
+            symbol: String::from(symbol),
+            minor_per_major,
+            denominations,
+            greedy_is_optimal,
+        })
+    }
+
+    pub fn usd() -> Currency {
+        Currency::new(
+            "USD",
+            "$",
+            100,
+            vec![
+                Denomination::coin("penny", 1),
+                Denomination::coin("nickel", 5),
+                Denomination::coin("dime", 10),
+                Denomination::coin("quarter", 25),
+                Denomination::coin("half dollar", 50),
+                Denomination::coin("dollar coin", 100),
+                Denomination::bill("$1", 100),
+                Denomination::bill("$5", 500),
+                Denomination::bill("$10", 1000),
+                Denomination::bill("$20", 2000),
+                Denomination::bill("$50", 5000),
+                Denomination::bill("$100", 10000),
+            ],
+        )
+        .expect("built-in USD table is valid")
+    }
+
+    pub fn eur() -> Currency {
+        Currency::new(
+            "EUR",
+            "€",
+            100,
+            vec![
+                Denomination::coin("1c", 1),
+                Denomination::coin("2c", 2),
+                Denomination::coin("5c", 5),
+                Denomination::coin("10c", 10),
+                Denomination::coin("20c", 20),
+                Denomination::coin("50c", 50),
+                Denomination::coin("€1", 100),
+                Denomination::coin("€2", 200),
+                Denomination::bill("€5", 500),
+                Denomination::bill("€10", 1000),
+                Denomination::bill("€20", 2000),
+                Denomination::bill("€50", 5000),
+                Denomination::bill("€100", 10000),
+                Denomination::bill("€200", 20000),
+            ],
+        )
+        .expect("built-in EUR table is valid")
+    }
+
+    pub fn gbp() -> Currency {
+        Currency::new(
+            "GBP",
+            "£",
+            100,
+            vec![
+                Denomination::coin("1p", 1),
+                Denomination::coin("2p", 2),
+                Denomination::coin("5p", 5),
+                Denomination::coin("10p", 10),
+                Denomination::coin("20p", 20),
+                Denomination::coin("50p", 50),
+                Denomination::coin("£1", 100),
+                Denomination::coin("£2", 200),
+                Denomination::bill("£5", 500),
+                Denomination::bill("£10", 1000),
+                Denomination::bill("£20", 2000),
+                Denomination::bill("£50", 5000),
+            ],
+        )
+        .expect("built-in GBP table is valid")
+    }
+
+    /// Largest first.
+    pub fn denominations(&self) -> &[Denomination] {
+        &self.denominations
+    }
+
+    pub fn find(&self, name: &str) -> Option<&Denomination> {
+        self.denominations.iter().find(|d| d.name == name)
+    }
+
+    fn index_of(&self, name: &str) -> Result<usize, ChangeError> {
+        self.denominations
+            .iter()
+            .position(|d| d.name == name)
+            .ok_or_else(|| ChangeError::UnknownDenomination(String::from(name)))
+    }
+
+    /// Formats minor units as e.g. `$12.05`. Minor units that aren't a power of ten can't be
+    /// written as decimals, so they're shown as a fraction instead: 17 iraimbilanja, five to the
+    /// ariary, is `Ar3 2/5`.
+    pub fn format(&self, amount: u64) -> String {
+        let per = self.minor_per_major;
+        if per <= 1 {
+            return format!("{}{}", self.symbol, amount);
+        }
+        let (major, minor) = (amount / per, amount % per);
+        let mut power = 1u64;
+        let mut digits = 0;
+        while power < per {
+            match power.checked_mul(10) {
+                Some(next) => power = next,
+                None => break,
+            }
+            digits += 1;
+        }
+        if power == per {
+            format!("{}{}.{:0width$}", self.symbol, major, minor, width = digits)
+        } else if minor == 0 {
+            format!("{}{}", self.symbol, major)
+        } else {
+            format!("{}{} {}/{}", self.symbol, major, minor, per)
+        }
+    }
+}
+
+/// How many of each denomination make up an amount.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breakdown {
+    /// Largest denomination first; only denominations actually used are listed.
+    pub pieces: Vec<(Denomination, u32)>,
+}
+
+impl Breakdown {
+    /// None if the total doesn't fit in a u64.
+    pub fn total(&self) -> Option<u64> {
+        total_of(self.pieces.iter().map(|(d, count)| (d, *count)))
+    }
+
+    pub fn piece_count(&self) -> u64 {
+        self.pieces.iter().map(|(_, count)| *count as u64).sum()
+    }
+}
+
+/// Counts a mixed bag of coins and bills by name.
+#[derive(Debug, Clone)]
+pub struct Counter {
+    currency: Currency,
+    counts: Vec<u32>,
+}
+
+impl Counter {
+    pub fn new(currency: Currency) -> Counter {
+        let counts = vec![0; currency.denominations.len()];
+        Counter { currency, counts }
+    }
+
+    /// Fails without changing anything if the denomination is unknown or its count would
+    /// overflow.
+    pub fn add(&mut self, name: &str, count: u32) -> Result<(), ChangeError> {
+        let i = self.currency.index_of(name)?;
+        self.counts[i] = self.counts[i].checked_add(count).ok_or_else(|| ChangeError::CountOverflow {
+            name: String::from(name),
+            count,
+        })?;
+        Ok(())
+    }
+
+    pub fn add_coin(&mut self, coin: Coin) -> Result<(), ChangeError> {
+        self.add(coin.name(), 1)
+    }
+
+    pub fn count_of(&self, name: &str) -> u32 {
+        self.currency.index_of(name).map(|i| self.counts[i]).unwrap_or(0)
+    }
+
+    /// None if the total doesn't fit in a u64.
+    pub fn total(&self) -> Option<u64> {
+        total_of(self.currency.denominations.iter().zip(self.counts.iter().copied()))
+    }
+
+    pub fn currency(&self) -> &Currency {
+        &self.currency
+    }
+}
+
+/// A cash drawer with a limited supply of each denomination.
+#[derive(Debug, Clone)]
+pub struct Till {
+    counter: Counter,
+}
+
+impl Till {
+    pub fn new(currency: Currency) -> Till {
+        Till {
+            counter: Counter::new(currency),
+        }
+    }
+
+    pub fn deposit(&mut self, name: &str, count: u32) -> Result<(), ChangeError> {
+        self.counter.add(name, count)
+    }
+
+    pub fn count_of(&self, name: &str) -> u32 {
+        self.counter.count_of(name)
+    }
+
+    /// None if the total doesn't fit in a u64.
+    pub fn total(&self) -> Option<u64> {
+        self.counter.total()
+    }
+
+    pub fn currency(&self) -> &Currency {
+        self.counter.currency()
+    }
+
+    /// Works out the fewest pieces adding up to `amount` without touching the till. Fails with
+    /// AmountTooLarge rather than running for a long time; see MAX_CHANGE_STEPS.
+    pub fn plan_change(&self, amount: u64) -> Result<Breakdown, ChangeError> {
+        // A total too big for a u64 is more than any amount.
+        if let Some(available) = self.total().filter(|&available| amount > available) {
+            return Err(ChangeError::InsufficientFunds { amount, available });
+        }
+
+        let currency = &self.counter.currency;
+        let denominations = &currency.denominations;
+        let greedy = greedy_pieces(amount, denominations, &self.counter.counts);
+        let counts = match greedy {
+            Some(counts) if currency.greedy_is_optimal => counts,
+            _ => fewest_pieces(amount, denominations, &self.counter.counts)?,
+        };
+
+        Ok(Breakdown {
+            pieces: denominations
+                .iter()
+                .zip(counts)
+                .filter(|(_, count)| *count > 0)
+                .map(|(d, count)| (d.clone(), count))
+                .collect(),
+        })
+    }
+
+    /// Like `plan_change`, but also takes the pieces out of the till.
+    pub fn give_change(&mut self, amount: u64) -> Result<Breakdown, ChangeError> {
+        let breakdown = self.plan_change(amount)?;
+        for (denomination, count) in &breakdown.pieces {
+            let i = self.counter.currency.index_of(&denomination.name)?;
+            self.counter.counts[i] -= count;
+        }
+        Ok(breakdown)
+    }
+}
+
+// Adds up value times count, or None if that doesn't fit in a u64.
+fn total_of<'a, I: Iterator<Item = (&'a Denomination, u32)>>(mut pieces: I) -> Option<u64> {
+    pieces.try_fold(0u64, |total, (d, count)| total.checked_add(d.value.checked_mul(count as u64)?))
+}
+
+// Largest first, as many of each as fit. Only returns counts when the supply never ran short, so
+// that they're what an unlimited supply would give: with a canonical currency that's the fewest
+// pieces. Denominations of the same value (a dollar coin and a $1 bill) count as one supply.
+fn greedy_pieces(amount: u64, denominations: &[Denomination], supply: &[u32]) -> Option<Vec<u32>> {
+    let mut counts = vec![0; denominations.len()];
+    let mut left = amount;
+    for (i, (denomination, &count)) in denominations.iter().zip(supply).enumerate() {
+        let take = (left / denomination.value).min(count as u64);
+        counts[i] = take as u32;
+        left -= take * denomination.value;
+        let last_of_value = denominations.get(i + 1).is_none_or(|next| next.value != denomination.value);
+        if last_of_value && left >= denomination.value {
+            return None;
+        }
+    }
+    if left == 0 {
+        Some(counts)
+    } else {
+        None
+    }
+}
+
+// Whether largest-first gives the fewest pieces for every amount, with an unlimited supply of each
+// of `denominations`, which are sorted largest first. Kozen and Zaks showed that if it ever doesn't, it already fails
+// for some amount below the two largest denominations added together, so only those are tried.
+// That needs the smallest denomination to divide all the others, and a table of that many
+// amounts; without either it's taken as not canonical.
+fn greedy_is_optimal(denominations: &[Denomination]) -> bool {
+    let Some(smallest) = denominations.last().map(|d| d.value) else {
+        return true;
+    };
+    if denominations.iter().any(|d| !d.value.is_multiple_of(smallest)) {
+        return false;
+    }
+    let values: Vec<u64> = denominations.iter().map(|d| d.value / smallest).collect();
+    let limit = match values.as_slice() {
+        [first, second, ..] => first.saturating_add(*second),
+        _ => return true,
+    };
+    if limit > MAX_CHANGE_STEPS {
+        return false;
+    }
+
+    let limit = limit as usize;
+    let mut fewest = vec![0u64; limit];
+    for total in 1..limit {
+        fewest[total] = values
+            .iter()
+            .filter(|&&value| value as usize <= total)
+            .map(|&value| fewest[total - value as usize] + 1)
+            .min()
+            .expect("the smallest value is 1");
+        let largest = values
+            .iter()
+            .find(|&&value| value as usize <= total)
+            .expect("the smallest value is 1");
+        if fewest[total - *largest as usize] + 1 != fewest[total] {
+            return false;
+        }
+    }
+    true
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+// Bounded coin change. Each denomination's supply is split into bundles of 1, 2, 4, ... pieces so
+// that a 0/1 knapsack over the bundles can pick any count up to the supply. The table has one
+// entry per step, where a step is the gcd of the denominations on hand: every reachable amount is
+// a multiple of it, so with no pennies the table is five times smaller.
+fn fewest_pieces(amount: u64, denominations: &[Denomination], supply: &[u32]) -> Result<Vec<u32>, ChangeError> {
+    let step = denominations
+        .iter()
+        .zip(supply)
+        .filter(|(_, &count)| count > 0)
+        .fold(0, |step, (d, _)| gcd(step, d.value));
+    if amount == 0 {
+        return Ok(vec![0; denominations.len()]);
+    }
+    if step == 0 || !amount.is_multiple_of(step) {
+        return Err(ChangeError::ImpossibleExactChange { amount });
+    }
+    let steps = amount / step;
+    if steps > MAX_CHANGE_STEPS {
+        return Err(ChangeError::AmountTooLarge { amount, steps });
+    }
+    // Both fit: steps is at most MAX_CHANGE_STEPS.
+    let amount = steps as usize;
+
+    // (denomination index, pieces in bundle, value of bundle)
+    let mut bundles = Vec::new();
+    for (i, (denomination, &count)) in denominations.iter().zip(supply).enumerate() {
+        let mut left = count as u64;
+        let mut size = 1;
+        while left > 0 {
+            let take = size.min(left);
+            // A bundle worth more than the amount can never be used.
+            let value = (denomination.value / step).checked_mul(take);
+            if let Some(value) = value.filter(|&v| v <= amount as u64) {
+                bundles.push((i, take as u32, value as usize));
+            }
+            left -= take;
+            size *= 2;
+        }
+    }
+
+    const UNREACHABLE: u64 = u64::MAX;
+    let mut best = vec![UNREACHABLE; amount + 1];
+    // For each amount, the bundle that last improved it and the bundle count at that time.
+    let mut choice: Vec<Vec<(usize, usize)>> = vec![Vec::new(); amount + 1];
+    best[0] = 0;
+
+    for (b, &(_, pieces, value)) in bundles.iter().enumerate() {
+        for total in (value..=amount).rev() {
+            let before = best[total - value];
+            if before != UNREACHABLE && before + (pieces as u64) < best[total] {
+                best[total] = before + pieces as u64;
+                choice[total].push((b, total - value));
+            }
+        }
+    }
+
+    if best[amount] == UNREACHABLE {
+        return Err(ChangeError::ImpossibleExactChange { amount: steps * step });
+    }
+
+    // Walk back through the choices. At each step the usable choice is the last one recorded
+    // with a bundle index below the one taken after it.
+    let mut counts = vec![0; denominations.len()];
+    let mut total = amount;
+    let mut limit = bundles.len();
+    while total > 0 {
+        let &(b, previous) = choice[total]
+            .iter()
+            .rev()
+            .find(|(b, _)| *b < limit)
+            .expect("every reachable amount has a choice leading to it");
+        let (i, pieces, _) = bundles[b];
+        counts[i] += pieces;
+        total = previous;
+        limit = b;
+    }
+
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    // The fewest pieces by trying every count of every denomination.
+    fn brute_force(amount: u64, denominations: &[Denomination], supply: &[u32]) -> Option<u64> {
+        let Some((first, rest)) = denominations.split_first() else {
+            return if amount == 0 { Some(0) } else { None };
+        };
+        (0..=supply[0])
+            .take_while(|&count| first.value * count as u64 <= amount)
+            .filter_map(|count| {
+                let left = amount - first.value * count as u64;
+                brute_force(left, rest, &supply[1..]).map(|pieces| pieces + count as u64)
+            })
+            .min()
+    }
+
+    #[test]
+    fn coins() {
+        let coins = [Coin::Quarter(UsState::Delaware), Coin::Dime, Coin::Quarter(UsState::Alaska), Coin::Penny];
+        assert_eq!(total_cents(coins), 61);
+        assert_eq!(Coin::Quarter(UsState::NewYork).name(), "quarter");
+
+        let mut counter = Counter::new(Currency::usd());
+        for coin in coins {
+            counter.add_coin(coin).unwrap();
+        }
+        assert_eq!(counter.count_of("quarter"), 2);
+        assert_eq!(counter.total(), Some(61));
+    }
+
+    #[test]
+    fn plan_change_matches_brute_force() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for _ in 0..300 {
+            let denominations: Vec<Denomination> = (0..1 + rng.below(4))
+                .map(|i| Denomination::coin(&format!("c{}", i), 2 + rng.below(30) as u64))
+                .collect();
+            let currency = Currency::new("XXX", "¤", 100, denominations).unwrap();
+            let mut till = Till::new(currency);
+            let names: Vec<String> = till.currency().denominations().iter().map(|d| d.name.clone()).collect();
+            for name in &names {
+                till.deposit(name, rng.below(6) as u32).unwrap();
+            }
+            let supply: Vec<u32> = names.iter().map(|name| till.count_of(name)).collect();
+
+            let total = till.total().unwrap();
+            for amount in 0..=total + 3 {
+                let expected = brute_force(amount, till.currency().denominations(), &supply);
+                match till.plan_change(amount) {
+                    Ok(breakdown) => {
+                        assert_eq!(breakdown.total(), Some(amount));
+                        assert_eq!(Some(breakdown.piece_count()), expected);
+                        for (denomination, count) in &breakdown.pieces {
+                            assert!(*count <= till.count_of(&denomination.name));
+                        }
+                    }
+                    Err(ChangeError::ImpossibleExactChange { amount: a }) => {
+                        assert_eq!(a, amount);
+                        assert_eq!(expected, None);
+                    }
+                    Err(ChangeError::InsufficientFunds { .. }) => assert!(amount > total),
+                    Err(error) => panic!("{}", error),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn canonical_currencies_match_brute_force() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        let coins = [("penny", 1), ("nickel", 5), ("dime", 10), ("quarter", 25)];
+        let currency = || Currency::new("USD", "$", 100, coins.iter().map(|&(n, v)| Denomination::coin(n, v)).collect());
+        assert!(currency().unwrap().greedy_is_optimal);
+        for _ in 0..200 {
+            let mut till = Till::new(currency().unwrap());
+            for (name, _) in coins {
+                till.deposit(name, rng.below(8) as u32).unwrap();
+            }
+            let supply: Vec<u32> = till.currency().denominations().iter().map(|d| till.count_of(&d.name)).collect();
+            for amount in 0..=till.total().unwrap() {
+                let expected = brute_force(amount, till.currency().denominations(), &supply);
+                let pieces = till.plan_change(amount).ok().map(|breakdown| breakdown.piece_count());
+                assert_eq!(pieces, expected, "{} from {:?}", amount, supply);
+            }
+        }
+    }
+
+    #[test]
+    fn which_currencies_are_canonical() {
+        assert!(Currency::usd().greedy_is_optimal);
+        assert!(Currency::eur().greedy_is_optimal);
+        assert!(Currency::gbp().greedy_is_optimal);
+        let coins = |values: &[u64]| {
+            let denominations = values.iter().map(|&v| Denomination::coin(&v.to_string(), v)).collect();
+            Currency::new("XXX", "¤", 100, denominations).unwrap().greedy_is_optimal
+        };
+        // 6 is 4 + 1 + 1 largest first, but 3 + 3.
+        assert!(!coins(&[1, 3, 4]));
+        // The old British 1, 3, 6, 12, 24, 30: 48 is 30 + 12 + 6 largest first, but 24 + 24.
+        assert!(!coins(&[1, 3, 6, 12, 24, 30]));
+        assert!(coins(&[2, 4, 10]));
+        assert!(!coins(&[3, 5]));
+        assert!(coins(&[7]));
+        assert!(coins(&[]));
+    }
+
+    #[test]
+    fn well_stocked_tills_take_any_amount() {
+        let mut till = Till::new(Currency::usd());
+        for denomination in Currency::usd().denominations() {
+            till.deposit(&denomination.name, 1_000_000).unwrap();
+        }
+        // $1,234,567.89 is far past MAX_CHANGE_STEPS.
+        let breakdown = till.give_change(123_456_789).unwrap();
+        assert_eq!(breakdown.total(), Some(123_456_789));
+        assert_eq!(breakdown.piece_count(), 12_345 + 5 + 7);
+        assert_eq!(breakdown.pieces[0], (Denomination::bill("$100", 10000), 12_345));
+        assert_eq!(till.count_of("$100"), 1_000_000 - 12_345);
+        assert_eq!(till.count_of("penny"), 999_996);
+    }
+
+    #[test]
+    fn short_tills_search_but_only_so_far() {
+        let mut till = Till::new(Currency::usd());
+        till.deposit("quarter", 1).unwrap();
+        till.deposit("dime", 3).unwrap();
+        // Largest first would take the quarter and be stuck with 5 cents left.
+        let breakdown = till.plan_change(30).unwrap();
+        assert_eq!(breakdown.pieces, [(Denomination::coin("dime", 10), 3)]);
+
+        till.deposit("penny", 1).unwrap();
+        till.deposit("$100", u32::MAX).unwrap();
+        assert_eq!(till.plan_change(1_000_001).unwrap().piece_count(), 101);
+        // Two pennies short, so this needs the search, which is too big.
+        assert_eq!(
+            till.plan_change(1_000_003),
+            Err(ChangeError::AmountTooLarge {
+                amount: 1_000_003,
+                steps: 1_000_003
+            })
+        );
+        assert_eq!(till.plan_change(1_000_000).unwrap().piece_count(), 100);
+    }
+
+    #[test]
+    fn totals_that_do_not_fit_are_none() {
+        let currency = Currency::new("XXX", "¤", 100, vec![Denomination::bill("big", u64::MAX / 2)]).unwrap();
+        let mut till = Till::new(currency);
+        till.deposit("big", 2).unwrap();
+        assert_eq!(till.total(), Some(u64::MAX - 1));
+        till.deposit("big", 1).unwrap();
+        assert_eq!(till.total(), None);
+        // More than any amount, so nothing is too much.
+        assert_eq!(till.plan_change(u64::MAX / 2).unwrap().piece_count(), 1);
+        assert_eq!(
+            till.plan_change(u64::MAX),
+            Err(ChangeError::ImpossibleExactChange { amount: u64::MAX })
+        );
+
+        let breakdown = Breakdown {
+            pieces: vec![(Denomination::bill("big", u64::MAX / 2), 3)],
+        };
+        assert_eq!(breakdown.total(), None);
+        assert_eq!(breakdown.piece_count(), 3);
+    }
+
+    #[test]
+    fn formatting() {
+        let usd = Currency::usd();
+        assert_eq!(usd.format(1205), "$12.05");
+        assert_eq!(usd.format(7), "$0.07");
+        assert_eq!(usd.format(0), "$0.00");
+        let currency = |symbol: &str, per: u64| Currency::new("XXX", symbol, per, Vec::new()).unwrap();
+        assert_eq!(currency("¥", 1).format(1205), "¥1205");
+        assert_eq!(currency("¥", 0).format(1205), "¥1205");
+        assert_eq!(currency("KD", 1000).format(12_005), "KD12.005");
+        assert_eq!(currency("Ar", 5).format(17), "Ar3 2/5");
+        assert_eq!(currency("Ar", 5).format(15), "Ar3");
+        assert_eq!(currency("¤", 12).format(25), "¤2 1/12");
+        assert_eq!(currency("¤", u64::MAX).format(u64::MAX - 1), "¤0 18446744073709551614/18446744073709551615");
+    }
+
+    #[test]
+    fn big_denominations_shrink_the_table() {
+        let mut till = Till::new(Currency::usd());
+        till.deposit("$100", u32::MAX).unwrap();
+        till.deposit("$20", 3).unwrap();
+        let breakdown = till.give_change(100_006_000).unwrap();
+        assert_eq!(breakdown.piece_count(), 10_003);
+        assert_eq!(till.count_of("$20"), 0);
+        assert_eq!(
+            till.plan_change(100_000_500),
+            Err(ChangeError::ImpossibleExactChange { amount: 100_000_500 })
+        );
+    }
+
+    #[test]
+    fn counts_do_not_overflow() {
+        let mut counter = Counter::new(Currency::usd());
+        counter.add("dime", u32::MAX).unwrap();
+        assert_eq!(
+            counter.add("dime", 1),
+            Err(ChangeError::CountOverflow {
+                name: String::from("dime"),
+                count: 1
+            })
+        );
+        assert_eq!(counter.count_of("dime"), u32::MAX);
+        assert_eq!(counter.total(), Some(u32::MAX as u64 * 10));
+    }
+}
@@ -0,0 +1,314 @@
+// Color math for the chapter 5 tuple struct `Color(i32, i32, i32)`.
+//
+// The fields stay i32 like the book, so a Color can hold values outside 0..=255. Color::new and
+// clamped() pull them back into range, and every conversion below works on the clamped value.
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Color(pub i32, pub i32, pub i32);
+
+/// Hue in degrees (0..360), saturation and lightness from 0.0 to 1.0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hsl {
+    pub h: f64,
+    pub s: f64,
+    pub l: f64,
+}
+
+/// Hue in degrees (0..360), saturation and value from 0.0 to 1.0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hsv {
+    pub h: f64,
+    pub s: f64,
+    pub v: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseColorError {
+    MissingHash,
+    /// Only `#rgb` and `#rrggbb` are accepted.
+    BadLength(usize),
+    BadDigit(char),
+}
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseColorError::MissingHash => write!(f, "hex colors must start with '#'"),
+            ParseColorError::BadLength(len) => {
+                write!(f, "expected 3 or 6 hex digits, found {}", len)
+            }
+            ParseColorError::BadDigit(c) => write!(f, "'{}' is not a hex digit", c),
+        }
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+impl Color {
+    pub const BLACK: Color = Color(0, 0, 0);
+    pub const WHITE: Color = Color(255, 255, 255);
+
+    /// Builds a color, clamping each channel into 0..=255.
+    pub fn new(r: i32, g: i32, b: i32) -> Color {
+        Color(r, g, b).clamped()
+    }
+
+    pub fn clamped(self) -> Color {
+        Color(self.0.clamp(0, 255), self.1.clamp(0, 255), self.2.clamp(0, 255))
+    }
+
+    pub fn red(&self) -> u8 {
+        self.0.clamp(0, 255) as u8
+    }
+
+    pub fn green(&self) -> u8 {
+        self.1.clamp(0, 255) as u8
+    }
+
+    pub fn blue(&self) -> u8 {
+        self.2.clamp(0, 255) as u8
+    }
+
+    /// Lowercase `#rrggbb`.
+    pub fn to_hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.red(), self.green(), self.blue())
+    }
+
+    /// Parses `#rrggbb` or the short form `#rgb`.
+    pub fn from_hex(text: &str) -> Result<Color, ParseColorError> {
+        let digits = text.strip_prefix('#').ok_or(ParseColorError::MissingHash)?;
+
+        let mut values = Vec::with_capacity(6);
+        for c in digits.chars() {
+            values.push(c.to_digit(16).ok_or(ParseColorError::BadDigit(c))? as i32);
+        }
+
+        match values.len() {
+            3 => Ok(Color(values[0] * 17, values[1] * 17, values[2] * 17)),
+            6 => Ok(Color(
+                values[0] * 16 + values[1],
+                values[2] * 16 + values[3],
+                values[4] * 16 + values[5],
+            )),
+            len => Err(ParseColorError::BadLength(len)),
+        }
+    }
+
+    pub fn to_hsl(&self) -> Hsl {
+        let (r, g, b) = self.unit_channels();
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+        let l = (max + min) / 2.0;
+
+        let s = if delta == 0.0 {
+            0.0
+        } else {
+            delta / (1.0 - (2.0 * l - 1.0).abs())
+        };
+
+        Hsl {
+            h: hue(r, g, b, max, delta),
+            s,
+            l,
+        }
+    }
+
+    pub fn from_hsl(hsl: Hsl) -> Color {
+        let s = hsl.s.clamp(0.0, 1.0);
+        let l = hsl.l.clamp(0.0, 1.0);
+        let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        from_chroma(hsl.h, chroma, l - chroma / 2.0)
+    }
+
+    pub fn to_hsv(&self) -> Hsv {
+        let (r, g, b) = self.unit_channels();
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+
+        Hsv {
+            h: hue(r, g, b, max, delta),
+            s: if max == 0.0 { 0.0 } else { delta / max },
+            v: max,
+        }
+    }
+
+    pub fn from_hsv(hsv: Hsv) -> Color {
+        let s = hsv.s.clamp(0.0, 1.0);
+        let v = hsv.v.clamp(0.0, 1.0);
+        let chroma = v * s;
+        from_chroma(hsv.h, chroma, v - chroma)
+    }
+
+    /// Mixes two colors. `t` of 0.0 gives `self`, 1.0 gives `other`.
+    pub fn blend(&self, other: Color, t: f64) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as i32;
+        Color(
+            mix(self.red(), other.red()),
+            mix(self.green(), other.green()),
+            mix(self.blue(), other.blue()),
+        )
+    }
+
+    /// Relative luminance from WCAG 2, from 0.0 (black) to 1.0 (white).
+    pub fn luminance(&self) -> f64 {
+        let linear = |c: f64| {
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        let (r, g, b) = self.unit_channels();
+        0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+    }
+
+    /// WCAG contrast ratio, from 1.0 (same luminance) to 21.0 (black on white).
+    pub fn contrast_ratio(&self, other: Color) -> f64 {
+        let (a, b) = (self.luminance(), other.luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    fn unit_channels(&self) -> (f64, f64, f64) {
+        (
+            self.red() as f64 / 255.0,
+            self.green() as f64 / 255.0,
+            self.blue() as f64 / 255.0,
+        )
+    }
+}
+
+fn hue(r: f64, g: f64, b: f64, max: f64, delta: f64) -> f64 {
+    if delta == 0.0 {
+        return 0.0;
+    }
+
+    let sector = if max == r {
+        ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / delta + 2.0
+    } else {
+        (r - g) / delta + 4.0
+    };
+    sector * 60.0
+}
+
+// Shared tail of the HSL and HSV conversions once chroma and the lightness offset are known.
+fn from_chroma(h: f64, chroma: f64, offset: f64) -> Color {
+    let sector = h.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector.rem_euclid(2.0) - 1.0).abs());
+
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+
+    let channel = |c: f64| ((c + offset) * 255.0).round() as i32;
+    Color::new(channel(r), channel(g), channel(b))
+}
+
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Color, ParseColorError> {
+        Color::from_hex(s)
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn random_color(rng: &mut Rng) -> Color {
+        Color(rng.below(256) as i32, rng.below(256) as i32, rng.below(256) as i32)
+    }
+
+    #[test]
+    fn hex_round_trips() {
+        let mut rng = Rng(0xC0102);
+        for _ in 0..1_000 {
+            let color = random_color(&mut rng);
+            assert_eq!(Color::from_hex(&color.to_hex()), Ok(color));
+            assert_eq!(color.to_string().parse(), Ok(color));
+        }
+        assert_eq!(Color::from_hex("#FfA500"), Ok(Color(255, 165, 0)));
+        assert_eq!(Color::from_hex("#f80"), Ok(Color(255, 136, 0)));
+        assert_eq!(Color(300, -4, 16).to_hex(), "#ff0010");
+    }
+
+    #[test]
+    fn bad_hex() {
+        assert_eq!(Color::from_hex("ff8800"), Err(ParseColorError::MissingHash));
+        assert_eq!(Color::from_hex(""), Err(ParseColorError::MissingHash));
+        assert_eq!(Color::from_hex("#"), Err(ParseColorError::BadLength(0)));
+        assert_eq!(Color::from_hex("#ff88"), Err(ParseColorError::BadLength(4)));
+        assert_eq!(Color::from_hex("#ff88000"), Err(ParseColorError::BadLength(7)));
+        assert_eq!(Color::from_hex("#ff88zz"), Err(ParseColorError::BadDigit('z')));
+        assert_eq!(Color::from_hex("#é00"), Err(ParseColorError::BadDigit('é')));
+        assert_eq!(Color::from_hex(" #fff"), Err(ParseColorError::MissingHash));
+        assert_eq!(
+            "#12".parse::<Color>().unwrap_err().to_string(),
+            "expected 3 or 6 hex digits, found 2"
+        );
+    }
+
+    #[test]
+    fn hsl_and_hsv_round_trip() {
+        let mut rng = Rng(99);
+        for _ in 0..10_000 {
+            let color = random_color(&mut rng);
+            assert_eq!(Color::from_hsl(color.to_hsl()), color, "{:?}", color.to_hsl());
+            assert_eq!(Color::from_hsv(color.to_hsv()), color, "{:?}", color.to_hsv());
+        }
+        for color in [Color::BLACK, Color::WHITE, Color(255, 0, 0), Color(0, 0, 255), Color(128, 128, 128)] {
+            assert_eq!(Color::from_hsl(color.to_hsl()), color);
+        }
+    }
+
+    #[test]
+    fn known_hsl_values() {
+        assert_eq!(Color(255, 0, 0).to_hsl(), Hsl { h: 0.0, s: 1.0, l: 0.5 });
+        assert_eq!(Color(0, 255, 0).to_hsl(), Hsl { h: 120.0, s: 1.0, l: 0.5 });
+        assert_eq!(Color(0, 0, 255).to_hsv(), Hsv { h: 240.0, s: 1.0, v: 1.0 });
+        assert_eq!(Color::WHITE.to_hsl(), Hsl { h: 0.0, s: 0.0, l: 1.0 });
+        // Hues wrap, and saturation and lightness are clamped.
+        assert_eq!(Color::from_hsl(Hsl { h: -240.0, s: 2.0, l: 0.5 }), Color(0, 255, 0));
+        assert_eq!(Color::from_hsv(Hsv { h: 720.0, s: 1.0, v: 1.0 }), Color(255, 0, 0));
+    }
+
+    #[test]
+    fn contrast_ratios() {
+        let close = |a: f64, b: f64| (a - b).abs() < 0.01;
+        assert_eq!(Color::BLACK.contrast_ratio(Color::WHITE), 21.0);
+        assert_eq!(Color::WHITE.contrast_ratio(Color::BLACK), 21.0);
+        assert_eq!(Color(40, 90, 200).contrast_ratio(Color(40, 90, 200)), 1.0);
+        // Values from the WCAG contrast checker.
+        assert!(close(Color(0x77, 0x77, 0x77).contrast_ratio(Color::WHITE), 4.48));
+        assert!(close(Color(0, 0, 255).contrast_ratio(Color::WHITE), 8.59));
+        assert!(close(Color(255, 0, 0).contrast_ratio(Color::BLACK), 5.25));
+        assert_eq!((Color::BLACK.luminance(), Color::WHITE.luminance()), (0.0, 1.0));
+    }
+
+    #[test]
+    fn blending() {
+        assert_eq!(Color::BLACK.blend(Color::WHITE, 0.5), Color(128, 128, 128));
+        assert_eq!(Color::BLACK.blend(Color::WHITE, -1.0), Color::BLACK);
+        assert_eq!(Color(10, 20, 30).blend(Color(20, 40, 60), 2.0), Color(20, 40, 60));
+    }
+}
@@ -0,0 +1,618 @@
+// The employee directory exercise from the end of chapter 8: text commands like
+// "Add Sally to Engineering" and "List all" over a HashMap of departments.
+//
+// Department and employee names compare without regard to case, so "list engineering" finds the
+// department added as "Engineering". A name keeps the spelling it was first added with.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use crate::words::fold_case;
+
+// A name that compares, hashes and sorts by its case-folded form but keeps its spelling.
+#[derive(Debug, Clone)]
+struct Name {
+    spelling: String,
+    key: String,
+}
+
+impl Name {
+    fn new(name: &str) -> Name {
+        let name = name.trim();
+        Name {
+            spelling: String::from(name),
+            key: fold_case(name),
+        }
+    }
+}
+
+impl PartialEq for Name {
+    fn eq(&self, other: &Name) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Eq for Name {}
+
+impl Hash for Name {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+    }
+}
+
+impl Ord for Name {
+    fn cmp(&self, other: &Name) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+impl PartialOrd for Name {
+    fn partial_cmp(&self, other: &Name) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Equal to any other spelling of the same name, and sorted alphabetically ignoring case.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Department(Name);
+
+impl Department {
+    /// Surrounding whitespace is trimmed.
+    pub fn new(name: &str) -> Department {
+        Department(Name::new(name))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0.spelling
+    }
+}
+
+impl fmt::Display for Department {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Compares like `Department`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Employee(Name);
+
+impl Employee {
+    pub fn new(name: &str) -> Employee {
+        Employee(Name::new(name))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0.spelling
+    }
+}
+
+impl fmt::Display for Employee {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// One parsed line of input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// "Add Sally to Engineering"
+    Add { employee: Employee, department: Department },
+    /// "Remove Sally from Engineering"
+    Remove { employee: Employee, department: Department },
+    /// "Move Sally from Engineering to Sales", or "Move Sally to Sales" when Sally is only in one
+    /// department.
+    Move {
+        employee: Employee,
+        from: Option<Department>,
+        to: Department,
+    },
+    /// "List Engineering", or "List all" for every department.
+    List(Option<Department>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    Empty,
+    UnknownCommand(String),
+    /// The command, e.g. "Add", that has no employee name.
+    MissingName(&'static str),
+    /// The command and the word it needs, e.g. "Add" and "to".
+    MissingKeyword(&'static str, &'static str),
+    MissingDepartment(&'static str),
+}
+
+fn example(command: &str) -> &'static str {
+    match command {
+        "Add" => "Add Sally to Engineering",
+        "Remove" => "Remove Sally from Engineering",
+        "Move" => "Move Sally from Engineering to Sales",
+        _ => "List Engineering, or List all",
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "type a command: Add, Remove, Move or List"),
+            ParseError::UnknownCommand(word) => {
+                write!(f, "unknown command '{}' (the commands are Add, Remove, Move and List)", word)
+            }
+            ParseError::MissingName(command) => {
+                write!(f, "{} needs an employee name, like: {}", command, example(command))
+            }
+            ParseError::MissingKeyword(command, keyword) => {
+                write!(f, "{} needs '{}' before the department, like: {}", command, keyword, example(command))
+            }
+            ParseError::MissingDepartment(command) => {
+                write!(f, "{} needs a department, like: {}", command, example(command))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Command words and the "to" and "from" in them can be any case.
+impl FromStr for Command {
+    type Err = ParseError;
+
+    fn from_str(line: &str) -> Result<Command, ParseError> {
+        let line = line.trim();
+        let (verb, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+
+        match verb.to_lowercase().as_str() {
+            "" => Err(ParseError::Empty),
+            "add" => {
+                let (employee, department) = name_and_department("Add", rest, "to", split_at_word)?;
+                Ok(Command::Add { employee, department })
+            }
+            "remove" => {
+                let (employee, department) = name_and_department("Remove", rest, "from", split_at_word)?;
+                Ok(Command::Remove { employee, department })
+            }
+            "move" => {
+                // The last "to", so "Move Tom from Back to Front to Sales" goes to Sales.
+                let (employee, to) = name_and_department("Move", rest, "to", split_at_last_word)?;
+                match split_at_word(employee.name(), "from") {
+                    Some((name, from)) => {
+                        let name = required(name, ParseError::MissingName("Move"))?;
+                        let from = required(from, ParseError::MissingDepartment("Move"))?;
+                        Ok(Command::Move {
+                            employee: Employee::new(&name),
+                            from: Some(Department::new(&from)),
+                            to,
+                        })
+                    }
+                    None => Ok(Command::Move { employee, from: None, to }),
+                }
+            }
+            "list" => match rest.trim() {
+                "" => Err(ParseError::MissingDepartment("List")),
+                all if all.eq_ignore_ascii_case("all") => Ok(Command::List(None)),
+                department => Ok(Command::List(Some(Department::new(department)))),
+            },
+            _ => Err(ParseError::UnknownCommand(String::from(verb))),
+        }
+    }
+}
+
+// "Sally to Engineering" split around `keyword` by `split`.
+fn name_and_department(
+    command: &'static str,
+    text: &str,
+    keyword: &'static str,
+    split: fn(&str, &str) -> Option<(String, String)>,
+) -> Result<(Employee, Department), ParseError> {
+    let (name, department) = match split(text, keyword) {
+        Some(split) => split,
+        None if text.trim().is_empty() => return Err(ParseError::MissingName(command)),
+        None => return Err(ParseError::MissingKeyword(command, keyword)),
+    };
+    let name = required(name, ParseError::MissingName(command))?;
+    let department = required(department, ParseError::MissingDepartment(command))?;
+    Ok((Employee::new(&name), Department::new(&department)))
+}
+
+// Splits at the first `keyword` that stands as a word of its own between any whitespace,
+// ignoring ASCII case, and trims both sides. "Sally to" splits into "Sally" and "".
+fn split_at_word(text: &str, keyword: &str) -> Option<(String, String)> {
+    let word = keyword_words(text, keyword).next()?;
+    Some(split_around(text, word))
+}
+
+// Like `split_at_word`, at the last `keyword` instead.
+fn split_at_last_word(text: &str, keyword: &str) -> Option<(String, String)> {
+    let word = keyword_words(text, keyword).last()?;
+    Some(split_around(text, word))
+}
+
+// The words of `text` that are `keyword`, as slices of it.
+fn keyword_words<'a>(text: &'a str, keyword: &'a str) -> impl Iterator<Item = &'a str> {
+    text.split_whitespace().filter(move |word| word.eq_ignore_ascii_case(keyword))
+}
+
+// `word` is a slice of `text`, so where it starts in memory says where it is in `text`.
+fn split_around(text: &str, word: &str) -> (String, String) {
+    let at = word.as_ptr() as usize - text.as_ptr() as usize;
+    let before = text[..at].trim();
+    let after = text[at + word.len()..].trim();
+    (String::from(before), String::from(after))
+}
+
+fn required(text: String, error: ParseError) -> Result<String, ParseError> {
+    if text.is_empty() {
+        Err(error)
+    } else {
+        Ok(text)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DirectoryError {
+    AlreadyInDepartment { employee: String, department: String },
+    NotInDepartment { employee: String, department: String },
+    /// The department asked for, and the ones there are.
+    NoSuchDepartment { department: String, known: Vec<String> },
+    NoSuchEmployee(String),
+    /// A Move without "from" for someone in more than one department.
+    InSeveralDepartments { employee: String, departments: Vec<String> },
+}
+
+impl fmt::Display for DirectoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DirectoryError::AlreadyInDepartment { employee, department } => {
+                write!(f, "{} is already in {}", employee, department)
+            }
+            DirectoryError::NotInDepartment { employee, department } => {
+                write!(f, "{} isn't in {}", employee, department)
+            }
+            DirectoryError::NoSuchDepartment { department, known } if known.is_empty() => {
+                write!(f, "there's no department named {} (there are no departments yet)", department)
+            }
+            DirectoryError::NoSuchDepartment { department, known } => {
+                write!(f, "there's no department named {} (try {})", department, known.join(", "))
+            }
+            DirectoryError::NoSuchEmployee(employee) => write!(f, "{} isn't in any department", employee),
+            DirectoryError::InSeveralDepartments { employee, departments } => write!(
+                f,
+                "{} is in {}; say which with: Move {} from {} to ...",
+                employee,
+                departments.join(" and "),
+                employee,
+                departments.first().map_or("...", String::as_str)
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DirectoryError {}
+
+/// Employees by department. Each department's list is kept sorted, and a department goes away
+/// when its last employee leaves.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Directory {
+    departments: HashMap<Department, Vec<Employee>>,
+}
+
+impl Directory {
+    pub fn new() -> Directory {
+        Directory::default()
+    }
+
+    pub fn add(&mut self, employee: &str, department: &str) -> Result<(), DirectoryError> {
+        let employee = Employee::new(employee);
+        let employees = self.departments.entry(Department::new(department)).or_default();
+        match employees.binary_search(&employee) {
+            Ok(i) => Err(DirectoryError::AlreadyInDepartment {
+                employee: employees[i].to_string(),
+                department: self.spelling(department),
+            }),
+            Err(i) => {
+                employees.insert(i, employee);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn remove(&mut self, employee: &str, department: &str) -> Result<(), DirectoryError> {
+        let key = Department::new(department);
+        let employees = match self.departments.get_mut(&key) {
+            Some(employees) => employees,
+            None => return Err(self.no_such_department(department)),
+        };
+        match employees.binary_search(&Employee::new(employee)) {
+            Ok(i) => {
+                employees.remove(i);
+                if employees.is_empty() {
+                    self.departments.remove(&key);
+                }
+                Ok(())
+            }
+            Err(_) => Err(DirectoryError::NotInDepartment {
+                employee: String::from(employee.trim()),
+                department: self.spelling(department),
+            }),
+        }
+    }
+
+    /// Moves an employee between departments. Without `from`, the employee has to be in exactly
+    /// one department.
+    pub fn move_employee(&mut self, employee: &str, from: Option<&str>, to: &str) -> Result<(), DirectoryError> {
+        let from = match from {
+            Some(from) => self.spelling(from),
+            None => {
+                let mut departments = self.departments_of(employee);
+                match departments.len() {
+                    0 => return Err(DirectoryError::NoSuchEmployee(String::from(employee.trim()))),
+                    1 => departments.remove(0),
+                    _ => {
+                        return Err(DirectoryError::InSeveralDepartments {
+                            employee: self.employee_spelling(employee),
+                            departments,
+                        })
+                    }
+                }
+            }
+        };
+        if self.employees(to).is_some_and(|employees| employees.contains(&Employee::new(employee))) {
+            return Err(DirectoryError::AlreadyInDepartment {
+                employee: self.employee_spelling(employee),
+                department: self.spelling(to),
+            });
+        }
+        // Check the removal can happen before adding, so a failed move changes nothing.
+        let spelling = self.employee_spelling(employee);
+        self.remove(employee, &from)?;
+        self.add(&spelling, to)
+    }
+
+    /// A department's employees in alphabetical order.
+    pub fn employees(&self, department: &str) -> Option<&[Employee]> {
+        self.departments.get(&Department::new(department)).map(Vec::as_slice)
+    }
+
+    /// Every department in alphabetical order, with its employees in alphabetical order.
+    pub fn all(&self) -> Vec<(&Department, &[Employee])> {
+        let mut all: Vec<(&Department, &[Employee])> = self
+            .departments
+            .iter()
+            .map(|(department, employees)| (department, employees.as_slice()))
+            .collect();
+        all.sort_by(|a, b| a.0.cmp(b.0));
+        all
+    }
+
+    /// The departments an employee is in, alphabetically.
+    pub fn departments_of(&self, employee: &str) -> Vec<String> {
+        let employee = Employee::new(employee);
+        self.all()
+            .into_iter()
+            .filter(|(_, employees)| employees.binary_search(&employee).is_ok())
+            .map(|(department, _)| department.to_string())
+            .collect()
+    }
+
+    /// Carries out a command and returns what to show the user.
+    pub fn execute(&mut self, command: &Command) -> Result<String, DirectoryError> {
+        match command {
+            Command::Add { employee, department } => {
+                self.add(employee.name(), department.name())?;
+                Ok(format!("Added {} to {}.", employee, self.spelling(department.name())))
+            }
+            Command::Remove { employee, department } => {
+                let spelling = self.spelling(department.name());
+                let employee_spelling = self.employee_spelling(employee.name());
+                self.remove(employee.name(), department.name())?;
+                Ok(format!("Removed {} from {}.", employee_spelling, spelling))
+            }
+            Command::Move { employee, from, to } => {
+                let before = self.departments_of(employee.name());
+                let employee_spelling = self.employee_spelling(employee.name());
+                self.move_employee(employee.name(), from.as_ref().map(Department::name), to.name())?;
+                let from = match from {
+                    Some(from) => from.to_string(),
+                    None => before.into_iter().next().unwrap_or_default(),
+                };
+                Ok(format!("Moved {} from {} to {}.", employee_spelling, from, self.spelling(to.name())))
+            }
+            Command::List(Some(department)) => match self.employees(department.name()) {
+                Some(employees) => Ok(employees.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("\n")),
+                None => Err(self.no_such_department(department.name())),
+            },
+            Command::List(None) => {
+                if self.departments.is_empty() {
+                    return Ok(String::from("No employees yet."));
+                }
+                let mut out = Vec::new();
+                for (department, employees) in self.all() {
+                    out.push(department.to_string());
+                    out.extend(employees.iter().map(|employee| format!("  {}", employee)));
+                }
+                Ok(out.join("\n"))
+            }
+        }
+    }
+
+    // A department name as it was first added, or as given if there's no such department.
+    fn spelling(&self, department: &str) -> String {
+        match self.departments.get_key_value(&Department::new(department)) {
+            Some((key, _)) => key.to_string(),
+            None => String::from(department.trim()),
+        }
+    }
+
+    fn employee_spelling(&self, employee: &str) -> String {
+        let wanted = Employee::new(employee);
+        self.departments
+            .values()
+            .flatten()
+            .find(|&existing| *existing == wanted)
+            .map_or_else(|| wanted.to_string(), Employee::to_string)
+    }
+
+    fn no_such_department(&self, department: &str) -> DirectoryError {
+        DirectoryError::NoSuchDepartment {
+            department: String::from(department.trim()),
+            known: self.all().into_iter().map(|(department, _)| department.to_string()).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> Result<Command, ParseError> {
+        line.parse()
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(parse("add Sally TO Engineering"), Ok(Command::Add {
+            employee: Employee::new("Sally"),
+            department: Department::new("Engineering"),
+        }));
+        assert_eq!(parse("Add Sally\tto  Engineering"), parse("Add Sally to Engineering"));
+        assert_eq!(parse("Remove Amir from Sales"), Ok(Command::Remove {
+            employee: Employee::new("Amir"),
+            department: Department::new("Sales"),
+        }));
+        assert_eq!(parse("List all"), Ok(Command::List(None)));
+        assert_eq!(parse("list Engineering"), Ok(Command::List(Some(Department::new("engineering")))));
+    }
+
+    #[test]
+    fn move_splits_on_the_last_to() {
+        assert_eq!(parse("Move Tom from Back to Front to Sales"), Ok(Command::Move {
+            employee: Employee::new("Tom"),
+            from: Some(Department::new("Back to Front")),
+            to: Department::new("Sales"),
+        }));
+        assert_eq!(parse("Move Sally\tto\tSales"), Ok(Command::Move {
+            employee: Employee::new("Sally"),
+            from: None,
+            to: Department::new("Sales"),
+        }));
+        // "to" inside a word doesn't count.
+        assert_eq!(parse("Move Otto to Sales"), Ok(Command::Move {
+            employee: Employee::new("Otto"),
+            from: None,
+            to: Department::new("Sales"),
+        }));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(parse(""), Err(ParseError::Empty));
+        assert_eq!(parse("  \t "), Err(ParseError::Empty));
+        assert_eq!(parse("Fire Sally"), Err(ParseError::UnknownCommand(String::from("Fire"))));
+        assert_eq!(parse("Add"), Err(ParseError::MissingName("Add")));
+        assert_eq!(parse("Add to Sales"), Err(ParseError::MissingName("Add")));
+        assert_eq!(parse("Move from Sales to Ops"), Err(ParseError::MissingName("Move")));
+        assert_eq!(parse("Add Sally"), Err(ParseError::MissingKeyword("Add", "to")));
+        assert_eq!(parse("Remove Sally to Sales"), Err(ParseError::MissingKeyword("Remove", "from")));
+        assert_eq!(parse("Add Sally to"), Err(ParseError::MissingDepartment("Add")));
+        assert_eq!(parse("Move Sally from to Ops"), Err(ParseError::MissingDepartment("Move")));
+        assert_eq!(parse("List"), Err(ParseError::MissingDepartment("List")));
+        assert_eq!(
+            ParseError::MissingKeyword("Remove", "from").to_string(),
+            "Remove needs 'from' before the department, like: Remove Sally from Engineering"
+        );
+    }
+
+    #[test]
+    fn lookups_ignore_case_and_keep_the_first_spelling() {
+        let mut directory = Directory::new();
+        directory.add("Sally", "Engineering").unwrap();
+        directory.add("amir", "ENGINEERING").unwrap();
+        assert_eq!(directory.add("SALLY", "engineering"), Err(DirectoryError::AlreadyInDepartment {
+            employee: String::from("Sally"),
+            department: String::from("Engineering"),
+        }));
+        let names: Vec<&str> = directory.employees("engineering").unwrap().iter().map(Employee::name).collect();
+        assert_eq!(names, ["amir", "Sally"]);
+        assert_eq!(directory.departments_of("sally"), ["Engineering"]);
+        let command = parse("remove sally from engineering").unwrap();
+        assert_eq!(directory.execute(&command), Ok(String::from("Removed Sally from Engineering.")));
+    }
+
+    #[test]
+    fn list_all_is_sorted() {
+        let mut directory = Directory::new();
+        let list = parse("List all").unwrap();
+        assert_eq!(directory.execute(&list), Ok(String::from("No employees yet.")));
+        for line in ["Add Zoe to sales", "Add bob to Engineering", "Add Amir to Sales", "Add Cy to accounts"] {
+            directory.execute(&parse(line).unwrap()).unwrap();
+        }
+        assert_eq!(
+            directory.execute(&list),
+            Ok(String::from("accounts\n  Cy\nEngineering\n  bob\nsales\n  Amir\n  Zoe"))
+        );
+    }
+
+    #[test]
+    fn moves() {
+        let mut directory = Directory::new();
+        directory.add("Sally", "Engineering").unwrap();
+        let command = parse("Move sally to Sales").unwrap();
+        assert_eq!(directory.execute(&command), Ok(String::from("Moved Sally from Engineering to Sales.")));
+        assert_eq!(directory.employees("Engineering"), None);
+        assert_eq!(directory.departments_of("Sally"), ["Sales"]);
+    }
+
+    #[test]
+    fn failed_moves_change_nothing() {
+        let mut directory = Directory::new();
+        directory.add("Sally", "Engineering").unwrap();
+        directory.add("Sally", "Sales").unwrap();
+        directory.add("Amir", "Ops").unwrap();
+        let before = directory.clone();
+
+        let error = directory.move_employee("Sally", None, "Ops").unwrap_err();
+        assert_eq!(error, DirectoryError::InSeveralDepartments {
+            employee: String::from("Sally"),
+            departments: vec![String::from("Engineering"), String::from("Sales")],
+        });
+        assert_eq!(
+            error.to_string(),
+            "Sally is in Engineering and Sales; say which with: Move Sally from Engineering to ..."
+        );
+        assert_eq!(
+            directory.move_employee("Sally", Some("Engineering"), "sales"),
+            Err(DirectoryError::AlreadyInDepartment {
+                employee: String::from("Sally"),
+                department: String::from("Sales"),
+            })
+        );
+        assert_eq!(
+            directory.move_employee("Sally", Some("Ops"), "Legal"),
+            Err(DirectoryError::NotInDepartment {
+                employee: String::from("Sally"),
+                department: String::from("Ops"),
+            })
+        );
+        assert!(matches!(
+            directory.move_employee("Sally", Some("Legal"), "Ops"),
+            Err(DirectoryError::NoSuchDepartment { .. })
+        ));
+        assert_eq!(
+            directory.move_employee("Nobody", None, "Ops"),
+            Err(DirectoryError::NoSuchEmployee(String::from("Nobody")))
+        );
+        assert_eq!(directory, before);
+    }
+
+    #[test]
+    fn several_departments_message_without_any() {
+        let error = DirectoryError::InSeveralDepartments {
+            employee: String::from("Sally"),
+            departments: Vec::new(),
+        };
+        assert_eq!(error.to_string(), "Sally is in ; say which with: Move Sally from ... to ...");
+    }
+}
@@ -0,0 +1,460 @@
+// Converting between String and the byte encodings we still receive files in.
+//
+// Chapter 8 prints the bytes() of "Hola" and they happen to be ASCII. A String is always UTF-8,
+// but files from older systems are often Latin-1 (ISO-8859-1), Windows-1252 or UTF-16. This module
+// decodes those to a String and encodes back, either strictly, stopping with the byte offset of
+// the first problem, or lossily, substituting U+FFFD when decoding and '?' when encoding. It
+// also spots byte order marks and prints hexdumps with the decoded text alongside.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::grapheme;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    Utf8,
+    /// ISO-8859-1: every byte is the code point with the same number.
+    Latin1,
+    /// Latin-1 with printable characters such as € and curly quotes in 0x80 to 0x9F.
+    Windows1252,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl Encoding {
+    /// The byte order mark for this encoding. Latin-1 and Windows-1252 don't have one.
+    pub fn bom(self) -> &'static [u8] {
+        match self {
+            Encoding::Utf8 => &[0xEF, 0xBB, 0xBF],
+            Encoding::Utf16Le => &[0xFF, 0xFE],
+            Encoding::Utf16Be => &[0xFE, 0xFF],
+            Encoding::Latin1 | Encoding::Windows1252 => &[],
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Latin1 => "Latin-1",
+            Encoding::Windows1252 => "Windows-1252",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownEncoding(pub String);
+
+impl fmt::Display for UnknownEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "unknown encoding '{}' (try utf-8, latin-1, windows-1252, utf-16le or utf-16be)",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for UnknownEncoding {}
+
+/// Accepts the common labels, ignoring case, dashes and underscores: "utf8", "ISO-8859-1",
+/// "cp1252", "utf-16le" and so on.
+///
+/// A plain "utf-16" doesn't say which byte order, and is taken as little-endian, the order
+/// Windows writes. Such files usually start with a byte order mark, so pass the result to
+/// `decode_with_bom` as the fallback and a big-endian mark still wins.
+impl FromStr for Encoding {
+    type Err = UnknownEncoding;
+
+    fn from_str(label: &str) -> Result<Encoding, UnknownEncoding> {
+        let normalized: String = label
+            .chars()
+            .filter(|c| !matches!(c, '-' | '_' | ' '))
+            .flat_map(char::to_lowercase)
+            .collect();
+        match normalized.as_str() {
+            "utf8" => Ok(Encoding::Utf8),
+            "latin1" | "iso88591" | "l1" => Ok(Encoding::Latin1),
+            "windows1252" | "cp1252" | "win1252" => Ok(Encoding::Windows1252),
+            "utf16le" | "utf16" => Ok(Encoding::Utf16Le),
+            "utf16be" => Ok(Encoding::Utf16Be),
+            _ => Err(UnknownEncoding(String::from(label))),
+        }
+    }
+}
+
+/// What to do with bytes or chars that the encoding can't represent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Stop with an error at the first problem.
+    Strict,
+    /// Substitute U+FFFD when decoding and '?' when encoding, and keep going.
+    Lossy,
+}
+
+/// Bytes that aren't valid in the encoding, starting at `offset`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError {
+    pub encoding: Encoding,
+    pub offset: usize,
+    pub bytes: Vec<u8>,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let hex: Vec<String> = self.bytes.iter().map(|b| format!("{:02X}", b)).collect();
+        write!(f, "invalid {} at byte {}: {}", self.encoding, self.offset, hex.join(" "))
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// A char the encoding has no bytes for. `offset` is its byte position in the input str.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodeError {
+    pub encoding: Encoding,
+    pub offset: usize,
+    pub character: char,
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:?} (U+{:04X}) at byte {} can't be written in {}",
+            self.character, self.character as u32, self.offset, self.encoding
+        )
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+// Windows-1252 0x80 to 0x9F. None marks the five bytes it leaves undefined.
+const WINDOWS_1252_HIGH: [Option<char>; 32] = [
+    Some('€'), None, Some('‚'), Some('ƒ'), Some('„'), Some('…'), Some('†'), Some('‡'),
+    Some('ˆ'), Some('‰'), Some('Š'), Some('‹'), Some('Œ'), None, Some('Ž'), None,
+    None, Some('‘'), Some('’'), Some('“'), Some('”'), Some('•'), Some('–'), Some('—'),
+    Some('˜'), Some('™'), Some('š'), Some('›'), Some('œ'), None, Some('ž'), Some('Ÿ'),
+];
+
+/// Looks for a byte order mark and returns the encoding it names and its length.
+pub fn detect_bom(bytes: &[u8]) -> Option<(Encoding, usize)> {
+    [Encoding::Utf8, Encoding::Utf16Le, Encoding::Utf16Be]
+        .into_iter()
+        .find(|encoding| bytes.starts_with(encoding.bom()))
+        .map(|encoding| (encoding, encoding.bom().len()))
+}
+
+// Decodes the char starting at `offset`: how many bytes it used, and the char if they were valid.
+fn decode_one(bytes: &[u8], offset: usize, encoding: Encoding) -> (usize, Option<char>) {
+    let rest = &bytes[offset..];
+    match encoding {
+        Encoding::Latin1 => (1, Some(char::from(rest[0]))),
+        Encoding::Windows1252 => match rest[0] {
+            byte @ 0x80..=0x9F => (1, WINDOWS_1252_HIGH[usize::from(byte - 0x80)]),
+            byte => (1, Some(char::from(byte))),
+        },
+        Encoding::Utf8 => {
+            let chunk = &rest[..rest.len().min(4)];
+            let valid = match std::str::from_utf8(chunk) {
+                Ok(text) => text,
+                Err(e) if e.valid_up_to() > 0 => std::str::from_utf8(&chunk[..e.valid_up_to()]).unwrap_or(""),
+                // error_len is None when the input ends partway through a char.
+                Err(e) => return (e.error_len().unwrap_or(chunk.len()), None),
+            };
+            match valid.chars().next() {
+                Some(c) => (c.len_utf8(), Some(c)),
+                None => (1, None),
+            }
+        }
+        Encoding::Utf16Le | Encoding::Utf16Be => {
+            let unit = |i: usize| -> Option<u16> {
+                let pair = [*rest.get(i)?, *rest.get(i + 1)?];
+                Some(if encoding == Encoding::Utf16Le {
+                    u16::from_le_bytes(pair)
+                } else {
+                    u16::from_be_bytes(pair)
+                })
+            };
+            let first = match unit(0) {
+                Some(first) => first,
+                None => return (rest.len(), None),
+            };
+            match char::decode_utf16([first, unit(2).unwrap_or(0)]).next() {
+                Some(Ok(c)) => (c.len_utf16() * 2, Some(c)),
+                _ => (2, None),
+            }
+        }
+    }
+}
+
+/// Iterates over the chars in the bytes, as (byte offset, byte length, char). The char is None
+/// for bytes that aren't valid in the encoding.
+pub fn decode_chars(bytes: &[u8], encoding: Encoding) -> impl Iterator<Item = (usize, usize, Option<char>)> + '_ {
+    let mut offset = 0;
+    std::iter::from_fn(move || {
+        if offset >= bytes.len() {
+            return None;
+        }
+        let (len, c) = decode_one(bytes, offset, encoding);
+        let start = offset;
+        offset += len;
+        Some((start, len, c))
+    })
+}
+
+pub fn decode(bytes: &[u8], encoding: Encoding, mode: Mode) -> Result<String, DecodeError> {
+    let mut text = String::with_capacity(bytes.len());
+    for (offset, len, c) in decode_chars(bytes, encoding) {
+        match (c, mode) {
+            (Some(c), _) => text.push(c),
+            (None, Mode::Lossy) => text.push(char::REPLACEMENT_CHARACTER),
+            (None, Mode::Strict) => {
+                return Err(DecodeError {
+                    encoding,
+                    offset,
+                    bytes: bytes[offset..offset + len].to_vec(),
+                })
+            }
+        }
+    }
+    Ok(text)
+}
+
+/// Decodes using the byte order mark if there is one, and `fallback` if not. The BOM itself
+/// isn't included in the text. Returns the encoding that was used.
+pub fn decode_with_bom(bytes: &[u8], fallback: Encoding, mode: Mode) -> Result<(String, Encoding), DecodeError> {
+    let (encoding, skip) = detect_bom(bytes).unwrap_or((fallback, 0));
+    match decode(&bytes[skip..], encoding, mode) {
+        Ok(text) => Ok((text, encoding)),
+        // Report offsets from the start of the input, BOM included.
+        Err(e) => Err(DecodeError {
+            offset: e.offset + skip,
+            ..e
+        }),
+    }
+}
+
+fn encode_one(c: char, encoding: Encoding, out: &mut Vec<u8>) -> bool {
+    match encoding {
+        Encoding::Utf8 => {
+            out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+        }
+        Encoding::Utf16Le | Encoding::Utf16Be => {
+            for unit in c.encode_utf16(&mut [0; 2]) {
+                if encoding == Encoding::Utf16Le {
+                    out.extend_from_slice(&unit.to_le_bytes());
+                } else {
+                    out.extend_from_slice(&unit.to_be_bytes());
+                }
+            }
+        }
+        Encoding::Latin1 => match u8::try_from(u32::from(c)) {
+            Ok(byte) => out.push(byte),
+            Err(_) => return false,
+        },
+        Encoding::Windows1252 => {
+            let high = WINDOWS_1252_HIGH.iter().position(|&mapped| mapped == Some(c));
+            match (high, u8::try_from(u32::from(c))) {
+                (Some(index), _) => out.push(0x80 + index as u8),
+                // 0x80 to 0x9F hold other characters, so those code points can't be written.
+                (None, Ok(byte)) if !(0x80..=0x9F).contains(&byte) => out.push(byte),
+                _ => return false,
+            }
+        }
+    }
+    true
+}
+
+/// Encodes without a byte order mark; prepend `encoding.bom()` if you want one.
+pub fn encode(text: &str, encoding: Encoding, mode: Mode) -> Result<Vec<u8>, EncodeError> {
+    let mut out = Vec::with_capacity(text.len());
+    for (offset, c) in text.char_indices() {
+        if encode_one(c, encoding, &mut out) {
+            continue;
+        }
+        match mode {
+            Mode::Lossy => out.push(b'?'),
+            Mode::Strict => {
+                return Err(EncodeError {
+                    encoding,
+                    offset,
+                    character: c,
+                })
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Lays the bytes out like `hexdump -C`: the offset, sixteen bytes in hex, then the decoded
+/// text. Each char is shown under its first byte; invalid bytes, controls and chars that don't
+/// fit at the end of a row are shown as '.'.
+pub fn hexdump(bytes: &[u8], encoding: Encoding) -> String {
+    const ROW: usize = 16;
+
+    // One cell of text per byte, so the text column lines up with the hex.
+    let mut cells: Vec<String> = vec![String::new(); bytes.len()];
+    for (offset, len, c) in decode_chars(bytes, encoding) {
+        let shown = c.filter(|c| !c.is_control()).map(String::from).unwrap_or_default();
+        let width = grapheme::width(&shown);
+        let room = (offset / ROW + 1) * ROW - offset;
+        if width == 0 || width > len.min(room) {
+            cells[offset] = String::from(".");
+            for cell in &mut cells[offset + 1..offset + len] {
+                *cell = String::from(" ");
+            }
+            continue;
+        }
+        cells[offset] = shown;
+        // A wide char uses up the next cell's column as well.
+        for (i, cell) in cells[offset + 1..offset + len].iter_mut().enumerate() {
+            *cell = if i + 1 < width { String::new() } else { String::from(" ") };
+        }
+    }
+
+    let mut out = String::new();
+    for (row, chunk) in bytes.chunks(ROW).enumerate() {
+        let mut hex = String::new();
+        for (i, byte) in chunk.iter().enumerate() {
+            if i == ROW / 2 {
+                hex.push(' ');
+            }
+            hex.push_str(&format!("{:02x} ", byte));
+        }
+        let start = row * ROW;
+        let text: String = cells[start..start + chunk.len()].concat();
+        out.push_str(&format!("{:08x}  {:<49} |{}|\n", start, hex, text));
+    }
+    out.push_str(&format!("{:08x}\n", bytes.len()));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_error(bytes: &[u8], encoding: Encoding) -> (usize, Vec<u8>) {
+        let e = decode(bytes, encoding, Mode::Strict).unwrap_err();
+        assert_eq!(e.encoding, encoding);
+        (e.offset, e.bytes)
+    }
+
+    #[test]
+    fn labels() {
+        assert_eq!("UTF-8".parse(), Ok(Encoding::Utf8));
+        assert_eq!("iso_8859_1".parse(), Ok(Encoding::Latin1));
+        assert_eq!("CP1252".parse(), Ok(Encoding::Windows1252));
+        assert_eq!("utf-16".parse(), Ok(Encoding::Utf16Le));
+        assert_eq!("UTF-16BE".parse(), Ok(Encoding::Utf16Be));
+        assert_eq!("ebcdic".parse::<Encoding>(), Err(UnknownEncoding(String::from("ebcdic"))));
+        for encoding in [Encoding::Utf8, Encoding::Latin1, Encoding::Windows1252, Encoding::Utf16Le, Encoding::Utf16Be] {
+            assert_eq!(encoding.to_string().parse(), Ok(encoding));
+        }
+    }
+
+    #[test]
+    fn latin1_round_trips_every_byte() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        let text = decode(&bytes, Encoding::Latin1, Mode::Strict).unwrap();
+        assert_eq!(text.chars().count(), 256);
+        assert_eq!(text.chars().nth(0x80), Some('\u{80}'));
+        assert_eq!(text.chars().nth(0xE9), Some('é'));
+        assert_eq!(encode(&text, Encoding::Latin1, Mode::Strict).unwrap(), bytes);
+
+        let e = encode("naïve €5", Encoding::Latin1, Mode::Strict).unwrap_err();
+        assert_eq!((e.offset, e.character), (7, '€'));
+        assert_eq!(encode("naïve €5", Encoding::Latin1, Mode::Lossy).unwrap(), b"na\xEFve ?5");
+    }
+
+    #[test]
+    fn windows_1252_round_trips_all_but_its_holes() {
+        let holes = [0x81, 0x8D, 0x8F, 0x90, 0x9D];
+        for byte in 0..=255u8 {
+            let decoded = decode(&[byte], Encoding::Windows1252, Mode::Strict);
+            if holes.contains(&byte) {
+                assert_eq!(decoded, Err(DecodeError { encoding: Encoding::Windows1252, offset: 0, bytes: vec![byte] }));
+                assert_eq!(decode(&[byte], Encoding::Windows1252, Mode::Lossy).unwrap(), "\u{FFFD}");
+                continue;
+            }
+            let text = decoded.unwrap();
+            assert_eq!(encode(&text, Encoding::Windows1252, Mode::Strict).unwrap(), [byte], "{:02X}", byte);
+        }
+        assert_eq!(decode(b"\x80 \x93hi\x94", Encoding::Windows1252, Mode::Strict).unwrap(), "€ “hi”");
+        assert_eq!(decode_error(b"ok\x81", Encoding::Windows1252), (2, vec![0x81]));
+
+        // The C1 controls that Latin-1 puts at 0x80 to 0x9F have no byte in Windows-1252.
+        let e = encode("a\u{81}", Encoding::Windows1252, Mode::Strict).unwrap_err();
+        assert_eq!((e.offset, e.character), (1, '\u{81}'));
+        assert_eq!(encode("€\u{9D}ÿ", Encoding::Windows1252, Mode::Lossy).unwrap(), [0x80, b'?', 0xFF]);
+        assert!(encode("😀", Encoding::Windows1252, Mode::Strict).is_err());
+    }
+
+    #[test]
+    fn utf16_both_byte_orders() {
+        let text = "Aé€😀";
+        let le = encode(text, Encoding::Utf16Le, Mode::Strict).unwrap();
+        let be = encode(text, Encoding::Utf16Be, Mode::Strict).unwrap();
+        assert_eq!(le, [0x41, 0, 0xE9, 0, 0xAC, 0x20, 0x3D, 0xD8, 0x00, 0xDE]);
+        assert_eq!(be, [0, 0x41, 0, 0xE9, 0x20, 0xAC, 0xD8, 0x3D, 0xDE, 0x00]);
+        assert_eq!(decode(&le, Encoding::Utf16Le, Mode::Strict).unwrap(), text);
+        assert_eq!(decode(&be, Encoding::Utf16Be, Mode::Strict).unwrap(), text);
+
+        // Without a BOM, the fallback decides.
+        assert_eq!(decode_with_bom(&le, Encoding::Utf16Le, Mode::Strict).unwrap(), (String::from(text), Encoding::Utf16Le));
+        // With one, the BOM wins, so a plain "utf-16" label still reads big-endian files.
+        let mut with_bom = Encoding::Utf16Be.bom().to_vec();
+        with_bom.extend_from_slice(&be);
+        let fallback: Encoding = "utf16".parse().unwrap();
+        assert_eq!(decode_with_bom(&with_bom, fallback, Mode::Strict).unwrap(), (String::from(text), Encoding::Utf16Be));
+        let mut with_bom = Encoding::Utf16Le.bom().to_vec();
+        with_bom.extend_from_slice(&le);
+        assert_eq!(decode_with_bom(&with_bom, Encoding::Utf8, Mode::Strict).unwrap(), (String::from(text), Encoding::Utf16Le));
+    }
+
+    #[test]
+    fn utf16_errors() {
+        // An odd byte at the end.
+        assert_eq!(decode_error(&[0x41, 0, 0x42], Encoding::Utf16Le), (2, vec![0x42]));
+        assert_eq!(decode(&[0x41, 0, 0x42], Encoding::Utf16Le, Mode::Lossy).unwrap(), "A\u{FFFD}");
+        // A high surrogate followed by something else, or by nothing.
+        assert_eq!(decode_error(&[0x3D, 0xD8, 0x41, 0], Encoding::Utf16Le), (0, vec![0x3D, 0xD8]));
+        assert_eq!(decode(&[0x3D, 0xD8, 0x41, 0], Encoding::Utf16Le, Mode::Lossy).unwrap(), "\u{FFFD}A");
+        assert_eq!(decode_error(&[0, 0x41, 0xD8, 0x3D], Encoding::Utf16Be), (2, vec![0xD8, 0x3D]));
+        // A low surrogate on its own.
+        assert_eq!(decode_error(&[0xDE, 0x00, 0, 0x41], Encoding::Utf16Be), (0, vec![0xDE, 0x00]));
+        // Offsets count the BOM.
+        let e = decode_with_bom(&[0xFF, 0xFE, 0x41, 0, 0x00, 0xDC], Encoding::Utf8, Mode::Strict).unwrap_err();
+        assert_eq!(e.to_string(), "invalid UTF-16LE at byte 4: 00 DC");
+        assert_eq!((e.encoding, e.offset, e.bytes), (Encoding::Utf16Le, 4, vec![0x00, 0xDC]));
+    }
+
+    #[test]
+    fn utf8_errors() {
+        assert_eq!(decode_error(b"ab\xFFc", Encoding::Utf8), (2, vec![0xFF]));
+        // An encoded surrogate is three bad bytes, one at a time.
+        assert_eq!(decode_error(b"\xED\xA0\x80", Encoding::Utf8), (0, vec![0xED]));
+        assert_eq!(decode(b"\xED\xA0\x80", Encoding::Utf8, Mode::Lossy).unwrap(), "\u{FFFD}\u{FFFD}\u{FFFD}");
+        // A char cut off at the end is one error.
+        assert_eq!(decode_error("é€".as_bytes()[..4].as_ref(), Encoding::Utf8), (2, vec![0xE2, 0x82]));
+        assert_eq!(decode(b"\xEF\xBB\xBFhi", Encoding::Utf8, Mode::Strict).unwrap(), "\u{FEFF}hi");
+        assert_eq!(decode_with_bom(b"\xEF\xBB\xBFhi", Encoding::Latin1, Mode::Strict).unwrap(), (String::from("hi"), Encoding::Utf8));
+        assert_eq!(detect_bom(b"\xEF\xBB"), None);
+        assert_eq!(detect_bom(&[0xFE, 0xFF, 0]), Some((Encoding::Utf16Be, 2)));
+    }
+
+    #[test]
+    fn hexdump_layout() {
+        let dump = hexdump("Hola, 世界!\n".as_bytes(), Encoding::Utf8);
+        assert_eq!(
+            dump,
+            "00000000  48 6f 6c 61 2c 20 e4 b8  96 e7 95 8c 21 0a        |Hola, 世 界 !.|\n0000000e\n"
+        );
+        assert_eq!(hexdump(&[], Encoding::Utf8), "00000000\n");
+    }
+}
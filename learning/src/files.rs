@@ -0,0 +1,26 @@
+// File reading helpers from chapter 9.
+
+use std::fs::{self, File};
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+/// Reads the whole file into a String. The chapter's `read_username_from_file`, minus the
+/// hard-coded hello.txt.
+pub fn read_username_from_file<P: AsRef<Path>>(path: P) -> Result<String, io::Error> {
+    fs::read_to_string(path)
+}
+
+/// Opens a file, creating it first if it doesn't exist yet.
+pub fn open_or_create<P: AsRef<Path>>(path: P) -> Result<File, io::Error> {
+    let path = path.as_ref();
+    match File::open(path) {
+        Ok(file) => Ok(file),
+        Err(error) if error.kind() == ErrorKind::NotFound => File::create(path),
+        Err(error) => Err(error),
+    }
+}
+
+/// The last character of the first line, if there is one.
+pub fn last_char_of_first_line(text: &str) -> Option<char> {
+    text.lines().next()?.chars().last()
+}
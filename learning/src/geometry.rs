@@ -0,0 +1,554 @@
+// Geometry grown out of the chapter 5 Rectangle example.
+//
+// The integer Rectangle keeps the chapter's u32 width and height, adds a position so that
+// intersection and union make sense, and reports overflow through Option instead of panicking.
+// Every shape also implements Shape, which works in f64.
+
+/// A point in the plane.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Point2 {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point2 {
+    pub fn new(x: f64, y: f64) -> Point2 {
+        Point2 { x, y }
+    }
+}
+
+/// Axis-aligned box given by its minimum and maximum corners.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct BoundingBox {
+    pub min: Point2,
+    pub max: Point2,
+}
+
+impl BoundingBox {
+    pub fn width(&self) -> f64 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max.y - self.min.y
+    }
+}
+
+pub trait Shape {
+    fn area(&self) -> f64;
+    fn perimeter(&self) -> f64;
+    fn bounding_box(&self) -> BoundingBox;
+    /// Points on the boundary count as inside.
+    fn contains(&self, point: Point2) -> bool;
+}
+
+/// Integer rectangle. `x` and `y` are the top-left corner, so `Rectangle::new(30, 50)` is the
+/// chapter 5 rectangle sitting at the origin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Rectangle {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rectangle {
+    pub fn new(width: u32, height: u32) -> Rectangle {
+        Rectangle {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }
+    }
+
+    pub fn at(x: i32, y: i32, width: u32, height: u32) -> Rectangle {
+        Rectangle {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn square(size: u32) -> Rectangle {
+        Rectangle::new(size, size)
+    }
+
+    // Edges are computed in i64 so a rectangle near i32::MAX never overflows.
+    pub fn left(&self) -> i64 {
+        self.x as i64
+    }
+
+    pub fn top(&self) -> i64 {
+        self.y as i64
+    }
+
+    pub fn right(&self) -> i64 {
+        self.x as i64 + self.width as i64
+    }
+
+    pub fn bottom(&self) -> i64 {
+        self.y as i64 + self.height as i64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Same as `area(&Rectangle)` in main.rs, but `None` instead of a panic when it won't fit a u32.
+    pub fn checked_area(&self) -> Option<u32> {
+        self.width.checked_mul(self.height)
+    }
+
+    /// Area that can never overflow.
+    pub fn area_u64(&self) -> u64 {
+        self.width as u64 * self.height as u64
+    }
+
+    pub fn checked_perimeter(&self) -> Option<u32> {
+        self.width.checked_add(self.height)?.checked_mul(2)
+    }
+
+    /// True when `other` fits strictly inside this rectangle, as in the book.
+    pub fn can_hold(&self, other: &Rectangle) -> bool {
+        self.width > other.width && self.height > other.height
+    }
+
+    /// True when `other` fits without rotation, touching edges allowed.
+    pub fn can_fit(&self, other: &Rectangle) -> bool {
+        self.width >= other.width && self.height >= other.height
+    }
+
+    pub fn contains_point(&self, x: i32, y: i32) -> bool {
+        let (x, y) = (x as i64, y as i64);
+        x >= self.left() && x <= self.right() && y >= self.top() && y <= self.bottom()
+    }
+
+    /// True when `other` lies completely within this rectangle's area.
+    pub fn contains_rect(&self, other: &Rectangle) -> bool {
+        other.left() >= self.left()
+            && other.top() >= self.top()
+            && other.right() <= self.right()
+            && other.bottom() <= self.bottom()
+    }
+
+    /// The overlapping region, or `None` if the rectangles only touch or don't meet at all.
+    pub fn intersection(&self, other: &Rectangle) -> Option<Rectangle> {
+        let left = self.left().max(other.left());
+        let top = self.top().max(other.top());
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+
+        if right <= left || bottom <= top {
+            return None;
+        }
+
+        from_edges(left, top, right, bottom)
+    }
+
+    /// The smallest rectangle covering both, or `None` if it is too big for i32/u32.
+    pub fn union(&self, other: &Rectangle) -> Option<Rectangle> {
+        let left = self.left().min(other.left());
+        let top = self.top().min(other.top());
+        let right = self.right().max(other.right());
+        let bottom = self.bottom().max(other.bottom());
+
+        from_edges(left, top, right, bottom)
+    }
+
+    pub fn to_f64(&self) -> RectangleF {
+        RectangleF {
+            x: self.x as f64,
+            y: self.y as f64,
+            width: self.width as f64,
+            height: self.height as f64,
+        }
+    }
+}
+
+fn from_edges(left: i64, top: i64, right: i64, bottom: i64) -> Option<Rectangle> {
+    Some(Rectangle {
+        x: i32::try_from(left).ok()?,
+        y: i32::try_from(top).ok()?,
+        width: u32::try_from(right - left).ok()?,
+        height: u32::try_from(bottom - top).ok()?,
+    })
+}
+
+impl Shape for Rectangle {
+    fn area(&self) -> f64 {
+        self.area_u64() as f64
+    }
+
+    fn perimeter(&self) -> f64 {
+        2.0 * (self.width as f64 + self.height as f64)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        self.to_f64().bounding_box()
+    }
+
+    fn contains(&self, point: Point2) -> bool {
+        self.to_f64().contains(point)
+    }
+}
+
+/// Floating point counterpart of `Rectangle`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RectangleF {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl RectangleF {
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> RectangleF {
+        RectangleF {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn right(&self) -> f64 {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> f64 {
+        self.y + self.height
+    }
+
+    pub fn can_hold(&self, other: &RectangleF) -> bool {
+        self.width > other.width && self.height > other.height
+    }
+
+    pub fn intersection(&self, other: &RectangleF) -> Option<RectangleF> {
+        let left = self.x.max(other.x);
+        let top = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+
+        if right <= left || bottom <= top {
+            return None;
+        }
+
+        Some(RectangleF::new(left, top, right - left, bottom - top))
+    }
+
+    /// `None` only when the result isn't finite.
+    pub fn union(&self, other: &RectangleF) -> Option<RectangleF> {
+        let left = self.x.min(other.x);
+        let top = self.y.min(other.y);
+        let right = self.right().max(other.right());
+        let bottom = self.bottom().max(other.bottom());
+
+        let union = RectangleF::new(left, top, right - left, bottom - top);
+        if union.width.is_finite() && union.height.is_finite() {
+            Some(union)
+        } else {
+            None
+        }
+    }
+}
+
+impl Shape for RectangleF {
+    fn area(&self) -> f64 {
+        self.width * self.height
+    }
+
+    fn perimeter(&self) -> f64 {
+        2.0 * (self.width + self.height)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox {
+            min: Point2::new(self.x, self.y),
+            max: Point2::new(self.right(), self.bottom()),
+        }
+    }
+
+    fn contains(&self, point: Point2) -> bool {
+        point.x >= self.x && point.x <= self.right() && point.y >= self.y && point.y <= self.bottom()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Circle {
+    pub center: Point2,
+    pub radius: f64,
+}
+
+impl Circle {
+    pub fn new(center: Point2, radius: f64) -> Circle {
+        Circle { center, radius }
+    }
+}
+
+impl Shape for Circle {
+    fn area(&self) -> f64 {
+        std::f64::consts::PI * self.radius * self.radius
+    }
+
+    fn perimeter(&self) -> f64 {
+        2.0 * std::f64::consts::PI * self.radius
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        BoundingBox {
+            min: Point2::new(self.center.x - self.radius, self.center.y - self.radius),
+            max: Point2::new(self.center.x + self.radius, self.center.y + self.radius),
+        }
+    }
+
+    fn contains(&self, point: Point2) -> bool {
+        let dx = point.x - self.center.x;
+        let dy = point.y - self.center.y;
+        dx * dx + dy * dy <= self.radius * self.radius
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Triangle {
+    pub a: Point2,
+    pub b: Point2,
+    pub c: Point2,
+}
+
+impl Triangle {
+    pub fn new(a: Point2, b: Point2, c: Point2) -> Triangle {
+        Triangle { a, b, c }
+    }
+}
+
+impl Shape for Triangle {
+    fn area(&self) -> f64 {
+        (cross(self.a, self.b, self.c) / 2.0).abs()
+    }
+
+    fn perimeter(&self) -> f64 {
+        distance(self.a, self.b) + distance(self.b, self.c) + distance(self.c, self.a)
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        bounding_box_of(&[self.a, self.b, self.c])
+    }
+
+    fn contains(&self, point: Point2) -> bool {
+        // A triangle with no area is a line segment, or a single point, and every point would be
+        // on the "same side" of its edges.
+        if cross(self.a, self.b, self.c) == 0.0 {
+            return on_segment(self.a, self.b, point)
+                || on_segment(self.b, self.c, point)
+                || on_segment(self.c, self.a, point);
+        }
+
+        // The point is inside when it is on the same side of all three edges.
+        let d1 = cross(self.a, self.b, point);
+        let d2 = cross(self.b, self.c, point);
+        let d3 = cross(self.c, self.a, point);
+
+        let has_negative = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+        let has_positive = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+        !(has_negative && has_positive)
+    }
+}
+
+/// A simple polygon given by its vertices in order. The last vertex joins back to the first.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Polygon {
+    pub vertices: Vec<Point2>,
+}
+
+impl Polygon {
+    pub fn new(vertices: Vec<Point2>) -> Polygon {
+        Polygon { vertices }
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (Point2, Point2)> + '_ {
+        let n = self.vertices.len();
+        (0..n).map(move |i| (self.vertices[i], self.vertices[(i + 1) % n]))
+    }
+}
+
+impl Shape for Polygon {
+    // Shoelace formula.
+    fn area(&self) -> f64 {
+        let twice: f64 = self.edges().map(|(p, q)| p.x * q.y - q.x * p.y).sum();
+        (twice / 2.0).abs()
+    }
+
+    fn perimeter(&self) -> f64 {
+        self.edges().map(|(p, q)| distance(p, q)).sum()
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        bounding_box_of(&self.vertices)
+    }
+
+    // Ray casting, with an explicit check so points on an edge count as inside.
+    fn contains(&self, point: Point2) -> bool {
+        if self.vertices.len() < 3 {
+            return false;
+        }
+
+        let mut inside = false;
+        for (p, q) in self.edges() {
+            if on_segment(p, q, point) {
+                return true;
+            }
+            if (p.y > point.y) != (q.y > point.y) {
+                let x_cross = p.x + (point.y - p.y) * (q.x - p.x) / (q.y - p.y);
+                if point.x < x_cross {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    }
+}
+
+fn distance(p: Point2, q: Point2) -> f64 {
+    (q.x - p.x).hypot(q.y - p.y)
+}
+
+// Twice the signed area of the triangle o, a, b.
+fn cross(o: Point2, a: Point2, b: Point2) -> f64 {
+    (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
+
+fn on_segment(p: Point2, q: Point2, point: Point2) -> bool {
+    cross(p, q, point).abs() <= f64::EPSILON * (1.0 + distance(p, q))
+        && point.x >= p.x.min(q.x)
+        && point.x <= p.x.max(q.x)
+        && point.y >= p.y.min(q.y)
+        && point.y <= p.y.max(q.y)
+}
+
+fn bounding_box_of(points: &[Point2]) -> BoundingBox {
+    let mut points = points.iter();
+    let first = match points.next() {
+        Some(first) => *first,
+        None => return BoundingBox::default(),
+    };
+
+    points.fold(
+        BoundingBox {
+            min: first,
+            max: first,
+        },
+        |bounds, p| BoundingBox {
+            min: Point2::new(bounds.min.x.min(p.x), bounds.min.y.min(p.y)),
+            max: Point2::new(bounds.max.x.max(p.x), bounds.max.y.max(p.y)),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(x: f64, y: f64) -> Point2 {
+        Point2::new(x, y)
+    }
+
+    #[test]
+    fn areas_and_perimeters() {
+        let rect = Rectangle::at(-5, 2, 30, 50);
+        assert_eq!((rect.area(), rect.perimeter()), (1500.0, 160.0));
+        let rect = RectangleF::new(0.0, 0.0, 2.5, 4.0);
+        assert_eq!((rect.area(), rect.perimeter()), (10.0, 13.0));
+        let circle = Circle::new(point(1.0, 1.0), 2.0);
+        assert!((circle.area() - 4.0 * std::f64::consts::PI).abs() < 1e-12);
+        assert!((circle.perimeter() - 4.0 * std::f64::consts::PI).abs() < 1e-12);
+
+        let triangle = Triangle::new(point(0.0, 0.0), point(4.0, 0.0), point(0.0, 3.0));
+        assert_eq!((triangle.area(), triangle.perimeter()), (6.0, 12.0));
+        // Clockwise vertices give the same area.
+        let triangle = Triangle::new(point(0.0, 0.0), point(0.0, 3.0), point(4.0, 0.0));
+        assert_eq!(triangle.area(), 6.0);
+
+        let square = Polygon::new(vec![point(0.0, 0.0), point(2.0, 0.0), point(2.0, 2.0), point(0.0, 2.0)]);
+        assert_eq!((square.area(), square.perimeter()), (4.0, 8.0));
+        assert_eq!(Polygon::default().area(), 0.0);
+    }
+
+    #[test]
+    fn triangle_contains_its_edges_and_vertices() {
+        let triangle = Triangle::new(point(0.0, 0.0), point(4.0, 0.0), point(0.0, 4.0));
+        for inside in [point(1.0, 1.0), point(0.0, 0.0), point(4.0, 0.0), point(0.0, 4.0)] {
+            assert!(triangle.contains(inside), "{:?}", inside);
+        }
+        // On each of the three edges.
+        for edge in [point(2.0, 0.0), point(2.0, 2.0), point(0.0, 1.5)] {
+            assert!(triangle.contains(edge), "{:?}", edge);
+        }
+        for outside in [point(3.0, 3.0), point(-0.1, 1.0), point(5.0, 0.0), point(2.0, -0.5)] {
+            assert!(!triangle.contains(outside), "{:?}", outside);
+        }
+    }
+
+    #[test]
+    fn flat_triangles_only_contain_their_segment() {
+        let flat = Triangle::new(point(0.0, 0.0), point(2.0, 2.0), point(4.0, 4.0));
+        assert_eq!(flat.area(), 0.0);
+        assert!(flat.contains(point(1.0, 1.0)));
+        assert!(flat.contains(point(4.0, 4.0)));
+        assert!(!flat.contains(point(5.0, 5.0)));
+        assert!(!flat.contains(point(1.0, 0.0)));
+        assert!(!flat.contains(point(-3.0, 7.0)));
+
+        let dot = Triangle::new(point(1.0, 1.0), point(1.0, 1.0), point(1.0, 1.0));
+        assert!(dot.contains(point(1.0, 1.0)));
+        assert!(!dot.contains(point(1.0, 2.0)));
+    }
+
+    #[test]
+    fn polygon_contains() {
+        let l_shape = Polygon::new(vec![
+            point(0.0, 0.0),
+            point(4.0, 0.0),
+            point(4.0, 1.0),
+            point(1.0, 1.0),
+            point(1.0, 4.0),
+            point(0.0, 4.0),
+        ]);
+        assert!(l_shape.contains(point(0.5, 3.0)));
+        assert!(l_shape.contains(point(2.0, 1.0)));
+        assert!(!l_shape.contains(point(2.0, 2.0)));
+        assert!(!Polygon::new(vec![point(0.0, 0.0), point(1.0, 1.0)]).contains(point(0.5, 0.5)));
+    }
+
+    #[test]
+    fn checked_area_and_perimeter_at_the_limits() {
+        assert_eq!(Rectangle::new(65_536, 65_535).checked_area(), Some(4_294_901_760));
+        assert_eq!(Rectangle::new(65_536, 65_536).checked_area(), None);
+        assert_eq!(Rectangle::new(u32::MAX, 1).checked_area(), Some(u32::MAX));
+        assert_eq!(Rectangle::new(u32::MAX, u32::MAX).area_u64(), u32::MAX as u64 * u32::MAX as u64);
+
+        assert_eq!(Rectangle::new(2_147_483_647, 0).checked_perimeter(), Some(4_294_967_294));
+        assert_eq!(Rectangle::new(2_147_483_647, 1).checked_perimeter(), None);
+        assert_eq!(Rectangle::new(u32::MAX, 1).checked_perimeter(), None);
+    }
+
+    #[test]
+    fn union_and_intersection_at_the_limits() {
+        let left = Rectangle::at(i32::MIN, 0, 1, 1);
+        let right = Rectangle::at(i32::MAX - 1, 0, 1, 1);
+        assert_eq!(left.union(&right), Some(Rectangle::at(i32::MIN, 0, u32::MAX, 1)));
+        // One more column is wider than a u32.
+        assert_eq!(left.union(&Rectangle::at(i32::MAX, 0, 1, 1)), None);
+        // The right edge is past i32::MAX, but that's fine as long as x fits.
+        let far = Rectangle::at(i32::MAX, i32::MAX, u32::MAX, u32::MAX);
+        assert_eq!(far.intersection(&far), Some(far));
+        assert!(far.contains_point(i32::MAX, i32::MAX));
+        assert!(far.contains_rect(&Rectangle::at(i32::MAX, i32::MAX, 1, 1)));
+
+        let a = Rectangle::at(0, 0, 10, 10);
+        assert_eq!(a.intersection(&Rectangle::at(5, 5, 10, 10)), Some(Rectangle::at(5, 5, 5, 5)));
+        assert_eq!(a.intersection(&Rectangle::at(10, 0, 5, 5)), None);
+        assert_eq!(a.union(&Rectangle::at(-5, 20, 1, 1)), Some(Rectangle::at(-5, 0, 15, 21)));
+    }
+}
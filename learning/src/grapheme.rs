@@ -0,0 +1,374 @@
+// Grapheme clusters, the user-perceived characters of Unicode Standard Annex #29.
+//
+// Chapter 8 iterates "Hola" with chars() and bytes(), which works because every letter is one
+// char. "नमस्ते" is six chars, and a family emoji is five chars joined with zero-width joiners.
+// This module splits text with the extended grapheme cluster rules, including Unicode 15.1's rule
+// GB9c that keeps the conjunct "स्ते" together, so "नमस्ते" is three clusters, and measures how
+// many terminal columns it takes up.
+//
+// The lookup tables in grapheme/tables.rs are generated from learning/data/unicode by
+// examples/generate_unicode_tables.rs. The data there is Unicode 14, with the Indic_Conjunct_Break
+// property that GB9c needs derived from it the way 15.1 defines it.
+
+mod tables;
+
+use std::iter::FusedIterator;
+use std::ops::Range;
+
+pub use tables::UNICODE_VERSION;
+
+/// The Grapheme_Cluster_Break property of a char.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GraphemeBreak {
+    Other,
+    Prepend,
+    Cr,
+    Lf,
+    Control,
+    Extend,
+    RegionalIndicator,
+    SpacingMark,
+    /// Hangul syllable pieces: leading consonant, vowel, trailing consonant, and the
+    /// precomposed LV and LVT syllables.
+    L,
+    V,
+    T,
+    Lv,
+    Lvt,
+    Zwj,
+}
+
+/// The Indic_Conjunct_Break property of a char, which rule GB9c uses to keep consonants joined by
+/// a virama in one cluster.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IndicConjunctBreak {
+    None,
+    /// A virama that joins the consonants on either side.
+    Linker,
+    Consonant,
+    /// A mark that can sit between a consonant and the linker, like a nukta.
+    Extend,
+}
+
+fn in_table(table: &[(u32, u32)], c: char) -> bool {
+    let c = c as u32;
+    table
+        .binary_search_by(|&(start, end)| {
+            if end < c {
+                std::cmp::Ordering::Less
+            } else if start > c {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .is_ok()
+}
+
+pub fn grapheme_break(c: char) -> GraphemeBreak {
+    let code = c as u32;
+    // Plain ASCII is by far the most common case, so skip the search for it.
+    if (0x20..0x7F).contains(&code) {
+        return GraphemeBreak::Other;
+    }
+    let index = tables::GRAPHEME_BREAK.partition_point(|&(_, end, _)| end < code);
+    match tables::GRAPHEME_BREAK.get(index) {
+        Some(&(start, _, value)) if start <= code => value,
+        _ => GraphemeBreak::Other,
+    }
+}
+
+pub fn indic_conjunct_break(c: char) -> IndicConjunctBreak {
+    let code = c as u32;
+    let index = tables::INDIC_CONJUNCT_BREAK.partition_point(|&(_, end, _)| end < code);
+    match tables::INDIC_CONJUNCT_BREAK.get(index) {
+        Some(&(start, _, value)) if start <= code => value,
+        _ => IndicConjunctBreak::None,
+    }
+}
+
+pub fn is_extended_pictographic(c: char) -> bool {
+    in_table(tables::EXTENDED_PICTOGRAPHIC, c)
+}
+
+fn is_boundary(
+    prev: GraphemeBreak,
+    next: GraphemeBreak,
+    linked_consonant: bool,
+    emoji_zwj: bool,
+    odd_regional: bool,
+) -> bool {
+    use GraphemeBreak::*;
+
+    match (prev, next) {
+        // GB3 to GB5: CR LF stays together, otherwise controls stand alone.
+        (Cr, Lf) => false,
+        (Cr | Lf | Control, _) | (_, Cr | Lf | Control) => true,
+        // GB6 to GB8: Hangul syllable sequences.
+        (L, L | V | Lv | Lvt) | (Lv | V, V | T) | (Lvt | T, T) => false,
+        // GB9 to GB9b: marks attach to what comes before, prepended chars to what follows.
+        (_, Extend | Zwj | SpacingMark) | (Prepend, _) => false,
+        // GB9c: a consonant joined to the one before it by a virama.
+        _ if linked_consonant => false,
+        // GB11: emoji joined with ZWJ.
+        (Zwj, _) if emoji_zwj => false,
+        // GB12 and GB13: flags are pairs of regional indicators.
+        (RegionalIndicator, RegionalIndicator) => !odd_regional,
+        // GB999
+        _ => true,
+    }
+}
+
+/// Iterator over the grapheme clusters of a string, with their byte offsets.
+#[derive(Debug, Clone)]
+pub struct GraphemeIndices<'a> {
+    text: &'a str,
+    offset: usize,
+}
+
+impl<'a> Iterator for GraphemeIndices<'a> {
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<(usize, &'a str)> {
+        let rest = &self.text[self.offset..];
+        let mut chars = rest.char_indices();
+        let (_, first) = chars.next()?;
+
+        let mut prev = grapheme_break(first);
+        // Consonant [Extend Linker]* so far, and whether a Linker was among them, for GB9c.
+        let mut in_conjunct = indic_conjunct_break(first) == IndicConjunctBreak::Consonant;
+        let mut linked = false;
+        // Extended_Pictographic Extend* so far, for GB11.
+        let mut in_emoji = is_extended_pictographic(first);
+        let mut emoji_zwj = false;
+        // How many regional indicators in a row end at `prev`, for GB12 and GB13.
+        let mut regional = usize::from(prev == GraphemeBreak::RegionalIndicator);
+        let mut end = rest.len();
+
+        for (i, c) in chars {
+            let class = grapheme_break(c);
+            let pictographic = is_extended_pictographic(c);
+            let conjunct = indic_conjunct_break(c);
+            let linked_consonant = linked && conjunct == IndicConjunctBreak::Consonant;
+            if is_boundary(prev, class, linked_consonant, emoji_zwj && pictographic, regional % 2 == 1) {
+                end = i;
+                break;
+            }
+
+            (in_conjunct, linked) = match conjunct {
+                IndicConjunctBreak::Consonant => (true, false),
+                IndicConjunctBreak::Linker if in_conjunct => (true, true),
+                IndicConjunctBreak::Extend if in_conjunct => (true, linked),
+                _ => (false, false),
+            };
+
+            emoji_zwj = in_emoji && class == GraphemeBreak::Zwj;
+            in_emoji = pictographic || (in_emoji && class == GraphemeBreak::Extend);
+            regional = if class == GraphemeBreak::RegionalIndicator {
+                regional + 1
+            } else {
+                0
+            };
+            prev = class;
+        }
+
+        let start = self.offset;
+        self.offset += end;
+        Some((start, &rest[..end]))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.text.len() - self.offset;
+        (usize::from(remaining > 0), Some(remaining))
+    }
+}
+
+impl FusedIterator for GraphemeIndices<'_> {}
+
+/// Iterator over the grapheme clusters of a string.
+#[derive(Debug, Clone)]
+pub struct Graphemes<'a> {
+    inner: GraphemeIndices<'a>,
+}
+
+impl<'a> Iterator for Graphemes<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        self.inner.next().map(|(_, grapheme)| grapheme)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl FusedIterator for Graphemes<'_> {}
+
+pub fn graphemes(text: &str) -> Graphemes<'_> {
+    Graphemes {
+        inner: grapheme_indices(text),
+    }
+}
+
+pub fn grapheme_indices(text: &str) -> GraphemeIndices<'_> {
+    GraphemeIndices { text, offset: 0 }
+}
+
+pub fn grapheme_count(text: &str) -> usize {
+    graphemes(text).count()
+}
+
+/// The graphemes from `range.start` up to (not including) `range.end`, counted in graphemes
+/// rather than bytes. None if the range runs backwards or past the end.
+pub fn slice(text: &str, range: Range<usize>) -> Option<&str> {
+    if range.start > range.end {
+        return None;
+    }
+    let start = grapheme_offset(text, range.start)?;
+    let end = start + grapheme_offset(&text[start..], range.end - range.start)?;
+    Some(&text[start..end])
+}
+
+// Byte offset where grapheme number `index` starts; the length of the text for one past the end.
+fn grapheme_offset(text: &str, index: usize) -> Option<usize> {
+    let mut indices = grapheme_indices(text).map(|(offset, _)| offset).chain([text.len()]);
+    indices.nth(index)
+}
+
+/// Terminal columns taken up by one grapheme cluster: 0 for controls and stray marks, 2 for
+/// East Asian wide characters and emoji, and 1 for everything else.
+pub fn grapheme_width(grapheme: &str) -> usize {
+    let first = match grapheme.chars().next() {
+        Some(first) => first,
+        None => return 0,
+    };
+    match grapheme_break(first) {
+        GraphemeBreak::Cr
+        | GraphemeBreak::Lf
+        | GraphemeBreak::Control
+        | GraphemeBreak::Extend
+        | GraphemeBreak::Zwj => return 0,
+        _ => {}
+    }
+
+    let wide = grapheme.chars().any(|c| {
+        c == '\u{FE0F}' || in_table(tables::WIDE, c) || in_table(tables::EMOJI_PRESENTATION, c)
+    });
+    if wide {
+        2
+    } else {
+        1
+    }
+}
+
+/// Terminal columns the text takes up, for lining up columns of output.
+pub fn width(text: &str) -> usize {
+    graphemes(text).map(grapheme_width).sum()
+}
+
+/// Shortens the text to at most `max_width` columns, ending with `ellipsis` if anything was cut.
+/// Never splits a grapheme. Text that already fits comes back unchanged.
+pub fn truncate(text: &str, max_width: usize, ellipsis: &str) -> String {
+    if width(text) <= max_width {
+        return String::from(text);
+    }
+
+    let budget = max_width.saturating_sub(width(ellipsis));
+    let mut used = 0;
+    let mut end = 0;
+    for (offset, grapheme) in grapheme_indices(text) {
+        let w = grapheme_width(grapheme);
+        if used + w > budget {
+            break;
+        }
+        used += w;
+        end = offset + grapheme.len();
+    }
+
+    let mut truncated = String::from(&text[..end]);
+    if width(ellipsis) <= max_width {
+        truncated.push_str(ellipsis);
+    }
+    truncated
+}
+
+/// Pads with spaces on the right up to `width` columns. `format!("{:<10}", s)` counts chars, so
+/// it misaligns wide and combined characters.
+pub fn pad_right(text: &str, target: usize) -> String {
+    let padding = target.saturating_sub(width(text));
+    format!("{}{}", text, " ".repeat(padding))
+}
+
+/// Pads with spaces on the left up to `width` columns.
+pub fn pad_left(text: &str, target: usize) -> String {
+    let padding = target.saturating_sub(width(text));
+    format!("{}{}", " ".repeat(padding), text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Lines like "÷ 0020 × 0308 ÷ 0020 ÷": the text, and the clusters it should split into.
+    fn break_test_cases() -> Vec<(String, Vec<String>)> {
+        let data = include_str!("../data/unicode/GraphemeBreakTest.txt");
+        let mut cases = Vec::new();
+        for line in data.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut clusters: Vec<String> = Vec::new();
+            for token in line.split_whitespace() {
+                match token {
+                    "÷" => clusters.push(String::new()),
+                    "×" => {}
+                    hex => {
+                        let code = u32::from_str_radix(hex, 16).unwrap();
+                        clusters.last_mut().unwrap().push(char::from_u32(code).unwrap());
+                    }
+                }
+            }
+            // The closing ÷ starts an empty cluster.
+            clusters.pop();
+            cases.push((clusters.concat(), clusters));
+        }
+        cases
+    }
+
+    #[test]
+    fn grapheme_break_test() {
+        let cases = break_test_cases();
+        assert!(cases.len() > 600);
+        for (text, expected) in cases {
+            let clusters: Vec<&str> = graphemes(&text).collect();
+            assert_eq!(clusters, expected, "{:?}", text);
+            let starts: Vec<usize> = grapheme_indices(&text).map(|(start, _)| start).collect();
+            let expected_starts: Vec<usize> = expected
+                .iter()
+                .scan(0, |offset, cluster| {
+                    let start = *offset;
+                    *offset += cluster.len();
+                    Some(start)
+                })
+                .collect();
+            assert_eq!(starts, expected_starts, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn conjuncts() {
+        assert_eq!(indic_conjunct_break('क'), IndicConjunctBreak::Consonant);
+        assert_eq!(indic_conjunct_break('\u{94D}'), IndicConjunctBreak::Linker);
+        assert_eq!(indic_conjunct_break('\u{93C}'), IndicConjunctBreak::Extend);
+        assert_eq!(indic_conjunct_break('\u{200D}'), IndicConjunctBreak::Extend);
+        assert_eq!(indic_conjunct_break('\u{947}'), IndicConjunctBreak::None);
+        assert_eq!(indic_conjunct_break('a'), IndicConjunctBreak::None);
+
+        assert_eq!(graphemes("नमस्ते").collect::<Vec<_>>(), ["न", "म", "स्ते"]);
+        assert_eq!(graphemes("क्षत्रिय").collect::<Vec<_>>(), ["क्ष", "त्रि", "य"]);
+        // Only the six scripts with InCB consonants join; Tamil's virama doesn't.
+        assert_eq!(grapheme_count("க்ஷ"), 2);
+    }
+}
@@ -0,0 +1,230 @@
+// Hashers to plug into HashMap and HashSet in place of the default.
+//
+// HashMap::new() hashes with SipHash and a random key per map. That resists collision attacks
+// from untrusted input, but it is slow for short keys like words, and the random key means
+// iteration order changes from run to run. FxHasher is the multiply-and-rotate hash rustc uses
+// for its own tables; FnvHasher is FNV-1a, which is simpler and good for very short keys. Neither
+// is random, so the same inserts always give the same iteration order, and neither should be
+// used for keys an attacker chooses. SeededState is FxHasher with a chosen starting value, for
+// tests that want a fixed order that is still different from plain Fx. examples/bench_hashers.rs
+// compares them all on word counting.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasher, BuildHasherDefault, Hasher};
+
+pub type FxBuildHasher = BuildHasherDefault<FxHasher>;
+pub type FnvBuildHasher = BuildHasherDefault<FnvHasher>;
+
+pub type FxHashMap<K, V> = HashMap<K, V, FxBuildHasher>;
+pub type FxHashSet<T> = HashSet<T, FxBuildHasher>;
+pub type FnvHashMap<K, V> = HashMap<K, V, FnvBuildHasher>;
+pub type FnvHashSet<T> = HashSet<T, FnvBuildHasher>;
+pub type SeededHashMap<K, V> = HashMap<K, V, SeededState>;
+
+// From rustc's FxHasher: an odd constant with well-spread bits.
+const FX_MULTIPLIER: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+/// Eight bytes at a time: rotate, xor in the next word, multiply. A zero word adds nothing but a
+/// step, so keys that differ only in zero bytes can collide: "a" and "a\0\0\0" both hash as one
+/// word holding 0x61. That doesn't matter for text, but keys full of zeros should use another
+/// hasher.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FxHasher {
+    hash: u64,
+}
+
+impl FxHasher {
+    fn add(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(FX_MULTIPLIER);
+    }
+}
+
+impl Hasher for FxHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        let mut chunks = bytes.chunks_exact(8);
+        for chunk in &mut chunks {
+            self.add(u64::from_le_bytes(chunk.try_into().unwrap()));
+        }
+        let mut rest = chunks.remainder();
+        if rest.len() >= 4 {
+            self.add(u64::from(u32::from_le_bytes(rest[..4].try_into().unwrap())));
+            rest = &rest[4..];
+        }
+        for &byte in rest {
+            self.add(u64::from(byte));
+        }
+    }
+
+    fn write_u8(&mut self, n: u8) {
+        self.add(u64::from(n));
+    }
+
+    fn write_u32(&mut self, n: u32) {
+        self.add(u64::from(n));
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.add(n);
+    }
+
+    fn write_usize(&mut self, n: usize) {
+        self.add(n as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// 64-bit FNV-1a: for each byte, xor it in and multiply by the FNV prime.
+#[derive(Debug, Clone, Copy)]
+pub struct FnvHasher {
+    hash: u64,
+}
+
+impl Default for FnvHasher {
+    fn default() -> FnvHasher {
+        FnvHasher { hash: FNV_OFFSET_BASIS }
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.hash ^= u64::from(byte);
+            self.hash = self.hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+/// Builds FxHashers that start from `seed` instead of 0. Two maps with the same seed and the
+/// same inserts iterate in the same order, in every run and on every machine with the same
+/// pointer width.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SeededState {
+    seed: u64,
+}
+
+impl SeededState {
+    pub fn new(seed: u64) -> SeededState {
+        SeededState { seed }
+    }
+}
+
+impl BuildHasher for SeededState {
+    type Hasher = FxHasher;
+
+    fn build_hasher(&self) -> FxHasher {
+        FxHasher { hash: self.seed }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::hash::Hash;
+
+    fn fx(bytes: &[u8]) -> u64 {
+        let mut hasher = FxHasher::default();
+        hasher.write(bytes);
+        hasher.finish()
+    }
+
+    // FxHasher::write as the doc comment describes it, one step per word, written out by hand.
+    fn fx_by_hand(bytes: &[u8]) -> u64 {
+        let step = |hash: u64, word: u64| (hash.rotate_left(5) ^ word).wrapping_mul(FX_MULTIPLIER);
+        let mut hash = 0;
+        let mut i = 0;
+        while bytes.len() - i >= 8 {
+            let word = (0..8).fold(0, |word, k| word | u64::from(bytes[i + k]) << (8 * k));
+            hash = step(hash, word);
+            i += 8;
+        }
+        if bytes.len() - i >= 4 {
+            let word = (0..4).fold(0, |word, k| word | u64::from(bytes[i + k]) << (8 * k));
+            hash = step(hash, word);
+            i += 4;
+        }
+        for &byte in &bytes[i..] {
+            hash = step(hash, u64::from(byte));
+        }
+        hash
+    }
+
+    #[test]
+    fn fx_write_handles_every_tail_length() {
+        let bytes: Vec<u8> = (1..=16).map(|i| i * 15).collect();
+        for len in 0..16 {
+            let input = &bytes[..len];
+            assert_eq!(fx(input), fx_by_hand(input), "length {}", len);
+
+            // Every byte, including the ones in the 4-byte and 1-byte tails, changes the hash.
+            for position in 0..len {
+                let mut changed = input.to_vec();
+                changed[position] ^= 0x80;
+                assert_ne!(fx(&changed), fx(input), "length {}, byte {}", len, position);
+            }
+        }
+    }
+
+    #[test]
+    fn fx_distinguishes_short_strings() {
+        let hash = |state: &dyn Fn() -> FxHasher, text: &str| {
+            let mut hasher = state();
+            text.hash(&mut hasher);
+            hasher.finish()
+        };
+        let fresh = || FxHasher::default();
+        let seeded = || SeededState::new(1).build_hasher();
+
+        let letters = "abcdefghijklmno";
+        let hashes: HashSet<u64> = (0..16).map(|len| hash(&fresh, &letters[..len])).collect();
+        assert_eq!(hashes.len(), 16);
+
+        let hashes: HashSet<u64> = (0..16).map(|len| hash(&seeded, &letters[..len])).collect();
+        assert_eq!(hashes.len(), 16);
+
+        // The collision the doc comment warns about: zero bytes only add steps, and a 4-byte
+        // word and a single byte are one step each.
+        assert_eq!(hash(&fresh, "a"), hash(&fresh, "a\0\0\0"));
+        assert_eq!(hash(&seeded, "a"), hash(&seeded, "a\0\0\0"));
+        assert_ne!(hash(&fresh, "a"), hash(&fresh, "a\0"));
+    }
+
+    #[test]
+    fn seeded_maps_iterate_in_the_same_order() {
+        let build = |seed: u64| {
+            let mut map = SeededHashMap::with_hasher(SeededState::new(seed));
+            for i in 0..1000u32 {
+                map.insert(format!("key {}", i.wrapping_mul(2_654_435_761)), i);
+            }
+            map.remove("key 0");
+            map
+        };
+        let order = |map: &SeededHashMap<String, u32>| map.iter().map(|(k, v)| (k.clone(), *v)).collect::<Vec<_>>();
+
+        assert_eq!(order(&build(42)), order(&build(42)));
+        assert_eq!(order(&build(0)), order(&build(0)));
+        assert_ne!(order(&build(42)), order(&build(7)));
+    }
+
+    #[test]
+    fn fnv_matches_published_values() {
+        let fnv = |bytes: &[u8]| {
+            let mut hasher = FnvHasher::default();
+            hasher.write(bytes);
+            hasher.finish()
+        };
+        // Test vectors from the FNV reference implementation.
+        assert_eq!(fnv(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+}
@@ -0,0 +1,555 @@
+// A small JSON reader and writer, enough to save program state without pulling in a crate.
+//
+// Objects keep their keys in the order they were written, so a saved file reads in the same order
+// the program built it. Numbers are f64, as in JavaScript, which holds every integer up to 2^53
+// exactly.
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Parses one JSON value. Whitespace around it is fine; anything else after it is an error.
+    /// Arrays and objects may nest at most MAX_DEPTH levels deep.
+    pub fn parse(text: &str) -> Result<Json, JsonError> {
+        let mut parser = Parser {
+            text,
+            offset: 0,
+            depth: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.offset < text.len() {
+            return Err(parser.error("unexpected text after the value"));
+        }
+        Ok(value)
+    }
+
+    /// The value for `key` if this is an object containing it. With duplicate keys, the first wins.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Json::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Only for whole numbers that f64 holds exactly, so 1.5 and 1e20 are None.
+    pub fn as_i64(&self) -> Option<i64> {
+        const EXACT: f64 = 9_007_199_254_740_992.0; // 2^53
+        match self {
+            Json::Number(n) if n.fract() == 0.0 && n.abs() <= EXACT => Some(*n as i64),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Indented with two spaces, one array item or object field per line. `Display` gives the
+    /// compact form.
+    pub fn to_pretty_string(&self) -> String {
+        let mut out = String::new();
+        self.write(&mut out, Some(0));
+        out
+    }
+
+    // `indent` is None for the compact form, or the current depth.
+    fn write(&self, out: &mut String, indent: Option<usize>) {
+        match self {
+            Json::Null => out.push_str("null"),
+            Json::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+            Json::Number(n) => write_number(out, *n),
+            Json::String(s) => write_string(out, s),
+            Json::Array(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    separate(out, i, indent);
+                    item.write(out, indent.map(|depth| depth + 1));
+                }
+                close(out, items.is_empty(), indent);
+                out.push(']');
+            }
+            Json::Object(fields) => {
+                out.push('{');
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    separate(out, i, indent);
+                    write_string(out, key);
+                    out.push_str(if indent.is_some() { ": " } else { ":" });
+                    value.write(out, indent.map(|depth| depth + 1));
+                }
+                close(out, fields.is_empty(), indent);
+                out.push('}');
+            }
+        }
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut out = String::new();
+        self.write(&mut out, None);
+        f.write_str(&out)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(b: bool) -> Json {
+        Json::Bool(b)
+    }
+}
+
+impl From<i32> for Json {
+    fn from(n: i32) -> Json {
+        Json::Number(f64::from(n))
+    }
+}
+
+impl From<f64> for Json {
+    fn from(n: f64) -> Json {
+        Json::Number(n)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Json {
+        Json::String(String::from(s))
+    }
+}
+
+impl From<String> for Json {
+    fn from(s: String) -> Json {
+        Json::String(s)
+    }
+}
+
+fn separate(out: &mut String, i: usize, indent: Option<usize>) {
+    if i > 0 {
+        out.push(',');
+    }
+    if let Some(depth) = indent {
+        out.push('\n');
+        out.push_str(&"  ".repeat(depth + 1));
+    }
+}
+
+fn close(out: &mut String, empty: bool, indent: Option<usize>) {
+    if let (Some(depth), false) = (indent, empty) {
+        out.push('\n');
+        out.push_str(&"  ".repeat(depth));
+    }
+}
+
+// JSON has no NaN or infinity, so those are written as null.
+fn write_number(out: &mut String, n: f64) {
+    if !n.is_finite() {
+        out.push_str("null");
+    } else if n.fract() == 0.0 && n.abs() < 1e15 {
+        out.push_str(&(n as i64).to_string());
+    } else if n.abs() >= 1e15 || n.abs() < 1e-6 {
+        out.push_str(&format!("{:e}", n));
+    } else {
+        out.push_str(&n.to_string());
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c < ' ' => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Line and column are 1-based; the column counts chars.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+/// How deeply `Json::parse` lets arrays and objects nest. The parser recurses once per level, so
+/// without a limit a file of 200,000 `[`s would overflow the stack.
+pub const MAX_DEPTH: usize = 128;
+
+struct Parser<'a> {
+    text: &'a str,
+    offset: usize,
+    // Arrays and objects currently open.
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> JsonError {
+        let before = &self.text[..self.offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        JsonError {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            message: String::from(message),
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.offset).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.offset += 1;
+        }
+    }
+
+    fn expect(&mut self, literal: &str) -> Result<(), JsonError> {
+        if self.text[self.offset..].starts_with(literal) {
+            self.offset += literal.len();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", literal)))
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, JsonError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'n') => self.expect("null").map(|_| Json::Null),
+            Some(b't') => self.expect("true").map(|_| Json::Bool(true)),
+            Some(b'f') => self.expect("false").map(|_| Json::Bool(false)),
+            Some(b'"') => self.string().map(Json::String),
+            Some(open @ (b'[' | b'{')) => {
+                if self.depth == MAX_DEPTH {
+                    return Err(self.error(&format!("nested more than {} levels deep", MAX_DEPTH)));
+                }
+                self.depth += 1;
+                let value = if open == b'[' { self.array() } else { self.object() };
+                self.depth -= 1;
+                value
+            }
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("expected a value")),
+            None => Err(self.error("expected a value, found the end of the text")),
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, JsonError> {
+        self.offset += 1;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.offset += 1;
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.offset += 1,
+                Some(b']') => {
+                    self.offset += 1;
+                    return Ok(Json::Array(items));
+                }
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, JsonError> {
+        self.offset += 1;
+        let mut fields = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.offset += 1;
+            return Ok(Json::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(self.error("expected a quoted key"));
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(":")?;
+            fields.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.offset += 1,
+                Some(b'}') => {
+                    self.offset += 1;
+                    return Ok(Json::Object(fields));
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.offset += 1;
+        let mut s = String::new();
+        loop {
+            let c = match self.text[self.offset..].chars().next() {
+                Some(c) => c,
+                None => return Err(self.error("string is never closed")),
+            };
+            match c {
+                '"' => {
+                    self.offset += 1;
+                    return Ok(s);
+                }
+                '\\' => {
+                    self.offset += 1;
+                    s.push(self.escape()?);
+                }
+                c if c < ' ' => return Err(self.error("control characters in strings must be escaped")),
+                c => {
+                    self.offset += c.len_utf8();
+                    s.push(c);
+                }
+            }
+        }
+    }
+
+    // Just past a backslash.
+    fn escape(&mut self) -> Result<char, JsonError> {
+        let c = match self.peek() {
+            Some(b'"') => '"',
+            Some(b'\\') => '\\',
+            Some(b'/') => '/',
+            Some(b'b') => '\u{8}',
+            Some(b'f') => '\u{c}',
+            Some(b'n') => '\n',
+            Some(b'r') => '\r',
+            Some(b't') => '\t',
+            Some(b'u') => {
+                self.offset += 1;
+                return self.unicode_escape();
+            }
+            _ => return Err(self.error("unknown escape")),
+        };
+        self.offset += 1;
+        Ok(c)
+    }
+
+    // The XXXX of \uXXXX, and the second half of a surrogate pair if this is the first.
+    fn unicode_escape(&mut self) -> Result<char, JsonError> {
+        let high = self.hex4()?;
+        if !(0xd800..0xdc00).contains(&high) {
+            return char::from_u32(high).ok_or_else(|| self.error("lone low surrogate"));
+        }
+        if !self.text[self.offset..].starts_with("\\u") {
+            return Err(self.error("high surrogate without a low surrogate after it"));
+        }
+        self.offset += 2;
+        let low = self.hex4()?;
+        if !(0xdc00..0xe000).contains(&low) {
+            return Err(self.error("high surrogate without a low surrogate after it"));
+        }
+        let code = 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00);
+        char::from_u32(code).ok_or_else(|| self.error("bad surrogate pair"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, JsonError> {
+        let digits = self.text.get(self.offset..self.offset + 4).unwrap_or("");
+        if digits.len() != 4 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(self.error("expected four hex digits"));
+        }
+        self.offset += 4;
+        Ok(u32::from_str_radix(digits, 16).unwrap())
+    }
+
+    // -?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?
+    fn number(&mut self) -> Result<Json, JsonError> {
+        let start = self.offset;
+        let bytes = self.text.as_bytes();
+        let digits = |from: usize| bytes[from..].iter().take_while(|b| b.is_ascii_digit()).count();
+
+        let mut end = start;
+        if bytes[end] == b'-' {
+            end += 1;
+        }
+        let whole = digits(end);
+        if whole == 0 || (whole > 1 && bytes[end] == b'0') {
+            return Err(self.error("badly formed number"));
+        }
+        end += whole;
+        if bytes.get(end) == Some(&b'.') {
+            let fraction = digits(end + 1);
+            if fraction == 0 {
+                return Err(self.error("badly formed number"));
+            }
+            end += 1 + fraction;
+        }
+        if let Some(b'e' | b'E') = bytes.get(end) {
+            end += 1;
+            if let Some(b'+' | b'-') = bytes.get(end) {
+                end += 1;
+            }
+            let exponent = digits(end);
+            if exponent == 0 {
+                return Err(self.error("badly formed number"));
+            }
+            end += exponent;
+        }
+
+        self.offset = end;
+        // Anything matching the pattern above parses as an f64.
+        Ok(Json::Number(self.text[start..end].parse().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_at(text: &str) -> (usize, usize, String) {
+        let e = Json::parse(text).unwrap_err();
+        (e.line, e.column, e.message)
+    }
+
+    #[test]
+    fn deep_nesting_is_an_error_not_a_crash() {
+        let (line, column, message) = error_at(&"[".repeat(200_000));
+        assert_eq!((line, column), (1, MAX_DEPTH + 1));
+        assert_eq!(message, "nested more than 128 levels deep");
+        assert!(Json::parse(&"{\"a\":".repeat(MAX_DEPTH + 1)).is_err());
+
+        let deepest = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert!(Json::parse(&deepest).is_ok());
+        // Depth counts open containers, not containers seen, so siblings don't add up.
+        let wide = format!("[{}]", vec![deepest[1..deepest.len() - 1].to_string(); 3].join(","));
+        assert!(Json::parse(&wide).is_ok());
+    }
+
+    #[test]
+    fn strings_round_trip_with_escapes() {
+        let text = "quote \" backslash \\ slash / newline \n tab \t bell \u{7} nul \0 é 🦀";
+        let json = Json::from(text);
+        let written = json.to_string();
+        assert_eq!(
+            written,
+            "\"quote \\\" backslash \\\\ slash / newline \\n tab \\t bell \\u0007 nul \\u0000 é 🦀\""
+        );
+        assert_eq!(Json::parse(&written), Ok(json));
+        assert_eq!(Json::parse(r#""\/\b\f\r""#), Ok(Json::from("/\u{8}\u{c}\r")));
+    }
+
+    #[test]
+    fn surrogate_pairs() {
+        assert_eq!(Json::parse(r#""\ud83e\udd80""#), Ok(Json::from("🦀")));
+        assert_eq!(Json::parse(r#""\u00e9\u4e2D""#), Ok(Json::from("é中")));
+        assert_eq!(error_at(r#""\ud83e""#).2, "high surrogate without a low surrogate after it");
+        assert_eq!(error_at(r#""\ud83e\u0041""#).2, "high surrogate without a low surrogate after it");
+        assert_eq!(error_at(r#""\udd80""#).2, "lone low surrogate");
+        assert_eq!(error_at(r#""\u12g4""#).2, "expected four hex digits");
+    }
+
+    #[test]
+    fn number_formatting() {
+        let written = |n: f64| Json::Number(n).to_string();
+        assert_eq!(written(0.0), "0");
+        assert_eq!(written(-0.0), "0");
+        assert_eq!(written(42.0), "42");
+        assert_eq!(written(-1.5), "-1.5");
+        assert_eq!(written(0.1), "0.1");
+        assert_eq!(written(1e15), "1e15");
+        assert_eq!(written(123456789012345.0), "123456789012345");
+        assert_eq!(written(1e300), "1e300");
+        assert_eq!(written(1.5e-7), "1.5e-7");
+        assert_eq!(written(f64::NAN), "null");
+        assert_eq!(written(f64::INFINITY), "null");
+        for n in [0.1, -2.5e-9, 1e300, 123456789.125, f64::MAX, f64::MIN_POSITIVE] {
+            assert_eq!(Json::parse(&written(n)), Ok(Json::Number(n)));
+        }
+
+        assert_eq!(Json::parse("-0.5e+2"), Ok(Json::Number(-50.0)));
+        assert_eq!(Json::parse("9007199254740993").unwrap().as_i64(), Some(9007199254740992));
+        assert_eq!(Json::Number(1e16).as_i64(), None);
+        assert_eq!(Json::Number(1.5).as_i64(), None);
+        for bad in ["01", "-", "1.", ".5", "1e", "+1", "1.e5"] {
+            assert!(Json::parse(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn errors_have_line_and_column() {
+        assert_eq!(error_at(""), (1, 1, String::from("expected a value, found the end of the text")));
+        assert_eq!(error_at("[1,\n  2,\n  x]"), (3, 3, String::from("expected a value")));
+        assert_eq!(error_at("{\"é\": 1 \"b\": 2}"), (1, 9, String::from("expected ',' or '}'")));
+        assert_eq!(error_at("{1: 2}"), (1, 2, String::from("expected a quoted key")));
+        assert_eq!(error_at("[1 2]"), (1, 4, String::from("expected ',' or ']'")));
+        assert_eq!(error_at("\"abc"), (1, 5, String::from("string is never closed")));
+        assert_eq!(error_at("\"a\tb\""), (1, 3, String::from("control characters in strings must be escaped")));
+        assert_eq!(error_at("tru"), (1, 1, String::from("expected 'true'")));
+        assert_eq!(error_at("null x"), (1, 6, String::from("unexpected text after the value")));
+        assert_eq!(error_at("\"\\q\"").2, "unknown escape");
+        assert_eq!(
+            JsonError { line: 2, column: 7, message: String::from("oops") }.to_string(),
+            "line 2, column 7: oops"
+        );
+    }
+
+    #[test]
+    fn values_round_trip_compact_and_pretty() {
+        let text = r#"{"name": "Blue", "scores": [1, 2.5, -3], "nested": {"ok": true, "none": null}, "empty": [], "blank": {}}"#;
+        let json = Json::parse(text).unwrap();
+        assert_eq!(Json::parse(&json.to_string()), Ok(json.clone()));
+        assert_eq!(Json::parse(&json.to_pretty_string()), Ok(json.clone()));
+        assert_eq!(
+            json.to_string(),
+            r#"{"name":"Blue","scores":[1,2.5,-3],"nested":{"ok":true,"none":null},"empty":[],"blank":{}}"#
+        );
+        assert_eq!(json.get("name").and_then(Json::as_str), Some("Blue"));
+        assert_eq!(json.get("scores").and_then(Json::as_array).map(|a| a.len()), Some(3));
+        assert_eq!(json.get("nested").and_then(|n| n.get("ok")).and_then(Json::as_bool), Some(true));
+        assert_eq!(json.get("missing"), None);
+        assert_eq!(Json::parse(r#"{"a": 1, "a": 2}"#).unwrap().get("a"), Some(&Json::from(1)));
+    }
+}
@@ -0,0 +1,62 @@
+// Pieces of the chapter walkthroughs that are worth reusing. The chapter crates depend on this
+// one instead of keeping their own copies.
+
+pub mod access;
+pub mod array_vec;
+pub mod change;
+pub mod color;
+pub mod directory;
+pub mod encoding;
+pub mod files;
+pub mod geometry;
+pub mod grapheme;
+pub mod hashing;
+pub mod json;
+pub mod numeric;
+pub mod packing;
+pub mod point;
+pub mod quarters;
+pub mod ring_buffer;
+#[cfg(test)]
+mod rng;
+pub mod rope;
+pub mod scoreboard;
+pub mod search;
+pub mod sorted;
+pub mod stats;
+pub mod str_access;
+pub mod table;
+pub mod template;
+pub mod text;
+pub mod words;
+
+/// The generic `largest` from chapter 10. Returns `None` for an empty list instead of panicking
+/// on `&list[0]`.
+pub fn largest<T: PartialOrd>(list: &[T]) -> Option<&T> {
+    let mut items = list.iter();
+    let mut largest = items.next()?;
+
+    for item in items {
+        if item > largest {
+            largest = item;
+        }
+    }
+    Some(largest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn largest_of_a_list() {
+        assert_eq!(largest(&[34, 50, 25, 100, 65]), Some(&100));
+        assert_eq!(largest(&['y', 'm', 'a', 'q']), Some(&'y'));
+        assert_eq!(largest(&[-3]), Some(&-3));
+        assert_eq!(largest::<i32>(&[]), None);
+        // The first of equal items, and NaN never wins a > comparison.
+        let words = ["b", "a", "b"];
+        assert!(std::ptr::eq(largest(&words).unwrap(), &words[0]));
+        assert_eq!(largest(&[1.0, f64::NAN, 2.0]), Some(&2.0));
+    }
+}
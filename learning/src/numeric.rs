@@ -0,0 +1,419 @@
+// Overflow-aware arithmetic, grown from chapter 6's `plus_one`.
+//
+// `plus_one` does `i + 1`, which panics in debug builds on i32::MAX and wraps in release builds.
+// The Integer trait wraps the standard library's checked, saturating and wrapping methods so that
+// the same generic code works for every primitive integer, and the Result versions say what went
+// wrong through ArithmeticError.
+
+use std::fmt;
+use std::ops;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArithmeticError {
+    Overflow,
+    Underflow,
+    DivisionByZero,
+    /// A negative exponent or similar input the operation isn't defined for.
+    Undefined,
+}
+
+impl fmt::Display for ArithmeticError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArithmeticError::Overflow => write!(f, "result is too large for the type"),
+            ArithmeticError::Underflow => write!(f, "result is too small for the type"),
+            ArithmeticError::DivisionByZero => write!(f, "division by zero"),
+            ArithmeticError::Undefined => write!(f, "result is undefined"),
+        }
+    }
+}
+
+impl std::error::Error for ArithmeticError {}
+
+pub trait Integer: Copy + PartialOrd + fmt::Debug + fmt::Display {
+    const ZERO: Self;
+    const ONE: Self;
+    const MIN: Self;
+    const MAX: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn checked_div(self, rhs: Self) -> Option<Self>;
+    fn checked_pow(self, exp: u32) -> Option<Self>;
+
+    fn saturating_add(self, rhs: Self) -> Self;
+    fn saturating_sub(self, rhs: Self) -> Self;
+    fn saturating_mul(self, rhs: Self) -> Self;
+    fn saturating_pow(self, exp: u32) -> Self;
+
+    fn wrapping_add(self, rhs: Self) -> Self;
+    fn wrapping_sub(self, rhs: Self) -> Self;
+    fn wrapping_mul(self, rhs: Self) -> Self;
+    fn wrapping_div(self, rhs: Self) -> Self;
+    fn wrapping_pow(self, exp: u32) -> Self;
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                const ZERO: $t = 0;
+                const ONE: $t = 1;
+                const MIN: $t = <$t>::MIN;
+                const MAX: $t = <$t>::MAX;
+
+                fn checked_add(self, rhs: $t) -> Option<$t> { <$t>::checked_add(self, rhs) }
+                fn checked_sub(self, rhs: $t) -> Option<$t> { <$t>::checked_sub(self, rhs) }
+                fn checked_mul(self, rhs: $t) -> Option<$t> { <$t>::checked_mul(self, rhs) }
+                fn checked_div(self, rhs: $t) -> Option<$t> { <$t>::checked_div(self, rhs) }
+                fn checked_pow(self, exp: u32) -> Option<$t> { <$t>::checked_pow(self, exp) }
+
+                fn saturating_add(self, rhs: $t) -> $t { <$t>::saturating_add(self, rhs) }
+                fn saturating_sub(self, rhs: $t) -> $t { <$t>::saturating_sub(self, rhs) }
+                fn saturating_mul(self, rhs: $t) -> $t { <$t>::saturating_mul(self, rhs) }
+                fn saturating_pow(self, exp: u32) -> $t { <$t>::saturating_pow(self, exp) }
+
+                fn wrapping_add(self, rhs: $t) -> $t { <$t>::wrapping_add(self, rhs) }
+                fn wrapping_sub(self, rhs: $t) -> $t { <$t>::wrapping_sub(self, rhs) }
+                fn wrapping_mul(self, rhs: $t) -> $t { <$t>::wrapping_mul(self, rhs) }
+                fn wrapping_div(self, rhs: $t) -> $t { <$t>::wrapping_div(self, rhs) }
+                fn wrapping_pow(self, exp: u32) -> $t { <$t>::wrapping_pow(self, exp) }
+            }
+        )*
+    };
+}
+
+impl_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+// The standard library only says "it didn't fit". Working out which way it missed is cheap
+// because we know the signs of the inputs.
+
+pub fn add<T: Integer>(a: T, b: T) -> Result<T, ArithmeticError> {
+    a.checked_add(b).ok_or(if b < T::ZERO {
+        ArithmeticError::Underflow
+    } else {
+        ArithmeticError::Overflow
+    })
+}
+
+pub fn sub<T: Integer>(a: T, b: T) -> Result<T, ArithmeticError> {
+    a.checked_sub(b).ok_or(if b > T::ZERO {
+        ArithmeticError::Underflow
+    } else {
+        ArithmeticError::Overflow
+    })
+}
+
+pub fn mul<T: Integer>(a: T, b: T) -> Result<T, ArithmeticError> {
+    a.checked_mul(b).ok_or(if (a < T::ZERO) != (b < T::ZERO) {
+        ArithmeticError::Underflow
+    } else {
+        ArithmeticError::Overflow
+    })
+}
+
+/// Fails on division by zero and on `MIN / -1` for signed types.
+pub fn div<T: Integer>(a: T, b: T) -> Result<T, ArithmeticError> {
+    if b == T::ZERO {
+        return Err(ArithmeticError::DivisionByZero);
+    }
+    a.checked_div(b).ok_or(ArithmeticError::Overflow)
+}
+
+pub fn pow<T: Integer>(base: T, exp: u32) -> Result<T, ArithmeticError> {
+    base.checked_pow(exp).ok_or(if base < T::ZERO && exp % 2 == 1 {
+        ArithmeticError::Underflow
+    } else {
+        ArithmeticError::Overflow
+    })
+}
+
+/// Power with a signed exponent. Negative exponents only have integer results for 1 and -1.
+pub fn pow_signed<T: Integer>(base: T, exp: i64) -> Result<T, ArithmeticError> {
+    // 0, 1 and -1 never overflow, so they have answers even for exponents past u32::MAX.
+    if base == T::ZERO {
+        return match exp {
+            0 => Ok(T::ONE),
+            _ if exp > 0 => Ok(T::ZERO),
+            _ => Err(ArithmeticError::DivisionByZero),
+        };
+    }
+    if base == T::ONE {
+        return Ok(T::ONE);
+    }
+    if T::MIN < T::ZERO && base == T::ZERO.wrapping_sub(T::ONE) {
+        return Ok(if exp % 2 == 0 { T::ONE } else { base });
+    }
+    if exp < 0 {
+        return Err(ArithmeticError::Undefined);
+    }
+    match u32::try_from(exp) {
+        Ok(exp) => pow(base, exp),
+        Err(_) if base < T::ZERO && exp % 2 == 1 => Err(ArithmeticError::Underflow),
+        Err(_) => Err(ArithmeticError::Overflow),
+    }
+}
+
+/// `plus_one` that can't panic.
+pub fn plus_one(x: Option<i32>) -> Option<i32> {
+    x?.checked_add(1)
+}
+
+/// `plus_one` that says why it failed.
+pub fn try_plus_one(x: Option<i32>) -> Option<Result<i32, ArithmeticError>> {
+    x.map(|i| add(i, 1))
+}
+
+/// Arithmetic over an `Option`, so `(Checked::new(x) + 1) * 3` is `None` as soon as any step
+/// overflows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checked<T>(pub Option<T>);
+
+impl<T: Integer> Checked<T> {
+    pub fn new(value: T) -> Checked<T> {
+        Checked(Some(value))
+    }
+
+    pub fn pow(self, exp: u32) -> Checked<T> {
+        Checked(self.0.and_then(|v| v.checked_pow(exp)))
+    }
+
+    pub fn get(self) -> Option<T> {
+        self.0
+    }
+}
+
+/// Like `Checked`, but keeps the first error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tracked<T>(pub Result<T, ArithmeticError>);
+
+impl<T: Integer> Tracked<T> {
+    pub fn new(value: T) -> Tracked<T> {
+        Tracked(Ok(value))
+    }
+
+    pub fn pow(self, exp: u32) -> Tracked<T> {
+        Tracked(self.0.and_then(|v| pow(v, exp)))
+    }
+
+    pub fn get(self) -> Result<T, ArithmeticError> {
+        self.0
+    }
+}
+
+/// Clamps to the type's range instead of failing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Saturating<T>(pub T);
+
+impl<T: Integer> Saturating<T> {
+    pub fn pow(self, exp: u32) -> Saturating<T> {
+        Saturating(self.0.saturating_pow(exp))
+    }
+}
+
+/// Two's complement wrap-around, what release builds do for `i + 1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Wrapping<T>(pub T);
+
+impl<T: Integer> Wrapping<T> {
+    pub fn pow(self, exp: u32) -> Wrapping<T> {
+        Wrapping(self.0.wrapping_pow(exp))
+    }
+
+    /// Division by zero has no wrapped value, so it is an error rather than an operator.
+    pub fn try_div(self, rhs: T) -> Result<Wrapping<T>, ArithmeticError> {
+        if rhs == T::ZERO {
+            return Err(ArithmeticError::DivisionByZero);
+        }
+        Ok(Wrapping(self.0.wrapping_div(rhs)))
+    }
+}
+
+macro_rules! impl_op {
+    ($wrapper:ident, $trait:ident, $method:ident, |$value:ident, $rhs:ident| $body:expr) => {
+        impl<T: Integer> ops::$trait<T> for $wrapper<T> {
+            type Output = $wrapper<T>;
+
+            fn $method(self, $rhs: T) -> $wrapper<T> {
+                let $value = self.0;
+                $wrapper($body)
+            }
+        }
+    };
+}
+
+impl_op!(Checked, Add, add, |v, rhs| v.and_then(|v| v.checked_add(rhs)));
+impl_op!(Checked, Sub, sub, |v, rhs| v.and_then(|v| v.checked_sub(rhs)));
+impl_op!(Checked, Mul, mul, |v, rhs| v.and_then(|v| v.checked_mul(rhs)));
+impl_op!(Checked, Div, div, |v, rhs| v.and_then(|v| v.checked_div(rhs)));
+
+impl_op!(Tracked, Add, add, |v, rhs| v.and_then(|v| add(v, rhs)));
+impl_op!(Tracked, Sub, sub, |v, rhs| v.and_then(|v| sub(v, rhs)));
+impl_op!(Tracked, Mul, mul, |v, rhs| v.and_then(|v| mul(v, rhs)));
+impl_op!(Tracked, Div, div, |v, rhs| v.and_then(|v| div(v, rhs)));
+
+impl_op!(Saturating, Add, add, |v, rhs| v.saturating_add(rhs));
+impl_op!(Saturating, Sub, sub, |v, rhs| v.saturating_sub(rhs));
+impl_op!(Saturating, Mul, mul, |v, rhs| v.saturating_mul(rhs));
+// `MIN / -1` saturates to MAX. Division by zero has nothing sensible to saturate to, so it panics
+// like std::num::Saturating does.
+impl_op!(Saturating, Div, div, |v, rhs| match div(v, rhs) {
+    Ok(q) => q,
+    Err(ArithmeticError::DivisionByZero) => panic!("attempt to divide by zero"),
+    Err(_) => T::MAX,
+});
+
+impl_op!(Wrapping, Add, add, |v, rhs| v.wrapping_add(rhs));
+impl_op!(Wrapping, Sub, sub, |v, rhs| v.wrapping_sub(rhs));
+impl_op!(Wrapping, Mul, mul, |v, rhs| v.wrapping_mul(rhs));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ArithmeticError::*;
+
+    // Every operation at MIN and MAX, for one type. Signed types also get the cases that need a
+    // negative operand.
+    fn boundaries<T: Integer>(bits: u32) {
+        let zero = T::ZERO;
+        let one = T::ONE;
+        let two = one.wrapping_add(one);
+
+        assert_eq!(add(T::MAX, one), Err(Overflow));
+        assert_eq!(add(T::MAX, zero), Ok(T::MAX));
+        assert_eq!(add(T::MIN, zero), Ok(T::MIN));
+        assert_eq!(sub(T::MIN, one), Err(Underflow));
+        assert_eq!(sub(T::MAX, T::MAX), Ok(zero));
+        assert_eq!(sub(T::MIN, T::MIN), Ok(zero));
+        assert_eq!(mul(T::MAX, two), Err(Overflow));
+        assert_eq!(mul(T::MAX, one), Ok(T::MAX));
+        assert_eq!(mul(T::MIN, one), Ok(T::MIN));
+        assert_eq!(mul(T::MAX, zero), Ok(zero));
+        assert_eq!(div(T::MAX, zero), Err(DivisionByZero));
+        assert_eq!(div(T::MIN, zero), Err(DivisionByZero));
+        assert_eq!(div(zero, zero), Err(DivisionByZero));
+        assert_eq!(div(T::MAX, one), Ok(T::MAX));
+        assert_eq!(div(T::MIN, one), Ok(T::MIN));
+        assert_eq!(div(T::MAX, T::MAX), Ok(one));
+        assert_eq!(pow(T::MAX, 0), Ok(one));
+        assert_eq!(pow(T::MAX, 1), Ok(T::MAX));
+        assert_eq!(pow(T::MAX, 2), Err(Overflow));
+        assert_eq!(pow(T::MIN, 1), Ok(T::MIN));
+        assert_eq!(pow(zero, u32::MAX), Ok(zero));
+        assert_eq!(pow(one, u32::MAX), Ok(one));
+
+        assert_eq!((Checked::new(T::MAX) + one).get(), None);
+        assert_eq!((Checked::new(T::MIN) - one).get(), None);
+        assert_eq!((Checked::new(T::MAX) / zero).get(), None);
+        assert_eq!((Tracked::new(T::MAX) * two + one).get(), Err(Overflow));
+        assert_eq!((Tracked::new(T::MIN) - one).get(), Err(Underflow));
+        assert_eq!(Saturating(T::MAX) + one, Saturating(T::MAX));
+        assert_eq!(Saturating(T::MIN) - one, Saturating(T::MIN));
+        assert_eq!(Saturating(T::MAX) * two, Saturating(T::MAX));
+        assert_eq!(Saturating(T::MAX).pow(2), Saturating(T::MAX));
+        assert_eq!(Wrapping(T::MAX) + one, Wrapping(T::MIN));
+        assert_eq!(Wrapping(T::MIN) - one, Wrapping(T::MAX));
+        assert_eq!(Wrapping(T::MAX).try_div(zero), Err(DivisionByZero));
+
+        if T::MIN < zero {
+            let minus_one = zero.wrapping_sub(one);
+            let minus_two = minus_one.wrapping_sub(one);
+
+            // 2^(bits-1) is one past MAX, but -2^(bits-1) is exactly MIN.
+            assert_eq!(pow(two, bits - 1), Err(Overflow));
+            assert_eq!(pow(minus_two, bits - 1), Ok(T::MIN));
+            assert_eq!(pow(minus_two, bits), Err(Overflow));
+            assert_eq!(pow(minus_two, bits + 1), Err(Underflow));
+            assert_eq!(pow(T::MIN, 2), Err(Overflow));
+            assert_eq!(pow(T::MIN, 3), Err(Underflow));
+
+            assert_eq!(add(T::MIN, minus_one), Err(Underflow));
+            assert_eq!(add(T::MAX, T::MIN), Ok(minus_one));
+            assert_eq!(sub(T::MAX, minus_one), Err(Overflow));
+            assert_eq!(sub(zero, T::MIN), Err(Overflow));
+            assert_eq!(sub(minus_one, T::MAX), Ok(T::MIN));
+            assert_eq!(mul(T::MIN, two), Err(Underflow));
+            assert_eq!(mul(T::MAX, minus_two), Err(Underflow));
+            assert_eq!(mul(T::MIN, minus_one), Err(Overflow));
+            assert_eq!(mul(T::MAX, minus_one), Ok(T::MIN.wrapping_add(one)));
+            assert_eq!(div(T::MIN, minus_one), Err(Overflow));
+            assert_eq!(div(T::MAX, minus_one), Ok(T::MIN.wrapping_add(one)));
+            assert_eq!(div(T::MIN, T::MIN), Ok(one));
+
+            assert_eq!((Checked::new(T::MIN) / minus_one).get(), None);
+            assert_eq!((Tracked::new(T::MIN) / minus_one).get(), Err(Overflow));
+            assert_eq!(Saturating(T::MIN) / minus_one, Saturating(T::MAX));
+            assert_eq!(Saturating(T::MIN) * two, Saturating(T::MIN));
+            assert_eq!(Saturating(T::MIN) + minus_one, Saturating(T::MIN));
+            assert_eq!(Wrapping(T::MIN).try_div(minus_one), Ok(Wrapping(T::MIN)));
+            assert_eq!(Wrapping(T::MIN) * minus_one, Wrapping(T::MIN));
+
+            assert_eq!(pow_signed(minus_one, -3), Ok(minus_one));
+            assert_eq!(pow_signed(minus_one, -2), Ok(one));
+            // Exponents too big for u32 still have answers when |base| <= 1.
+            assert_eq!(pow_signed(minus_one, i64::MAX), Ok(minus_one));
+            assert_eq!(pow_signed(minus_one, i64::MAX - 1), Ok(one));
+            assert_eq!(pow_signed(minus_one, i64::MIN), Ok(one));
+            assert_eq!(pow_signed(zero.wrapping_sub(two), i64::MAX), Err(Underflow));
+            assert_eq!(pow_signed(zero.wrapping_sub(two), i64::MAX - 1), Err(Overflow));
+        } else {
+            assert_eq!(T::MIN, zero);
+            // 2^(bits-1) is the top bit on its own; one more doubling runs off the end.
+            let top = pow(two, bits - 1).unwrap();
+            assert_eq!(top.wrapping_mul(two), zero);
+            assert_eq!(top.wrapping_sub(one).wrapping_add(top), T::MAX);
+            assert_eq!(pow(two, bits), Err(Overflow));
+            assert_eq!(sub(zero, T::MAX), Err(Underflow));
+            assert_eq!(Wrapping(zero) - one, Wrapping(T::MAX));
+        }
+
+        assert_eq!(pow_signed(two, -1), Err(Undefined));
+        assert_eq!(pow_signed(zero, -1), Err(DivisionByZero));
+        assert_eq!(pow_signed(one, i64::MIN), Ok(one));
+        assert_eq!(pow_signed(one, i64::MAX), Ok(one));
+        assert_eq!(pow_signed(zero, i64::MAX), Ok(zero));
+        assert_eq!(pow_signed(zero, 0), Ok(one));
+        assert_eq!(pow_signed(zero, i64::MIN), Err(DivisionByZero));
+        assert_eq!(pow_signed(two, i64::MAX), Err(Overflow));
+    }
+
+    macro_rules! boundary_tests {
+        ($($name:ident: $t:ty),*) => {
+            $(
+                #[test]
+                fn $name() {
+                    boundaries::<$t>(<$t>::BITS);
+                }
+            )*
+        };
+    }
+
+    boundary_tests!(
+        i8_boundaries: i8,
+        i16_boundaries: i16,
+        i32_boundaries: i32,
+        i64_boundaries: i64,
+        i128_boundaries: i128,
+        isize_boundaries: isize,
+        u8_boundaries: u8,
+        u16_boundaries: u16,
+        u32_boundaries: u32,
+        u64_boundaries: u64,
+        u128_boundaries: u128,
+        usize_boundaries: usize
+    );
+
+    #[test]
+    #[should_panic(expected = "divide by zero")]
+    fn saturating_division_by_zero_panics() {
+        let _ = Saturating(1) / 0;
+    }
+
+    #[test]
+    fn plus_one_at_max() {
+        assert_eq!(plus_one(Some(i32::MAX)), None);
+        assert_eq!(try_plus_one(Some(i32::MAX)), Some(Err(Overflow)));
+        assert_eq!(try_plus_one(None), None);
+    }
+}
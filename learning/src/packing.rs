@@ -0,0 +1,506 @@
+// Rectangle packing for sprite sheets and cutting lists.
+//
+// Items are plain sizes (the chapter 5 `Rectangle { width, height }`), and each placement is a
+// positioned Rectangle inside a container whose top-left corner is the origin.
+
+use std::fmt;
+
+use crate::geometry::Rectangle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Rows of items, tallest first. Fast, but wastes space above shorter items.
+    Shelf,
+    /// Splits the free area into two smaller free rectangles after every placement.
+    Guillotine,
+    /// Keeps every maximal free rectangle. Slowest and usually the tightest.
+    MaxRects,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    /// Position of the item in the input slice.
+    pub index: usize,
+    /// Where the item ended up. Width and height are swapped when `rotated` is true.
+    pub rect: Rectangle,
+    pub rotated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Packing {
+    pub container: Rectangle,
+    pub placements: Vec<Placement>,
+    /// Indexes of the items that didn't fit.
+    pub unplaced: Vec<usize>,
+}
+
+impl Packing {
+    pub fn is_complete(&self) -> bool {
+        self.unplaced.is_empty()
+    }
+
+    pub fn used_area(&self) -> u64 {
+        self.placements.iter().map(|p| p.rect.area_u64()).sum()
+    }
+
+    pub fn wasted_area(&self) -> u64 {
+        self.container.area_u64() - self.used_area()
+    }
+
+    /// Share of the container covered by items, from 0.0 to 1.0.
+    pub fn occupancy(&self) -> f64 {
+        if self.container.is_empty() {
+            return 0.0;
+        }
+        self.used_area() as f64 / self.container.area_u64() as f64
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackError {
+    /// Placements use i32 coordinates, so the container can't be wider or taller than i32::MAX.
+    ContainerTooLarge,
+    /// An item has zero width or height.
+    EmptyItem(usize),
+}
+
+impl fmt::Display for PackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PackError::ContainerTooLarge => write!(f, "container is larger than i32::MAX"),
+            PackError::EmptyItem(index) => write!(f, "item {} has no area", index),
+        }
+    }
+}
+
+impl std::error::Error for PackError {}
+
+/// Packs `items` into a container of the given size. Items that don't fit are listed in
+/// `Packing::unplaced` rather than failing the whole packing.
+pub fn pack(
+    container: Rectangle,
+    items: &[Rectangle],
+    strategy: Strategy,
+    allow_rotation: bool,
+) -> Result<Packing, PackError> {
+    if container.width > i32::MAX as u32 || container.height > i32::MAX as u32 {
+        return Err(PackError::ContainerTooLarge);
+    }
+    if let Some(index) = items.iter().position(|item| item.is_empty()) {
+        return Err(PackError::EmptyItem(index));
+    }
+
+    let container = Rectangle::new(container.width, container.height);
+    let order = packing_order(items, strategy);
+
+    let mut packing = Packing {
+        container,
+        placements: Vec::new(),
+        unplaced: Vec::new(),
+    };
+
+    match strategy {
+        Strategy::Shelf => {
+            let mut packer = ShelfPacker::new(container);
+            place_all(&mut packing, items, &order, |w, h| packer.insert(w, h, allow_rotation));
+        }
+        Strategy::Guillotine => {
+            let mut packer = FreeListPacker::new(container, Split::Guillotine);
+            place_all(&mut packing, items, &order, |w, h| packer.insert(w, h, allow_rotation));
+        }
+        Strategy::MaxRects => {
+            let mut packer = FreeListPacker::new(container, Split::MaxRects);
+            place_all(&mut packing, items, &order, |w, h| packer.insert(w, h, allow_rotation));
+        }
+    }
+
+    packing.placements.sort_by_key(|p| p.index);
+    packing.unplaced.sort();
+    Ok(packing)
+}
+
+/// Finds a small container that holds every item, trying a range of widths and keeping the
+/// packing with the least total area.
+pub fn pack_min_area(
+    items: &[Rectangle],
+    strategy: Strategy,
+    allow_rotation: bool,
+) -> Result<Packing, PackError> {
+    if let Some(index) = items.iter().position(|item| item.is_empty()) {
+        return Err(PackError::EmptyItem(index));
+    }
+
+    let limit = i32::MAX as u64;
+    let min_side = |r: &Rectangle| if allow_rotation { r.width.min(r.height) } else { r.width };
+    let narrowest = items.iter().map(min_side).max().unwrap_or(0) as u64;
+    let total_width: u64 = items.iter().map(|r| min_side(r) as u64).sum();
+    let total_height: u64 = items.iter().map(|r| r.width.max(r.height) as u64).sum();
+    let total_area: u64 = items.iter().map(|r| r.area_u64()).sum();
+
+    if total_width > limit && narrowest > limit || total_height > limit {
+        return Err(PackError::ContainerTooLarge);
+    }
+
+    let mut widths = vec![narrowest, total_width.min(limit), (total_area as f64).sqrt().ceil() as u64];
+    const STEPS: u64 = 32;
+    let span = total_width.min(limit).saturating_sub(narrowest);
+    for step in 1..STEPS {
+        widths.push(narrowest + span * step / STEPS);
+    }
+    widths.retain(|&w| w >= narrowest && w <= limit);
+    widths.sort();
+    widths.dedup();
+
+    let mut best: Option<Packing> = None;
+    for width in widths {
+        let tall = Rectangle::new(width as u32, total_height as u32);
+        let mut packing = pack(tall, items, strategy, allow_rotation)?;
+        if !packing.is_complete() {
+            continue;
+        }
+
+        // Shrink the container to what is actually used.
+        let used_width = packing.placements.iter().map(|p| p.rect.right()).max().unwrap_or(0);
+        let used_height = packing.placements.iter().map(|p| p.rect.bottom()).max().unwrap_or(0);
+        packing.container = Rectangle::new(used_width as u32, used_height as u32);
+
+        let better = match &best {
+            Some(current) => packing.container.area_u64() < current.container.area_u64(),
+            None => true,
+        };
+        if better {
+            best = Some(packing);
+        }
+    }
+
+    // None of the widths tried held everything.
+    Ok(best.unwrap_or(Packing {
+        container: Rectangle::new(0, 0),
+        placements: Vec::new(),
+        unplaced: (0..items.len()).collect(),
+    }))
+}
+
+// Big items first packs much tighter for every strategy.
+fn packing_order(items: &[Rectangle], strategy: Strategy) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    match strategy {
+        Strategy::Shelf => order.sort_by(|&a, &b| {
+            let (a, b) = (&items[a], &items[b]);
+            b.height.max(b.width).cmp(&a.height.max(a.width))
+        }),
+        Strategy::Guillotine | Strategy::MaxRects => {
+            order.sort_by(|&a, &b| items[b].area_u64().cmp(&items[a].area_u64()))
+        }
+    }
+    order
+}
+
+fn place_all<F>(packing: &mut Packing, items: &[Rectangle], order: &[usize], mut insert: F)
+where
+    F: FnMut(u32, u32) -> Option<(Rectangle, bool)>,
+{
+    for &index in order {
+        let item = &items[index];
+        match insert(item.width, item.height) {
+            Some((rect, rotated)) => packing.placements.push(Placement {
+                index,
+                rect,
+                rotated,
+            }),
+            None => packing.unplaced.push(index),
+        }
+    }
+}
+
+struct Shelf {
+    y: u32,
+    height: u32,
+    used_width: u32,
+}
+
+struct ShelfPacker {
+    container: Rectangle,
+    shelves: Vec<Shelf>,
+}
+
+impl ShelfPacker {
+    fn new(container: Rectangle) -> ShelfPacker {
+        ShelfPacker {
+            container,
+            shelves: Vec::new(),
+        }
+    }
+
+    fn insert(&mut self, width: u32, height: u32, allow_rotation: bool) -> Option<(Rectangle, bool)> {
+        // Lying items down keeps shelves short, but an item too long to lie down may still fit
+        // standing up.
+        if allow_rotation && height > width {
+            if let Some(rect) = self.insert_as(height, width) {
+                return Some((rect, true));
+            }
+        }
+        self.insert_as(width, height).map(|rect| (rect, false))
+    }
+
+    fn insert_as(&mut self, width: u32, height: u32) -> Option<Rectangle> {
+        for shelf in &mut self.shelves {
+            if height <= shelf.height && width <= self.container.width - shelf.used_width {
+                let rect = Rectangle::at(shelf.used_width as i32, shelf.y as i32, width, height);
+                shelf.used_width += width;
+                return Some(rect);
+            }
+        }
+
+        let next_y = self.shelves.last().map(|s| s.y + s.height).unwrap_or(0);
+        if width > self.container.width || height > self.container.height - next_y {
+            return None;
+        }
+
+        self.shelves.push(Shelf {
+            y: next_y,
+            height,
+            used_width: width,
+        });
+        Some(Rectangle::at(0, next_y as i32, width, height))
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Split {
+    Guillotine,
+    MaxRects,
+}
+
+struct FreeListPacker {
+    split: Split,
+    free: Vec<Rectangle>,
+}
+
+impl FreeListPacker {
+    fn new(container: Rectangle, split: Split) -> FreeListPacker {
+        let free = if container.is_empty() { Vec::new() } else { vec![container] };
+        FreeListPacker { split, free }
+    }
+
+    fn insert(&mut self, width: u32, height: u32, allow_rotation: bool) -> Option<(Rectangle, bool)> {
+        let mut best: Option<(u64, u64, usize, bool)> = None;
+
+        for (i, free) in self.free.iter().enumerate() {
+            let mut orientations = vec![(width, height, false)];
+            if allow_rotation && width != height {
+                orientations.push((height, width, true));
+            }
+
+            for (w, h, rotated) in orientations {
+                if !free.can_fit(&Rectangle::new(w, h)) {
+                    continue;
+                }
+                let leftover_w = (free.width - w) as u64;
+                let leftover_h = (free.height - h) as u64;
+                // Guillotine scores by best area fit, MaxRects by best short side fit.
+                let score = match self.split {
+                    Split::Guillotine => (free.area_u64() - w as u64 * h as u64, leftover_w.min(leftover_h)),
+                    Split::MaxRects => (leftover_w.min(leftover_h), leftover_w.max(leftover_h)),
+                };
+                if best.is_none_or(|(a, b, _, _)| score < (a, b)) {
+                    best = Some((score.0, score.1, i, rotated));
+                }
+            }
+        }
+
+        let (_, _, i, rotated) = best?;
+        let free = self.free[i];
+        let (w, h) = if rotated { (height, width) } else { (width, height) };
+        let placed = Rectangle::at(free.x, free.y, w, h);
+
+        match self.split {
+            Split::Guillotine => self.split_guillotine(i, placed),
+            Split::MaxRects => self.split_maxrects(placed),
+        }
+
+        Some((placed, rotated))
+    }
+
+    // Cut along the shorter leftover axis so the bigger leftover stays in one piece.
+    fn split_guillotine(&mut self, i: usize, placed: Rectangle) {
+        let free = self.free.swap_remove(i);
+        let leftover_w = free.width - placed.width;
+        let leftover_h = free.height - placed.height;
+
+        let (right, below) = if leftover_w < leftover_h {
+            (
+                Rectangle::at(placed.x + placed.width as i32, free.y, leftover_w, placed.height),
+                Rectangle::at(free.x, placed.y + placed.height as i32, free.width, leftover_h),
+            )
+        } else {
+            (
+                Rectangle::at(placed.x + placed.width as i32, free.y, leftover_w, free.height),
+                Rectangle::at(free.x, placed.y + placed.height as i32, placed.width, leftover_h),
+            )
+        };
+
+        for rect in [right, below] {
+            if !rect.is_empty() {
+                self.free.push(rect);
+            }
+        }
+    }
+
+    fn split_maxrects(&mut self, placed: Rectangle) {
+        let mut next = Vec::with_capacity(self.free.len() + 4);
+
+        for free in self.free.drain(..) {
+            if free.intersection(&placed).is_none() {
+                next.push(free);
+                continue;
+            }
+
+            // Up to four maximal pieces of `free` that lie outside `placed`.
+            if placed.left() > free.left() {
+                next.push(Rectangle::at(free.x, free.y, (placed.left() - free.left()) as u32, free.height));
+            }
+            if placed.right() < free.right() {
+                next.push(Rectangle::at(
+                    placed.right() as i32,
+                    free.y,
+                    (free.right() - placed.right()) as u32,
+                    free.height,
+                ));
+            }
+            if placed.top() > free.top() {
+                next.push(Rectangle::at(free.x, free.y, free.width, (placed.top() - free.top()) as u32));
+            }
+            if placed.bottom() < free.bottom() {
+                next.push(Rectangle::at(
+                    free.x,
+                    placed.bottom() as i32,
+                    free.width,
+                    (free.bottom() - placed.bottom()) as u32,
+                ));
+            }
+        }
+
+        // Drop free rectangles that sit entirely inside another one.
+        let mut i = 0;
+        while i < next.len() {
+            let contained = next
+                .iter()
+                .enumerate()
+                .any(|(j, other)| j != i && other.contains_rect(&next[i]) && (other != &next[i] || j < i));
+            if contained {
+                next.swap_remove(i);
+            } else {
+                i += 1;
+            }
+        }
+
+        self.free = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    const STRATEGIES: [Strategy; 3] = [Strategy::Shelf, Strategy::Guillotine, Strategy::MaxRects];
+
+    fn random_items(rng: &mut Rng, count: usize, max_side: usize) -> Vec<Rectangle> {
+        (0..count)
+            .map(|_| Rectangle::new(1 + rng.below(max_side) as u32, 1 + rng.below(max_side) as u32))
+            .collect()
+    }
+
+    // Every item is placed once or reported once, at its own size, inside the container and
+    // clear of every other item.
+    fn check(packing: &Packing, items: &[Rectangle], allow_rotation: bool) {
+        let mut seen = vec![0; items.len()];
+        for placement in &packing.placements {
+            seen[placement.index] += 1;
+            let item = items[placement.index];
+            let size = (placement.rect.width, placement.rect.height);
+            if placement.rotated {
+                assert!(allow_rotation);
+                assert_eq!(size, (item.height, item.width));
+            } else {
+                assert_eq!(size, (item.width, item.height));
+            }
+            assert!(packing.container.contains_rect(&placement.rect), "{:?} outside {:?}", placement, packing.container);
+        }
+        for &index in &packing.unplaced {
+            seen[index] += 1;
+        }
+        assert!(seen.iter().all(|&count| count == 1), "{:?}", seen);
+
+        for (i, a) in packing.placements.iter().enumerate() {
+            for b in &packing.placements[i + 1..] {
+                assert_eq!(a.rect.intersection(&b.rect), None, "{:?} overlaps {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn random_packings_are_valid() {
+        let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+        for round in 0..300 {
+            let (count, max_side) = (1 + rng.below(30), 1 + rng.below(40));
+            let items = random_items(&mut rng, count, max_side);
+            let container = Rectangle::new(1 + rng.below(120) as u32, 1 + rng.below(120) as u32);
+            let allow_rotation = round % 2 == 0;
+            for strategy in STRATEGIES {
+                let packing = pack(container, &items, strategy, allow_rotation).unwrap();
+                check(&packing, &items, allow_rotation);
+                assert!(packing.used_area() <= container.area_u64());
+            }
+        }
+    }
+
+    #[test]
+    fn min_area_packings_hold_everything() {
+        let mut rng = Rng(12_345);
+        for round in 0..60 {
+            let (count, max_side) = (1 + rng.below(20), 1 + rng.below(30));
+            let items = random_items(&mut rng, count, max_side);
+            let total: u64 = items.iter().map(Rectangle::area_u64).sum();
+            let allow_rotation = round % 2 == 0;
+            for strategy in STRATEGIES {
+                let packing = pack_min_area(&items, strategy, allow_rotation).unwrap();
+                assert!(packing.is_complete());
+                check(&packing, &items, allow_rotation);
+                assert!(packing.container.area_u64() >= total, "{:?} < {}", packing.container, total);
+                assert_eq!(packing.wasted_area(), packing.container.area_u64() - total);
+            }
+        }
+    }
+
+    #[test]
+    fn exact_fits() {
+        let items = [Rectangle::new(2, 2); 4];
+        for strategy in STRATEGIES {
+            let packing = pack(Rectangle::square(4), &items, strategy, false).unwrap();
+            assert!(packing.is_complete());
+            assert_eq!(packing.occupancy(), 1.0);
+        }
+        // Only fits on its side.
+        let packing = pack(Rectangle::new(5, 1), &[Rectangle::new(1, 5)], Strategy::MaxRects, true).unwrap();
+        assert!(packing.placements[0].rotated);
+        let packing = pack(Rectangle::new(5, 1), &[Rectangle::new(1, 5)], Strategy::MaxRects, false).unwrap();
+        assert_eq!(packing.unplaced, [0]);
+        // Too long to lie down on a shelf, so it stands up.
+        let packing = pack(Rectangle::new(8, 16), &[Rectangle::new(8, 16)], Strategy::Shelf, true).unwrap();
+        assert!(packing.is_complete());
+        assert!(!packing.placements[0].rotated);
+    }
+
+    #[test]
+    fn errors() {
+        let items = [Rectangle::new(1, 1), Rectangle::new(3, 0)];
+        assert_eq!(pack(Rectangle::square(10), &items, Strategy::Shelf, false), Err(PackError::EmptyItem(1)));
+        assert_eq!(pack_min_area(&items, Strategy::Shelf, false), Err(PackError::EmptyItem(1)));
+        let huge = Rectangle::new(i32::MAX as u32 + 1, 1);
+        assert_eq!(pack(huge, &items[..1], Strategy::Guillotine, false), Err(PackError::ContainerTooLarge));
+        assert_eq!(pack_min_area(&[], Strategy::MaxRects, true).unwrap().container, Rectangle::new(0, 0));
+        assert_eq!(Packing { container: Rectangle::new(0, 5), placements: Vec::new(), unplaced: Vec::new() }.occupancy(), 0.0);
+    }
+}
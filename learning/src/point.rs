@@ -0,0 +1,178 @@
+// Vector math for the chapter 5 tuple struct `Point(i32, i32, i32)`.
+//
+// Point and Color (see color.rs) have the same fields but are different types on purpose, so there
+// are no conversions between them and the operators here only accept Points.
+
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Point(pub i32, pub i32, pub i32);
+
+impl Point {
+    pub const ORIGIN: Point = Point(0, 0, 0);
+
+    pub fn x(&self) -> i32 {
+        self.0
+    }
+
+    pub fn y(&self) -> i32 {
+        self.1
+    }
+
+    pub fn z(&self) -> i32 {
+        self.2
+    }
+
+    /// Dot product, computed in i128. Each product of two i32s fits in an i64, but the sum of
+    /// three can reach 3 * 2^62, which doesn't.
+    pub fn dot(self, other: Point) -> i128 {
+        self.0 as i128 * other.0 as i128 + self.1 as i128 * other.1 as i128 + self.2 as i128 * other.2 as i128
+    }
+
+    /// Cross product, or `None` if a component doesn't fit in an i32.
+    pub fn cross(self, other: Point) -> Option<Point> {
+        let (a, b) = (self.wide(), other.wide());
+        Some(Point(
+            i32::try_from(a.1 * b.2 - a.2 * b.1).ok()?,
+            i32::try_from(a.2 * b.0 - a.0 * b.2).ok()?,
+            i32::try_from(a.0 * b.1 - a.1 * b.0).ok()?,
+        ))
+    }
+
+    /// Exact up to f64 rounding for every Point, since the squared length is summed in i128.
+    pub fn length(self) -> f64 {
+        (self.dot(self) as f64).sqrt()
+    }
+
+    pub fn distance(self, other: Point) -> f64 {
+        let (a, b) = (self.wide(), other.wide());
+        let (dx, dy, dz) = ((a.0 - b.0) as f64, (a.1 - b.1) as f64, (a.2 - b.2) as f64);
+        (dx * dx + dy * dy + dz * dz).sqrt()
+    }
+
+    /// Taxicab distance.
+    pub fn manhattan_distance(self, other: Point) -> u64 {
+        let (a, b) = (self.wide(), other.wide());
+        (a.0 - b.0).unsigned_abs() + (a.1 - b.1).unsigned_abs() + (a.2 - b.2).unsigned_abs()
+    }
+
+    pub fn checked_add(self, other: Point) -> Option<Point> {
+        Some(Point(
+            self.0.checked_add(other.0)?,
+            self.1.checked_add(other.1)?,
+            self.2.checked_add(other.2)?,
+        ))
+    }
+
+    pub fn checked_sub(self, other: Point) -> Option<Point> {
+        Some(Point(
+            self.0.checked_sub(other.0)?,
+            self.1.checked_sub(other.1)?,
+            self.2.checked_sub(other.2)?,
+        ))
+    }
+
+    pub fn checked_scale(self, factor: i32) -> Option<Point> {
+        Some(Point(
+            self.0.checked_mul(factor)?,
+            self.1.checked_mul(factor)?,
+            self.2.checked_mul(factor)?,
+        ))
+    }
+
+    fn wide(self) -> (i64, i64, i64) {
+        (self.0 as i64, self.1 as i64, self.2 as i64)
+    }
+}
+
+// The operators behave like i32 arithmetic: they panic on overflow in debug builds. Use the
+// checked_ methods when the inputs aren't trusted.
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point(self.0 + other.0, self.1 + other.1, self.2 + other.2)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, other: Point) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        Point(self.0 - other.0, self.1 - other.1, self.2 - other.2)
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, other: Point) {
+        *self = *self - other;
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Point {
+        Point(-self.0, -self.1, -self.2)
+    }
+}
+
+/// Scaling by a whole number.
+impl Mul<i32> for Point {
+    type Output = Point;
+
+    fn mul(self, factor: i32) -> Point {
+        Point(self.0 * factor, self.1 * factor, self.2 * factor)
+    }
+}
+
+impl Mul<Point> for i32 {
+    type Output = Point;
+
+    fn mul(self, point: Point) -> Point {
+        point * self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dot_and_length_at_the_extremes() {
+        let min = Point(i32::MIN, i32::MIN, i32::MIN);
+        let max = Point(i32::MAX, i32::MAX, i32::MAX);
+        assert_eq!(min.dot(min), 3 << 62);
+        assert_eq!(min.dot(max), -3 * (i32::MIN as i128 * -(i32::MAX as i128)));
+        assert_eq!(max.dot(max), 3 * (i32::MAX as i128).pow(2));
+        assert!((min.length() - 3f64.sqrt() * 2f64.powi(31)).abs() < 1e-3);
+        assert_eq!(Point(3, 4, 12).length(), 13.0);
+    }
+
+    #[test]
+    fn cross_at_the_extremes() {
+        let a = Point(i32::MIN, i32::MIN, i32::MAX);
+        let b = Point(i32::MAX, i32::MIN, i32::MIN);
+        assert_eq!(a.cross(b), None);
+        assert_eq!(Point(1, 0, 0).cross(Point(0, 1, 0)), Some(Point(0, 0, 1)));
+        assert_eq!(Point(i32::MAX, 0, 0).cross(Point(i32::MIN, 0, 0)), Some(Point::ORIGIN));
+    }
+
+    #[test]
+    fn distances_do_not_overflow() {
+        let min = Point(i32::MIN, i32::MIN, i32::MIN);
+        let max = Point(i32::MAX, i32::MAX, i32::MAX);
+        assert_eq!(min.manhattan_distance(max), 3 * u32::MAX as u64);
+        assert!((min.distance(max) - 3f64.sqrt() * u32::MAX as f64).abs() < 1e-3);
+        assert_eq!(max.checked_add(Point(1, 0, 0)), None);
+        assert_eq!(min.checked_sub(Point(0, 0, 1)), None);
+        assert_eq!(min.checked_scale(-1), None);
+    }
+}
@@ -0,0 +1,494 @@
+// State quarters, grown from chapter 6's `UsState` and `Coin::Quarter(UsState)`.
+//
+// The 50 State Quarters program ran 1999 to 2008 and was followed in 2009 by quarters for DC and
+// the five territories. Every design was struck for circulation at Philadelphia (P) and Denver (D)
+// and as proofs at San Francisco (S).
+//
+// A Collection counts the quarters we hold and saves them to a plain text file, one line per
+// state and mint mark: `Delaware P 2`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum UsState {
+    Alabama,
+    Alaska,
+    Arizona,
+    Arkansas,
+    California,
+    Colorado,
+    Connecticut,
+    Delaware,
+    Florida,
+    Georgia,
+    Hawaii,
+    Idaho,
+    Illinois,
+    Indiana,
+    Iowa,
+    Kansas,
+    Kentucky,
+    Louisiana,
+    Maine,
+    Maryland,
+    Massachusetts,
+    Michigan,
+    Minnesota,
+    Mississippi,
+    Missouri,
+    Montana,
+    Nebraska,
+    Nevada,
+    NewHampshire,
+    NewJersey,
+    NewMexico,
+    NewYork,
+    NorthCarolina,
+    NorthDakota,
+    Ohio,
+    Oklahoma,
+    Oregon,
+    Pennsylvania,
+    RhodeIsland,
+    SouthCarolina,
+    SouthDakota,
+    Tennessee,
+    Texas,
+    Utah,
+    Vermont,
+    Virginia,
+    Washington,
+    WestVirginia,
+    Wisconsin,
+    Wyoming,
+    AmericanSamoa,
+    DistrictOfColumbia,
+    Guam,
+    NorthernMarianaIslands,
+    PuertoRico,
+    UsVirginIslands,
+}
+
+// Name, postal code and release year, in the same order as the enum.
+const STATES: [(UsState, &str, &str, u16); 56] = [
+    (UsState::Alabama, "Alabama", "AL", 2003),
+    (UsState::Alaska, "Alaska", "AK", 2008),
+    (UsState::Arizona, "Arizona", "AZ", 2008),
+    (UsState::Arkansas, "Arkansas", "AR", 2003),
+    (UsState::California, "California", "CA", 2005),
+    (UsState::Colorado, "Colorado", "CO", 2006),
+    (UsState::Connecticut, "Connecticut", "CT", 1999),
+    (UsState::Delaware, "Delaware", "DE", 1999),
+    (UsState::Florida, "Florida", "FL", 2004),
+    (UsState::Georgia, "Georgia", "GA", 1999),
+    (UsState::Hawaii, "Hawaii", "HI", 2008),
+    (UsState::Idaho, "Idaho", "ID", 2007),
+    (UsState::Illinois, "Illinois", "IL", 2003),
+    (UsState::Indiana, "Indiana", "IN", 2002),
+    (UsState::Iowa, "Iowa", "IA", 2004),
+    (UsState::Kansas, "Kansas", "KS", 2005),
+    (UsState::Kentucky, "Kentucky", "KY", 2001),
+    (UsState::Louisiana, "Louisiana", "LA", 2002),
+    (UsState::Maine, "Maine", "ME", 2003),
+    (UsState::Maryland, "Maryland", "MD", 2000),
+    (UsState::Massachusetts, "Massachusetts", "MA", 2000),
+    (UsState::Michigan, "Michigan", "MI", 2004),
+    (UsState::Minnesota, "Minnesota", "MN", 2005),
+    (UsState::Mississippi, "Mississippi", "MS", 2002),
+    (UsState::Missouri, "Missouri", "MO", 2003),
+    (UsState::Montana, "Montana", "MT", 2007),
+    (UsState::Nebraska, "Nebraska", "NE", 2006),
+    (UsState::Nevada, "Nevada", "NV", 2006),
+    (UsState::NewHampshire, "New Hampshire", "NH", 2000),
+    (UsState::NewJersey, "New Jersey", "NJ", 1999),
+    (UsState::NewMexico, "New Mexico", "NM", 2008),
+    (UsState::NewYork, "New York", "NY", 2001),
+    (UsState::NorthCarolina, "North Carolina", "NC", 2001),
+    (UsState::NorthDakota, "North Dakota", "ND", 2006),
+    (UsState::Ohio, "Ohio", "OH", 2002),
+    (UsState::Oklahoma, "Oklahoma", "OK", 2008),
+    (UsState::Oregon, "Oregon", "OR", 2005),
+    (UsState::Pennsylvania, "Pennsylvania", "PA", 1999),
+    (UsState::RhodeIsland, "Rhode Island", "RI", 2001),
+    (UsState::SouthCarolina, "South Carolina", "SC", 2000),
+    (UsState::SouthDakota, "South Dakota", "SD", 2006),
+    (UsState::Tennessee, "Tennessee", "TN", 2002),
+    (UsState::Texas, "Texas", "TX", 2004),
+    (UsState::Utah, "Utah", "UT", 2007),
+    (UsState::Vermont, "Vermont", "VT", 2001),
+    (UsState::Virginia, "Virginia", "VA", 2000),
+    (UsState::Washington, "Washington", "WA", 2007),
+    (UsState::WestVirginia, "West Virginia", "WV", 2005),
+    (UsState::Wisconsin, "Wisconsin", "WI", 2004),
+    (UsState::Wyoming, "Wyoming", "WY", 2007),
+    (UsState::AmericanSamoa, "American Samoa", "AS", 2009),
+    (UsState::DistrictOfColumbia, "District of Columbia", "DC", 2009),
+    (UsState::Guam, "Guam", "GU", 2009),
+    (UsState::NorthernMarianaIslands, "Northern Mariana Islands", "MP", 2009),
+    (UsState::PuertoRico, "Puerto Rico", "PR", 2009),
+    (UsState::UsVirginIslands, "U.S. Virgin Islands", "VI", 2009),
+];
+
+impl UsState {
+    pub const ALL: [UsState; STATES.len()] = {
+        let mut all = [UsState::Alabama; STATES.len()];
+        let mut i = 0;
+        while i < STATES.len() {
+            all[i] = STATES[i].0;
+            i += 1;
+        }
+        all
+    };
+
+    fn entry(self) -> &'static (UsState, &'static str, &'static str, u16) {
+        // STATES is in the same order as the enum.
+        &STATES[self as usize]
+    }
+
+    pub fn name(self) -> &'static str {
+        self.entry().1
+    }
+
+    /// Two-letter postal code.
+    pub fn abbreviation(self) -> &'static str {
+        self.entry().2
+    }
+
+    pub fn release_year(self) -> u16 {
+        self.entry().3
+    }
+
+    /// False for DC and the territories.
+    pub fn is_state(self) -> bool {
+        self.release_year() < 2009
+    }
+
+    pub fn mint_marks(self) -> &'static [MintMark] {
+        &[MintMark::Philadelphia, MintMark::Denver, MintMark::SanFrancisco]
+    }
+}
+
+impl fmt::Display for UsState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Accepts the full name or the postal code, ignoring case.
+impl FromStr for UsState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<UsState, String> {
+        let s = s.trim();
+        STATES
+            .iter()
+            .find(|(_, name, abbreviation, _)| {
+                name.eq_ignore_ascii_case(s) || abbreviation.eq_ignore_ascii_case(s)
+            })
+            .map(|entry| entry.0)
+            .ok_or_else(|| format!("unknown state '{}'", s))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum MintMark {
+    Philadelphia,
+    Denver,
+    /// Proof coins only.
+    SanFrancisco,
+}
+
+impl MintMark {
+    pub fn letter(self) -> char {
+        match self {
+            MintMark::Philadelphia => 'P',
+            MintMark::Denver => 'D',
+            MintMark::SanFrancisco => 'S',
+        }
+    }
+}
+
+impl fmt::Display for MintMark {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.letter())
+    }
+}
+
+impl FromStr for MintMark {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<MintMark, String> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "P" => Ok(MintMark::Philadelphia),
+            "D" => Ok(MintMark::Denver),
+            "S" => Ok(MintMark::SanFrancisco),
+            other => Err(format!("unknown mint mark '{}'", other)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CollectionError {
+    Io(io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for CollectionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CollectionError::Io(e) => write!(f, "{}", e),
+            CollectionError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for CollectionError {}
+
+impl From<io::Error> for CollectionError {
+    fn from(e: io::Error) -> CollectionError {
+        CollectionError::Io(e)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Collection {
+    counts: BTreeMap<(UsState, MintMark), u32>,
+}
+
+impl Collection {
+    pub fn new() -> Collection {
+        Collection::default()
+    }
+
+    /// Records one quarter and returns true if we already had that state and mint mark. A count
+    /// that has reached u32::MAX stays there.
+    pub fn add(&mut self, state: UsState, mint: MintMark) -> bool {
+        let count = self.counts.entry((state, mint)).or_insert(0);
+        *count = count.saturating_add(1);
+        *count > 1
+    }
+
+    /// Takes one quarter out, returning false if there wasn't one.
+    pub fn remove(&mut self, state: UsState, mint: MintMark) -> bool {
+        match self.counts.get_mut(&(state, mint)) {
+            Some(count) if *count > 1 => {
+                *count -= 1;
+                true
+            }
+            Some(_) => {
+                self.counts.remove(&(state, mint));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn count(&self, state: UsState, mint: MintMark) -> u32 {
+        self.counts.get(&(state, mint)).copied().unwrap_or(0)
+    }
+
+    /// Summed in u64, since each count can be up to u32::MAX.
+    pub fn total(&self) -> u64 {
+        self.counts.values().map(|&count| u64::from(count)).sum()
+    }
+
+    pub fn has_state(&self, state: UsState) -> bool {
+        state.mint_marks().iter().any(|&mint| self.count(state, mint) > 0)
+    }
+
+    /// Every state and mint mark we have more than one of, with the number of spares.
+    pub fn duplicates(&self) -> Vec<(UsState, MintMark, u32)> {
+        self.counts
+            .iter()
+            .filter(|(_, &count)| count > 1)
+            .map(|(&(state, mint), &count)| (state, mint, count - 1))
+            .collect()
+    }
+
+    /// States without a single quarter, in release order.
+    pub fn missing_states(&self) -> Vec<UsState> {
+        let mut missing: Vec<UsState> =
+            UsState::ALL.iter().copied().filter(|&state| !self.has_state(state)).collect();
+        missing.sort_by_key(|state| state.release_year());
+        missing
+    }
+
+    /// Every state and mint mark combination not yet held.
+    pub fn missing(&self) -> Vec<(UsState, MintMark)> {
+        let mut missing = Vec::new();
+        for state in UsState::ALL {
+            for &mint in state.mint_marks() {
+                if self.count(state, mint) == 0 {
+                    missing.push((state, mint));
+                }
+            }
+        }
+        missing.sort_by_key(|(state, _)| state.release_year());
+        missing
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for (&(state, mint), count) in &self.counts {
+            text.push_str(&format!("{} {} {}\n", state.name(), mint, count));
+        }
+        text
+    }
+
+    /// Blank lines and lines starting with `#` are skipped.
+    pub fn from_text(text: &str) -> Result<Collection, CollectionError> {
+        let mut collection = Collection::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parse_error = |message: String| CollectionError::Parse {
+                line: i + 1,
+                message,
+            };
+
+            // State names contain spaces, so take the count and mint mark off the right.
+            let mut words: Vec<&str> = line.split_whitespace().collect();
+            let count = words.pop().unwrap_or("");
+            if count.parse::<u32>().is_err() && count.parse::<MintMark>().is_ok() {
+                return Err(parse_error(String::from("missing count after the mint mark")));
+            }
+            let mint = words.pop().ok_or_else(|| parse_error(String::from("missing mint mark")))?;
+            if words.is_empty() {
+                // "Delaware 2" is short a mint mark, "P 2" a state.
+                let missing = if mint.parse::<MintMark>().is_ok() { "missing state" } else { "missing mint mark" };
+                return Err(parse_error(String::from(missing)));
+            }
+
+            let state: UsState = words.join(" ").parse().map_err(parse_error)?;
+            let mint: MintMark = mint.parse().map_err(parse_error)?;
+            let count: u32 = count
+                .parse()
+                .map_err(|_| parse_error(format!("'{}' is not a count", count)))?;
+
+            if count > 0 {
+                // The same state and mint mark can be listed more than once; the counts add up.
+                let total = collection.counts.entry((state, mint)).or_insert(0);
+                *total = total
+                    .checked_add(count)
+                    .ok_or_else(|| parse_error(format!("{} {} adds up to more than {}", state, mint, u32::MAX)))?;
+            }
+        }
+
+        Ok(collection)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), CollectionError> {
+        fs::write(path, self.to_text())?;
+        Ok(())
+    }
+
+    /// A missing file is an empty collection.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Collection, CollectionError> {
+        match fs::read_to_string(path) {
+            Ok(text) => Collection::from_text(&text),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Collection::new()),
+            Err(e) => Err(CollectionError::Io(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn district_of_columbia_round_trips() {
+        let dc = UsState::DistrictOfColumbia;
+        assert_eq!(dc.name(), "District of Columbia");
+        assert_eq!("district of columbia".parse(), Ok(dc));
+        assert_eq!("DC".parse(), Ok(dc));
+
+        let mut collection = Collection::new();
+        collection.add(dc, MintMark::Denver);
+        assert_eq!(Collection::from_text(&collection.to_text()).unwrap(), collection);
+    }
+
+    #[test]
+    fn counts_stop_at_the_maximum() {
+        let text = format!("Delaware P {}\nDelaware D {}\n", u32::MAX - 1, u32::MAX);
+        let mut collection = Collection::from_text(&text).unwrap();
+        assert!(collection.add(UsState::Delaware, MintMark::Philadelphia));
+        assert!(collection.add(UsState::Delaware, MintMark::Philadelphia));
+        assert_eq!(collection.count(UsState::Delaware, MintMark::Philadelphia), u32::MAX);
+        assert_eq!(collection.total(), 2 * u32::MAX as u64);
+    }
+
+    #[test]
+    fn repeated_lines_that_overflow_are_an_error() {
+        let text = format!("Delaware P 1\n\nDelaware P {}\n", u32::MAX);
+        match Collection::from_text(&text) {
+            Err(CollectionError::Parse { line, message }) => {
+                assert_eq!(line, 3);
+                assert_eq!(message, format!("Delaware P adds up to more than {}", u32::MAX));
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+        let text = format!("Delaware P 1\nDelaware P {}\n", u32::MAX - 1);
+        assert_eq!(Collection::from_text(&text).unwrap().count(UsState::Delaware, MintMark::Philadelphia), u32::MAX);
+    }
+
+    fn parse_error(text: &str) -> (usize, String) {
+        match Collection::from_text(text) {
+            Err(CollectionError::Parse { line, message }) => (line, message),
+            other => panic!("expected a parse error for {:?}, got {:?}", text, other),
+        }
+    }
+
+    #[test]
+    fn parse_errors_name_what_is_missing() {
+        let missing_count = String::from("missing count after the mint mark");
+        assert_eq!(parse_error("Delaware P"), (1, missing_count.clone()));
+        assert_eq!(parse_error("# header\nNew York d\n"), (2, missing_count));
+        assert_eq!(parse_error("Delaware 2"), (1, String::from("missing mint mark")));
+        assert_eq!(parse_error("P 2"), (1, String::from("missing state")));
+        assert_eq!(parse_error("Delaware"), (1, String::from("missing mint mark")));
+        assert_eq!(parse_error("Atlantis P 2"), (1, String::from("unknown state 'Atlantis'")));
+        assert_eq!(parse_error("Delaware X 2"), (1, String::from("unknown mint mark 'X'")));
+        assert_eq!(parse_error("Delaware P two"), (1, String::from("'two' is not a count")));
+        assert_eq!(parse_error("Delaware P -1"), (1, String::from("'-1' is not a count")));
+    }
+
+    #[test]
+    fn text_is_forgiving_about_spacing_and_case() {
+        let collection = Collection::from_text("  new   york\tD  3\n\n# comment\nde p 0\nNY d 1\n").unwrap();
+        assert_eq!(collection.count(UsState::NewYork, MintMark::Denver), 4);
+        // A zero count adds nothing.
+        assert_eq!(collection.total(), 4);
+        assert_eq!(collection.to_text(), "New York D 4\n");
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir().join(format!("quarters-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        assert_eq!(Collection::load(&path).unwrap(), Collection::new());
+
+        let mut collection = Collection::new();
+        collection.add(UsState::Delaware, MintMark::Philadelphia);
+        collection.add(UsState::Delaware, MintMark::Philadelphia);
+        collection.add(UsState::Hawaii, MintMark::SanFrancisco);
+        collection.save(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "Delaware P 2\nHawaii S 1\n");
+        assert_eq!(Collection::load(&path).unwrap(), collection);
+
+        fs::write(&path, "Delaware P\n").unwrap();
+        assert!(matches!(Collection::load(&path), Err(CollectionError::Parse { line: 1, .. })));
+        fs::remove_file(&path).unwrap();
+
+        // A directory can't be read as a file.
+        assert!(matches!(Collection::load(std::env::temp_dir()), Err(CollectionError::Io(_))));
+    }
+}
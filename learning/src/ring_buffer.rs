@@ -0,0 +1,206 @@
+// A fixed-capacity queue that overwrites its oldest element when full, for keeping the last N
+// telemetry samples.
+
+use std::collections::vec_deque;
+use std::collections::VecDeque;
+use std::ops::RangeBounds;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RingBuffer<T> {
+    items: VecDeque<T>,
+    capacity: usize,
+}
+
+impl<T> RingBuffer<T> {
+    /// Allocates room for `capacity` elements up front; the buffer never grows past that.
+    pub fn with_capacity(capacity: usize) -> RingBuffer<T> {
+        RingBuffer {
+            items: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Adds an element at the back. When the buffer is full the oldest element is pushed out and
+    /// returned. With a capacity of 0 the new element itself comes straight back.
+    pub fn push(&mut self, item: T) -> Option<T> {
+        if self.capacity == 0 {
+            return Some(item);
+        }
+
+        let overwritten = if self.is_full() {
+            self.items.pop_front()
+        } else {
+            None
+        };
+        self.items.push_back(item);
+        overwritten
+    }
+
+    /// Removes the oldest element.
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.items.pop_front()
+    }
+
+    /// Removes the newest element.
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.items.pop_back()
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.items.front()
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.items.back()
+    }
+
+    /// Index 0 is the oldest element.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.items.get(index)
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.items.get_mut(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.items.len() == self.capacity
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    /// Oldest to newest.
+    pub fn iter(&self) -> vec_deque::Iter<'_, T> {
+        self.items.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> vec_deque::IterMut<'_, T> {
+        self.items.iter_mut()
+    }
+
+    /// Removes the given range (0 is the oldest) and yields it oldest first.
+    ///
+    /// Panics if the range is out of bounds, like `Vec::drain`.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> vec_deque::Drain<'_, T> {
+        self.items.drain(range)
+    }
+
+    /// Copies the contents into a Vec, oldest first.
+    pub fn to_vec(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        self.items.iter().cloned().collect()
+    }
+}
+
+/// Pushes every element in turn, so only the last `capacity` of them are kept.
+impl<T> Extend<T> for RingBuffer<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<T> IntoIterator for RingBuffer<T> {
+    type Item = T;
+    type IntoIter = vec_deque::IntoIter<T>;
+
+    fn into_iter(self) -> vec_deque::IntoIter<T> {
+        self.items.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RingBuffer<T> {
+    type Item = &'a T;
+    type IntoIter = vec_deque::Iter<'a, T>;
+
+    fn into_iter(self) -> vec_deque::Iter<'a, T> {
+        self.items.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut RingBuffer<T> {
+    type Item = &'a mut T;
+    type IntoIter = vec_deque::IterMut<'a, T>;
+
+    fn into_iter(self) -> vec_deque::IterMut<'a, T> {
+        self.items.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn random_operations_match_vec() {
+        for capacity in 0..6 {
+            let mut rng = Rng(0x2545_f491_4f6c_dd1d + capacity as u64);
+            let mut ring = RingBuffer::with_capacity(capacity);
+            let mut oracle: Vec<usize> = Vec::new();
+
+            for step in 0..2000 {
+                let len = oracle.len();
+                match rng.below(6) {
+                    0 | 1 => {
+                        let expected = if capacity == 0 {
+                            Some(step)
+                        } else {
+                            oracle.push(step);
+                            if oracle.len() > capacity {
+                                Some(oracle.remove(0))
+                            } else {
+                                None
+                            }
+                        };
+                        assert_eq!(ring.push(step), expected);
+                    }
+                    2 => {
+                        let expected = if oracle.is_empty() { None } else { Some(oracle.remove(0)) };
+                        assert_eq!(ring.pop_front(), expected);
+                    }
+                    3 => assert_eq!(ring.pop_back(), oracle.pop()),
+                    4 => {
+                        let index = rng.below(len + 2);
+                        assert_eq!(ring.get(index), oracle.get(index));
+                    }
+                    _ => {
+                        let end = rng.below(len + 1);
+                        let start = rng.below(end + 1);
+                        let drained: Vec<usize> = ring.drain(start..end).collect();
+                        assert_eq!(drained, oracle.drain(start..end).collect::<Vec<_>>());
+                    }
+                }
+                assert_eq!(ring.to_vec(), oracle);
+                assert_eq!(ring.front(), oracle.first());
+                assert_eq!(ring.back(), oracle.last());
+                assert_eq!(ring.is_full(), oracle.len() == capacity);
+            }
+            assert_eq!(ring.into_iter().collect::<Vec<_>>(), oracle);
+        }
+    }
+
+    #[test]
+    fn extend_keeps_the_newest() {
+        let mut ring = RingBuffer::with_capacity(3);
+        ring.extend(1..=10);
+        assert_eq!(ring.to_vec(), [8, 9, 10]);
+    }
+}
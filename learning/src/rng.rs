@@ -0,0 +1,30 @@
+// A small xorshift generator, so randomized tests and the benchmarks are repeatable without pulling
+// in a crate. The library only builds it for tests; the examples include this file with
+// `#[path = "../src/rng.rs"] mod rng;`.
+//
+// Each user needs a different subset of the methods.
+#![allow(dead_code)]
+
+/// The seed must not be 0, which xorshift never leaves.
+pub struct Rng(pub u64);
+
+impl Rng {
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number in `0..n`. Panics if `n` is 0.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    /// A number in `0..n` that is usually small: cubing a uniform number in [0, 1) piles the
+    /// picks up near 0, like word frequencies in real text.
+    pub fn skewed(&mut self, n: usize) -> usize {
+        let u = (self.next() >> 11) as f64 / (1u64 << 53) as f64;
+        (u * u * u * n as f64) as usize
+    }
+}
@@ -0,0 +1,837 @@
+// A rope: a string stored as a balanced tree of small chunks, for editing large text.
+//
+// Chapter 8 builds Strings with push_str and `s1 + "-" + &s2`. That's ideal for short strings,
+// but inserting into the middle of a String moves everything after the insertion point, so an
+// editor making many changes to a large document does work proportional to its whole size each
+// time. A rope only touches the chunks along one path from the root, so inserts and removals at
+// any char offset are O(log n). See examples/bench_rope.rs for how the two compare.
+//
+// The tree is an AVL tree. Every edit is built from two operations: split a tree at a char
+// offset, and join two trees back together, both of which keep it balanced.
+
+use std::fmt;
+use std::ops::RangeBounds;
+
+use crate::access;
+
+// Chunks are merged while they fit in this many bytes, and new text is cut into pieces no larger.
+const MAX_LEAF: usize = 1024;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Info {
+    bytes: usize,
+    chars: usize,
+    newlines: usize,
+}
+
+impl Info {
+    fn of(text: &str) -> Info {
+        Info {
+            bytes: text.len(),
+            chars: text.chars().count(),
+            newlines: text.bytes().filter(|&b| b == b'\n').count(),
+        }
+    }
+
+    fn add(self, other: Info) -> Info {
+        Info {
+            bytes: self.bytes + other.bytes,
+            chars: self.chars + other.chars,
+            newlines: self.newlines + other.newlines,
+        }
+    }
+
+    fn sub(self, other: Info) -> Info {
+        Info {
+            bytes: self.bytes - other.bytes,
+            chars: self.chars - other.chars,
+            newlines: self.newlines - other.newlines,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Leaf {
+        text: String,
+        info: Info,
+    },
+    Branch {
+        left: Box<Node>,
+        right: Box<Node>,
+        info: Info,
+        height: usize,
+    },
+}
+
+impl Node {
+    fn leaf(text: String) -> Box<Node> {
+        let info = Info::of(&text);
+        Box::new(Node::Leaf { text, info })
+    }
+
+    fn branch(left: Box<Node>, right: Box<Node>) -> Box<Node> {
+        let info = left.info().add(right.info());
+        let height = left.height().max(right.height()) + 1;
+        Box::new(Node::Branch {
+            left,
+            right,
+            info,
+            height,
+        })
+    }
+
+    fn info(&self) -> Info {
+        match self {
+            Node::Leaf { info, .. } | Node::Branch { info, .. } => *info,
+        }
+    }
+
+    fn height(&self) -> usize {
+        match self {
+            Node::Leaf { .. } => 0,
+            Node::Branch { height, .. } => *height,
+        }
+    }
+
+    // Builds a balanced tree from text, cut into chunks on char boundaries.
+    fn build(text: &str) -> Option<Box<Node>> {
+        let mut chunks = Vec::new();
+        let mut rest = text;
+        while !rest.is_empty() {
+            let mut end = rest.len().min(MAX_LEAF);
+            while !rest.is_char_boundary(end) {
+                end -= 1;
+            }
+            chunks.push(&rest[..end]);
+            rest = &rest[end..];
+        }
+        Node::build_balanced(&chunks)
+    }
+
+    fn build_balanced(chunks: &[&str]) -> Option<Box<Node>> {
+        match chunks {
+            [] => None,
+            [chunk] => Some(Node::leaf(String::from(*chunk))),
+            _ => {
+                let (left, right) = chunks.split_at(chunks.len() / 2);
+                Some(Node::branch(Node::build_balanced(left)?, Node::build_balanced(right)?))
+            }
+        }
+    }
+}
+
+// Makes a branch from two balanced trees whose heights differ by at most two, rotating if needed.
+fn balance(left: Box<Node>, right: Box<Node>) -> Box<Node> {
+    if left.height() > right.height() + 1 {
+        if let Node::Branch {
+            left: outer,
+            right: inner,
+            ..
+        } = *left
+        {
+            if outer.height() >= inner.height() {
+                return Node::branch(outer, Node::branch(inner, right));
+            }
+            if let Node::Branch {
+                left: inner_left,
+                right: inner_right,
+                ..
+            } = *inner
+            {
+                return Node::branch(Node::branch(outer, inner_left), Node::branch(inner_right, right));
+            }
+            unreachable!("a taller inner subtree is a branch");
+        }
+        unreachable!("a subtree two levels taller is a branch");
+    }
+
+    if right.height() > left.height() + 1 {
+        if let Node::Branch {
+            left: inner,
+            right: outer,
+            ..
+        } = *right
+        {
+            if outer.height() >= inner.height() {
+                return Node::branch(Node::branch(left, inner), outer);
+            }
+            if let Node::Branch {
+                left: inner_left,
+                right: inner_right,
+                ..
+            } = *inner
+            {
+                return Node::branch(Node::branch(left, inner_left), Node::branch(inner_right, outer));
+            }
+            unreachable!("a taller inner subtree is a branch");
+        }
+        unreachable!("a subtree two levels taller is a branch");
+    }
+
+    Node::branch(left, right)
+}
+
+// Concatenates two trees. The shorter one is hung off the edge of the taller one at the same
+// height, so only that path needs rebalancing. Two small leaves that meet are merged.
+fn join(left: Box<Node>, right: Box<Node>) -> Box<Node> {
+    if left.height() > right.height() {
+        if let Node::Branch {
+            left: left_left,
+            right: left_right,
+            ..
+        } = *left
+        {
+            return balance(left_left, join(left_right, right));
+        }
+        unreachable!("the taller tree is a branch");
+    }
+
+    if right.height() > left.height() {
+        if let Node::Branch {
+            left: right_left,
+            right: right_right,
+            ..
+        } = *right
+        {
+            return balance(join(left, right_left), right_right);
+        }
+        unreachable!("the taller tree is a branch");
+    }
+
+    match (*left, *right) {
+        (Node::Leaf { mut text, .. }, Node::Leaf { text: more, .. }) if text.len() + more.len() <= MAX_LEAF => {
+            text.push_str(&more);
+            Node::leaf(text)
+        }
+        (left, right) => Node::branch(Box::new(left), Box::new(right)),
+    }
+}
+
+fn join_options(left: Option<Box<Node>>, right: Option<Box<Node>>) -> Option<Box<Node>> {
+    match (left, right) {
+        (Some(left), Some(right)) => Some(join(left, right)),
+        (left, None) => left,
+        (None, right) => right,
+    }
+}
+
+// Splits into the first `at` chars and the rest. Either side can be empty.
+fn split(node: Box<Node>, at: usize) -> (Option<Box<Node>>, Option<Box<Node>>) {
+    if at == 0 {
+        return (None, Some(node));
+    }
+    if at >= node.info().chars {
+        return (Some(node), None);
+    }
+
+    match *node {
+        Node::Leaf { mut text, .. } => {
+            let byte = byte_offset(&text, at);
+            let rest = text.split_off(byte);
+            (Some(Node::leaf(text)), Some(Node::leaf(rest)))
+        }
+        Node::Branch { left, right, .. } => {
+            let left_chars = left.info().chars;
+            if at <= left_chars {
+                let (a, b) = split(left, at);
+                (a, join_options(b, Some(right)))
+            } else {
+                let (a, b) = split(right, at - left_chars);
+                (join_options(Some(left), a), b)
+            }
+        }
+    }
+}
+
+// The fast path for small edits: change one leaf in place and fix up the counts above it,
+// without splitting or joining anything. Returns false if the edit doesn't fit in one leaf.
+fn edit_leaf(node: &mut Node, start: usize, end: usize, insert: &str) -> bool {
+    match node {
+        Node::Leaf { text, info } => {
+            let from = byte_offset(text, start);
+            let to = from + byte_offset(&text[from..], end - start);
+            let removed = Info::of(&text[from..to]);
+            let inserted = Info::of(insert);
+            let fits = text.len() - removed.bytes + inserted.bytes <= MAX_LEAF;
+            // Leaves are never left empty; removing a whole leaf goes the slow way.
+            if !fits || (removed.chars == info.chars && inserted.chars == 0) {
+                return false;
+            }
+            text.replace_range(from..to, insert);
+            *info = info.sub(removed).add(inserted);
+            true
+        }
+        Node::Branch { left, right, info, .. } => {
+            let left_chars = left.info().chars;
+            let done = if end <= left_chars {
+                edit_leaf(left, start, end, insert)
+            } else if start >= left_chars {
+                edit_leaf(right, start - left_chars, end - left_chars, insert)
+            } else {
+                false
+            };
+            if done {
+                *info = left.info().add(right.info());
+            }
+            done
+        }
+    }
+}
+
+fn byte_offset(text: &str, chars: usize) -> usize {
+    text.char_indices().nth(chars).map_or(text.len(), |(offset, _)| offset)
+}
+
+#[derive(Clone, Default)]
+pub struct Rope {
+    root: Option<Box<Node>>,
+}
+
+impl Rope {
+    pub fn new() -> Rope {
+        Rope { root: None }
+    }
+
+    fn info(&self) -> Info {
+        self.root.as_ref().map(|root| root.info()).unwrap_or_default()
+    }
+
+    pub fn len_chars(&self) -> usize {
+        self.info().chars
+    }
+
+    pub fn len_bytes(&self) -> usize {
+        self.info().bytes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Inserts `text` so that it starts at char index `at`.
+    ///
+    /// Panics if `at` is past the end, like `String::insert_str`.
+    pub fn insert(&mut self, at: usize, text: &str) {
+        let len = self.len_chars();
+        assert!(at <= len, "insertion index {} is past the end ({})", at, len);
+        if text.is_empty() {
+            return;
+        }
+
+        if let Some(root) = self.root.as_deref_mut() {
+            if edit_leaf(root, at, at, text) {
+                return;
+            }
+        }
+
+        let (left, right) = match self.root.take() {
+            Some(root) => split(root, at),
+            None => (None, None),
+        };
+        self.root = join_options(join_options(left, Node::build(text)), right);
+    }
+
+    pub fn push_str(&mut self, text: &str) {
+        self.insert(self.len_chars(), text);
+    }
+
+    /// Removes a range of chars.
+    ///
+    /// Panics if the range is backwards or goes past the end, like `String::drain`.
+    pub fn remove<R: RangeBounds<usize>>(&mut self, range: R) {
+        let (start, end) = self.resolve(&range);
+        if let Some(root) = self.root.as_deref_mut() {
+            if edit_leaf(root, start, end, "") {
+                return;
+            }
+        }
+
+        let root = match self.root.take() {
+            Some(root) => root,
+            None => return,
+        };
+        let (rest, right) = split(root, end);
+        let left = rest.and_then(|rest| split(rest, start).0);
+        self.root = join_options(left, right);
+    }
+
+    /// Adds another rope to the end in O(log n).
+    pub fn append(&mut self, other: Rope) {
+        self.root = join_options(self.root.take(), other.root);
+    }
+
+    /// Splits in two at char index `at`, leaving the first part in `self`.
+    ///
+    /// Panics if `at` is past the end.
+    pub fn split_off(&mut self, at: usize) -> Rope {
+        let len = self.len_chars();
+        assert!(at <= len, "split index {} is past the end ({})", at, len);
+        let (left, right) = match self.root.take() {
+            Some(root) => split(root, at),
+            None => (None, None),
+        };
+        self.root = left;
+        Rope { root: right }
+    }
+
+    pub fn char_at(&self, index: usize) -> Option<char> {
+        let mut node = self.root.as_deref()?;
+        let mut index = index;
+        loop {
+            match node {
+                Node::Leaf { text, .. } => return text.chars().nth(index),
+                Node::Branch { left, right, .. } => {
+                    let left_chars = left.info().chars;
+                    if index < left_chars {
+                        node = left;
+                    } else {
+                        index -= left_chars;
+                        node = right;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Copies a range of chars out into a String.
+    ///
+    /// Panics if the range is backwards or goes past the end.
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> String {
+        let (start, end) = self.resolve(&range);
+        let mut out = String::new();
+        if let Some(root) = &self.root {
+            collect(root, start, end, &mut out);
+        }
+        out
+    }
+
+    /// How many lines `lines()` yields. A trailing newline doesn't start another line.
+    pub fn len_lines(&self) -> usize {
+        let len = self.len_chars();
+        if len == 0 {
+            return 0;
+        }
+        let newlines = self.info().newlines;
+        if self.char_at(len - 1) == Some('\n') {
+            newlines
+        } else {
+            newlines + 1
+        }
+    }
+
+    /// The line a char index is on, counting from 0.
+    pub fn char_to_line(&self, index: usize) -> usize {
+        let mut node = match self.root.as_deref() {
+            Some(root) => root,
+            None => return 0,
+        };
+        let mut index = index.min(self.len_chars());
+        let mut line = 0;
+        loop {
+            match node {
+                Node::Leaf { text, .. } => {
+                    return line + text.chars().take(index).filter(|&c| c == '\n').count();
+                }
+                Node::Branch { left, right, .. } => {
+                    let left_info = left.info();
+                    if index < left_info.chars {
+                        node = left;
+                    } else {
+                        index -= left_info.chars;
+                        line += left_info.newlines;
+                        node = right;
+                    }
+                }
+            }
+        }
+    }
+
+    /// The char index where a line starts. None if there aren't that many lines.
+    pub fn line_to_char(&self, line: usize) -> Option<usize> {
+        if line >= self.len_lines() {
+            return None;
+        }
+        if line == 0 {
+            return Some(0);
+        }
+
+        // Find the `line`th newline; the line starts just after it.
+        let mut node = self.root.as_deref()?;
+        let mut remaining = line;
+        let mut offset = 0;
+        loop {
+            match node {
+                Node::Leaf { text, .. } => {
+                    let (index, _) = text.chars().enumerate().filter(|&(_, c)| c == '\n').nth(remaining - 1)?;
+                    return Some(offset + index + 1);
+                }
+                Node::Branch { left, right, .. } => {
+                    let left_info = left.info();
+                    if remaining <= left_info.newlines {
+                        node = left;
+                    } else {
+                        remaining -= left_info.newlines;
+                        offset += left_info.chars;
+                        node = right;
+                    }
+                }
+            }
+        }
+    }
+
+    /// One line without its line ending, like the items of `str::lines`.
+    pub fn line(&self, line: usize) -> Option<String> {
+        let start = self.line_to_char(line)?;
+        let end = self.line_to_char(line + 1).unwrap_or(self.len_chars());
+        let mut text = self.slice(start..end);
+        if text.ends_with('\n') {
+            text.pop();
+            if text.ends_with('\r') {
+                text.pop();
+            }
+        }
+        Some(text)
+    }
+
+    /// The chunks of text the rope is stored in, in order.
+    pub fn chunks(&self) -> Chunks<'_> {
+        Chunks {
+            stack: self.root.as_deref().into_iter().collect(),
+        }
+    }
+
+    pub fn chars(&self) -> Chars<'_> {
+        Chars {
+            chunks: self.chunks(),
+            current: "".chars(),
+        }
+    }
+
+    pub fn lines(&self) -> Lines<'_> {
+        Lines {
+            rope: self,
+            next: 0,
+            count: self.len_lines(),
+        }
+    }
+
+    fn resolve<R: RangeBounds<usize>>(&self, range: &R) -> (usize, usize) {
+        let len = self.len_chars();
+        let (start, end) = access::bounds(range, len);
+        assert!(start <= end && end <= len, "range {}..{} is out of bounds for length {}", start, end, len);
+        (start, end)
+    }
+}
+
+// Appends chars start..end of the subtree, only visiting the parts that overlap the range.
+fn collect(node: &Node, start: usize, end: usize, out: &mut String) {
+    if start >= end {
+        return;
+    }
+    match node {
+        Node::Leaf { text, .. } => {
+            let from = byte_offset(text, start);
+            let to = from + byte_offset(&text[from..], end - start);
+            out.push_str(&text[from..to]);
+        }
+        Node::Branch { left, right, .. } => {
+            let left_chars = left.info().chars;
+            if start < left_chars {
+                collect(left, start, end.min(left_chars), out);
+            }
+            if end > left_chars {
+                collect(right, start.saturating_sub(left_chars), end - left_chars, out);
+            }
+        }
+    }
+}
+
+impl From<&str> for Rope {
+    fn from(text: &str) -> Rope {
+        Rope {
+            root: Node::build(text),
+        }
+    }
+}
+
+impl From<String> for Rope {
+    fn from(text: String) -> Rope {
+        Rope::from(text.as_str())
+    }
+}
+
+impl From<&Rope> for String {
+    fn from(rope: &Rope) -> String {
+        let mut text = String::with_capacity(rope.len_bytes());
+        rope.chunks().for_each(|chunk| text.push_str(chunk));
+        text
+    }
+}
+
+impl fmt::Display for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for chunk in self.chunks() {
+            write!(f, "{}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Rope({:?})", String::from(self))
+    }
+}
+
+// Compares the text, not how it happens to be chunked.
+impl PartialEq for Rope {
+    fn eq(&self, other: &Rope) -> bool {
+        self.len_bytes() == other.len_bytes() && self.chars().eq(other.chars())
+    }
+}
+
+impl Eq for Rope {}
+
+impl PartialEq<str> for Rope {
+    fn eq(&self, other: &str) -> bool {
+        self.len_bytes() == other.len() && self.chars().eq(other.chars())
+    }
+}
+
+impl PartialEq<&str> for Rope {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl Extend<char> for Rope {
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        let text: String = iter.into_iter().collect();
+        self.push_str(&text);
+    }
+}
+
+impl<'a> Extend<&'a str> for Rope {
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        for text in iter {
+            self.push_str(text);
+        }
+    }
+}
+
+/// Iterator over the stored chunks, left to right.
+pub struct Chunks<'a> {
+    stack: Vec<&'a Node>,
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        loop {
+            match self.stack.pop()? {
+                Node::Leaf { text, .. } => return Some(text),
+                Node::Branch { left, right, .. } => {
+                    self.stack.push(right);
+                    self.stack.push(left);
+                }
+            }
+        }
+    }
+}
+
+pub struct Chars<'a> {
+    chunks: Chunks<'a>,
+    current: std::str::Chars<'a>,
+}
+
+impl Iterator for Chars<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        loop {
+            if let Some(c) = self.current.next() {
+                return Some(c);
+            }
+            self.current = self.chunks.next()?.chars();
+        }
+    }
+}
+
+/// Iterator over lines without their line endings. Each step is O(log n) plus the line length.
+pub struct Lines<'a> {
+    rope: &'a Rope,
+    next: usize,
+    count: usize,
+}
+
+impl Iterator for Lines<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        if self.next >= self.count {
+            return None;
+        }
+        self.next += 1;
+        self.rope.line(self.next - 1)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.count - self.next;
+        (remaining, Some(remaining))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    // Walks the whole tree and panics if a cached height or Info is stale, a branch is out of
+    // AVL balance, or a leaf is empty or oversized. Returns the node's height and Info.
+    fn check(node: &Node) -> (usize, Info) {
+        match node {
+            Node::Leaf { text, info } => {
+                assert!(!text.is_empty(), "empty leaf");
+                assert!(text.len() <= MAX_LEAF, "leaf of {} bytes", text.len());
+                assert_eq!(*info, Info::of(text));
+                (0, *info)
+            }
+            Node::Branch {
+                left,
+                right,
+                info,
+                height,
+            } => {
+                let (left_height, left_info) = check(left);
+                let (right_height, right_info) = check(right);
+                assert!(
+                    left_height.abs_diff(right_height) <= 1,
+                    "unbalanced: heights {} and {}",
+                    left_height,
+                    right_height
+                );
+                assert_eq!(*height, left_height.max(right_height) + 1);
+                assert_eq!(*info, left_info.add(right_info));
+                (*height, *info)
+            }
+        }
+    }
+
+    fn check_rope(rope: &Rope, oracle: &[char]) {
+        if let Some(root) = &rope.root {
+            let (height, _) = check(root);
+            // An AVL tree with n leaves is at most about 1.44 log2(n) high.
+            let leaves = rope.chunks().count();
+            assert!(height as f64 <= 1.45 * ((leaves + 2) as f64).log2());
+        }
+        let text: String = oracle.iter().collect();
+        assert_eq!(rope.to_string(), text);
+        assert_eq!(rope.len_chars(), oracle.len());
+        assert_eq!(rope.len_bytes(), text.len());
+        assert_eq!(rope.is_empty(), oracle.is_empty());
+        assert_eq!(rope.len_lines(), text.lines().count());
+    }
+
+    const PIECES: [&str; 8] = ["a", "bc", "\n", "\r\n", "é", "한", "🦀", " "];
+
+    fn random_text(rng: &mut Rng) -> String {
+        // Mostly short edits that take the leaf fast path, sometimes more than a leaf holds.
+        let pieces = match rng.below(20) {
+            0 => 300 + rng.below(600),
+            _ => rng.below(20),
+        };
+        (0..pieces).map(|_| PIECES[rng.below(PIECES.len())]).collect()
+    }
+
+    #[test]
+    fn random_edits_match_string() {
+        for seed in 1..=6u64 {
+            let mut rng = Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+            let mut rope = Rope::new();
+            let mut oracle: Vec<char> = Vec::new();
+
+            for _ in 0..600 {
+                let len = oracle.len();
+                match rng.below(10) {
+                    0..=3 => {
+                        let at = rng.below(len + 1);
+                        let text = random_text(&mut rng);
+                        rope.insert(at, &text);
+                        oracle.splice(at..at, text.chars());
+                    }
+                    4..=6 => {
+                        let end = rng.below(len + 1);
+                        let start = end - rng.below(end.min(if len > 5000 { 2000 } else { 50 }) + 1);
+                        rope.remove(start..end);
+                        oracle.drain(start..end);
+                    }
+                    7 => {
+                        let at = rng.below(len + 1);
+                        let tail = rope.split_off(at);
+                        let expected: Vec<char> = oracle[at..].to_vec();
+                        check_rope(&rope, &oracle[..at]);
+                        check_rope(&tail, &expected);
+                        rope.append(tail);
+                    }
+                    8 => {
+                        let end = rng.below(len + 1);
+                        let start = rng.below(end + 1);
+                        let expected: String = oracle[start..end].iter().collect();
+                        assert_eq!(rope.slice(start..end), expected);
+                        let index = rng.below(len + 2);
+                        assert_eq!(rope.char_at(index), oracle.get(index).copied());
+                    }
+                    _ => {
+                        let text: String = oracle.iter().collect();
+                        let lines: Vec<&str> = text.lines().collect();
+                        let line = rng.below(lines.len() + 2);
+                        assert_eq!(rope.line(line).as_deref(), lines.get(line).copied());
+
+                        let index = rng.below(len + 2);
+                        let newlines = oracle[..index.min(len)].iter().filter(|&&c| c == '\n').count();
+                        assert_eq!(rope.char_to_line(index), newlines);
+
+                        let start = match line {
+                            0 if !lines.is_empty() => Some(0),
+                            _ if line < lines.len() => oracle
+                                .iter()
+                                .enumerate()
+                                .filter(|&(_, &c)| c == '\n')
+                                .nth(line - 1)
+                                .map(|(i, _)| i + 1),
+                            _ => None,
+                        };
+                        assert_eq!(rope.line_to_char(line), start);
+                    }
+                }
+                check_rope(&rope, &oracle);
+            }
+
+            let text: String = oracle.iter().collect();
+            assert_eq!(rope.lines().collect::<Vec<_>>(), text.lines().collect::<Vec<_>>());
+            assert!(rope.chars().eq(oracle.iter().copied()));
+        }
+    }
+
+    #[test]
+    fn appending_ropes_of_very_different_heights_stays_balanced() {
+        let mut rope = Rope::new();
+        let mut oracle = String::new();
+        for i in 0..60 {
+            let piece = "x".repeat(MAX_LEAF * (i % 7 + 1) + i);
+            let mut other = Rope::from(piece.as_str());
+            if i % 3 == 0 {
+                other.append(rope);
+                rope = other;
+                oracle.insert_str(0, &piece);
+            } else {
+                rope.append(other);
+                oracle.push_str(&piece);
+            }
+            check(rope.root.as_ref().unwrap());
+        }
+        assert_eq!(rope.to_string(), oracle);
+    }
+}
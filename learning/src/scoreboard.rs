@@ -0,0 +1,422 @@
+// Team scores from chapter 8.3, grown into a scoreboard.
+//
+// The chapter keeps a HashMap<String, i32> and either overwrites a score with insert or keeps the
+// first one with entry().or_insert(50). Scoreboard makes that choice an explicit Policy, keeps
+// every change with the time it happened so each team's history can be shown and changes can be
+// undone, and saves itself as JSON.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::grapheme;
+use crate::json::Json;
+
+/// How a new score combines with the one a team already has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    /// The new score replaces the old one, like HashMap::insert.
+    Overwrite,
+    /// Only a score higher than the team's best changes it.
+    KeepMax,
+    /// Scores are points added to the team's total.
+    Accumulate,
+}
+
+impl fmt::Display for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Policy::Overwrite => "overwrite",
+            Policy::KeepMax => "keep-max",
+            Policy::Accumulate => "accumulate",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownPolicy(pub String);
+
+impl fmt::Display for UnknownPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown policy '{}' (try overwrite, keep-max or accumulate)", self.0)
+    }
+}
+
+impl std::error::Error for UnknownPolicy {}
+
+impl FromStr for Policy {
+    type Err = UnknownPolicy;
+
+    fn from_str(name: &str) -> Result<Policy, UnknownPolicy> {
+        match name {
+            "overwrite" => Ok(Policy::Overwrite),
+            "keep-max" => Ok(Policy::KeepMax),
+            "accumulate" => Ok(Policy::Accumulate),
+            _ => Err(UnknownPolicy(String::from(name))),
+        }
+    }
+}
+
+/// One call to `record`: the points given and the team's score afterwards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub team: String,
+    pub points: i32,
+    pub score: i32,
+    pub at: SystemTime,
+}
+
+/// A row of the leaderboard. Tied teams share a rank.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Standing<'a> {
+    pub rank: usize,
+    pub team: &'a str,
+    pub score: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scoreboard {
+    policy: Policy,
+    scores: HashMap<String, i32>,
+    // Oldest first. The scores map is what replaying these would give.
+    changes: Vec<Change>,
+}
+
+impl Scoreboard {
+    pub fn new(policy: Policy) -> Scoreboard {
+        Scoreboard {
+            policy,
+            scores: HashMap::new(),
+            changes: Vec::new(),
+        }
+    }
+
+    pub fn policy(&self) -> Policy {
+        self.policy
+    }
+
+    /// Records a score for `team` now and returns the team's score afterwards. A team's first
+    /// score is taken as it is under every policy.
+    ///
+    /// Every call is kept in the history, including a KeepMax score that didn't beat the best.
+    pub fn record(&mut self, team: &str, points: i32) -> i32 {
+        self.record_at(team, points, SystemTime::now())
+    }
+
+    /// `record` with the time given, for replaying scores from elsewhere.
+    pub fn record_at(&mut self, team: &str, points: i32, at: SystemTime) -> i32 {
+        let score = match (self.policy, self.scores.get(team)) {
+            (_, None) | (Policy::Overwrite, _) => points,
+            (Policy::KeepMax, Some(&old)) => old.max(points),
+            (Policy::Accumulate, Some(&old)) => old.saturating_add(points),
+        };
+        self.scores.insert(String::from(team), score);
+        self.changes.push(Change {
+            team: String::from(team),
+            points,
+            score,
+            at,
+        });
+        score
+    }
+
+    /// Gives `team` a starting score if it doesn't have one yet, and returns its score either way,
+    /// like entry().or_insert. A team that already has a score is left alone and nothing is
+    /// recorded.
+    pub fn join(&mut self, team: &str, score: i32) -> i32 {
+        match self.scores.get(team) {
+            Some(&current) => current,
+            None => self.record(team, score),
+        }
+    }
+
+    pub fn score(&self, team: &str) -> Option<i32> {
+        self.scores.get(team).copied()
+    }
+
+    /// The number of teams.
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    /// Every change for every team, oldest first.
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    /// One team's changes, oldest first.
+    pub fn history<'a>(&'a self, team: &'a str) -> impl Iterator<Item = &'a Change> {
+        self.changes.iter().filter(move |change| change.team == team)
+    }
+
+    /// Takes back the latest change and returns it, or None if there's nothing left to undo.
+    /// Calling it again steps further back. A team whose only change is undone is removed.
+    pub fn undo(&mut self) -> Option<Change> {
+        let change = self.changes.pop()?;
+        match self.changes.iter().rev().find(|earlier| earlier.team == change.team) {
+            Some(earlier) => {
+                self.scores.insert(change.team.clone(), earlier.score);
+            }
+            None => {
+                self.scores.remove(&change.team);
+            }
+        }
+        Some(change)
+    }
+
+    /// Highest score first. Tied teams share a rank, listed by name, and the ranks they use up
+    /// are skipped, so two teams tied for first are followed by third.
+    pub fn leaderboard(&self) -> Vec<Standing<'_>> {
+        let mut sorted: Vec<(&str, i32)> = self.scores.iter().map(|(team, &score)| (team.as_str(), score)).collect();
+        sorted.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+        let mut standings: Vec<Standing> = Vec::with_capacity(sorted.len());
+        for (i, (team, score)) in sorted.into_iter().enumerate() {
+            let rank = match standings.last() {
+                Some(previous) if previous.score == score => previous.rank,
+                _ => i + 1,
+            };
+            standings.push(Standing { rank, team, score });
+        }
+        standings
+    }
+
+    /// The policy and every change, with times in milliseconds since the Unix epoch:
+    ///
+    /// ```text
+    /// {"policy": "accumulate", "changes": [{"team": "Blue", "points": 10, "score": 10, "at": 1760000000000}]}
+    /// ```
+    pub fn to_json(&self) -> Json {
+        let changes = self
+            .changes
+            .iter()
+            .map(|change| {
+                let millis = change.at.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis());
+                Json::Object(vec![
+                    (String::from("team"), Json::from(change.team.as_str())),
+                    (String::from("points"), Json::from(change.points)),
+                    (String::from("score"), Json::from(change.score)),
+                    (String::from("at"), Json::Number(millis as f64)),
+                ])
+            })
+            .collect();
+        Json::Object(vec![
+            (String::from("policy"), Json::from(self.policy.to_string())),
+            (String::from("changes"), Json::Array(changes)),
+        ])
+    }
+
+    /// Reads what `to_json` wrote. The error names the first thing that was missing or wrong.
+    pub fn from_json(json: &Json) -> Result<Scoreboard, String> {
+        let policy = json
+            .get("policy")
+            .and_then(Json::as_str)
+            .ok_or("missing \"policy\"")?
+            .parse::<Policy>()
+            .map_err(|e| e.to_string())?;
+        let changes = json.get("changes").and_then(Json::as_array).ok_or("missing \"changes\" list")?;
+
+        let mut scoreboard = Scoreboard::new(policy);
+        for (i, change) in changes.iter().enumerate() {
+            let team = change.get("team").and_then(Json::as_str);
+            let int = |field: &str| change.get(field).and_then(Json::as_i64);
+            let (team, points, score, millis) = match (team, int("points"), int("score"), int("at")) {
+                (Some(team), Some(points), Some(score), Some(millis)) => (team, points, score, millis),
+                _ => return Err(format!("change {} needs a team, points, score and at", i + 1)),
+            };
+            let (points, score, millis) = match (i32::try_from(points), i32::try_from(score), u64::try_from(millis)) {
+                (Ok(points), Ok(score), Ok(millis)) => (points, score, millis),
+                _ => return Err(format!("change {} has a number out of range", i + 1)),
+            };
+            // Kept as saved rather than recomputed, so the file is the record of what happened.
+            scoreboard.scores.insert(String::from(team), score);
+            scoreboard.changes.push(Change {
+                team: String::from(team),
+                points,
+                score,
+                at: UNIX_EPOCH + Duration::from_millis(millis),
+            });
+        }
+        Ok(scoreboard)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut text = self.to_json().to_pretty_string();
+        text.push('\n');
+        fs::write(path, text)
+    }
+
+    /// A file that isn't a saved scoreboard is an `InvalidData` error.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Scoreboard> {
+        let text = fs::read_to_string(path)?;
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        let json = Json::parse(&text).map_err(|e| invalid(e.to_string()))?;
+        Scoreboard::from_json(&json).map_err(invalid)
+    }
+}
+
+/// The leaderboard as aligned text, one team per line, with '=' marking shared ranks.
+impl fmt::Display for Scoreboard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let standings = self.leaderboard();
+        let rank_width = standings.last().map_or(1, |last| last.rank.to_string().len());
+        let team_width = standings.iter().map(|s| grapheme::width(s.team)).max().unwrap_or(0);
+        let score_width = standings.iter().map(|s| s.score.to_string().len()).max().unwrap_or(0);
+        for standing in &standings {
+            let tied = standings.iter().filter(|other| other.rank == standing.rank).count() > 1;
+            writeln!(
+                f,
+                "{}{:>rank_width$}. {}  {:>score_width$}",
+                if tied { '=' } else { ' ' },
+                standing.rank,
+                grapheme::pad_right(standing.team, team_width),
+                standing.score,
+                rank_width = rank_width,
+                score_width = score_width
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(millis: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(millis)
+    }
+
+    fn scores_after(policy: Policy, points: &[i32]) -> Vec<i32> {
+        let mut scoreboard = Scoreboard::new(policy);
+        points.iter().map(|&p| scoreboard.record("Blue", p)).collect()
+    }
+
+    #[test]
+    fn policies() {
+        assert_eq!(scores_after(Policy::Overwrite, &[10, 50, 20]), [10, 50, 20]);
+        assert_eq!(scores_after(Policy::KeepMax, &[10, 50, 20]), [10, 50, 50]);
+        assert_eq!(scores_after(Policy::KeepMax, &[-5, -9]), [-5, -5]);
+        assert_eq!(scores_after(Policy::Accumulate, &[10, 50, -20]), [10, 60, 40]);
+        assert_eq!(scores_after(Policy::Accumulate, &[i32::MAX, 1]), [i32::MAX, i32::MAX]);
+
+        for policy in [Policy::Overwrite, Policy::KeepMax, Policy::Accumulate] {
+            assert_eq!(policy.to_string().parse(), Ok(policy));
+        }
+        assert_eq!(
+            "max".parse::<Policy>().unwrap_err().to_string(),
+            "unknown policy 'max' (try overwrite, keep-max or accumulate)"
+        );
+    }
+
+    #[test]
+    fn join_only_sets_a_missing_score() {
+        let mut scoreboard = Scoreboard::new(Policy::Overwrite);
+        assert_eq!(scoreboard.join("Blue", 50), 50);
+        assert_eq!(scoreboard.join("Blue", 10), 50);
+        assert_eq!(scoreboard.changes().len(), 1);
+    }
+
+    #[test]
+    fn undo_steps_back_to_removal() {
+        let mut scoreboard = Scoreboard::new(Policy::Accumulate);
+        scoreboard.record_at("Blue", 10, at(1));
+        scoreboard.record_at("Yellow", 5, at(2));
+        scoreboard.record_at("Blue", 7, at(3));
+        assert_eq!(scoreboard.history("Blue").map(|c| c.score).collect::<Vec<_>>(), [10, 17]);
+
+        assert_eq!(scoreboard.undo().map(|c| (c.team, c.points)), Some((String::from("Blue"), 7)));
+        assert_eq!(scoreboard.score("Blue"), Some(10));
+        assert_eq!(scoreboard.undo().map(|c| c.team), Some(String::from("Yellow")));
+        assert_eq!(scoreboard.score("Yellow"), None);
+        assert_eq!(scoreboard.undo().map(|c| c.at), Some(at(1)));
+        assert_eq!(scoreboard.score("Blue"), None);
+        assert!(scoreboard.is_empty());
+        assert_eq!(scoreboard.undo(), None);
+        assert_eq!(scoreboard, Scoreboard::new(Policy::Accumulate));
+    }
+
+    #[test]
+    fn tied_teams_share_a_rank() {
+        let mut scoreboard = Scoreboard::new(Policy::Overwrite);
+        for (team, score) in [("Yellow", 30), ("Blue", 50), ("Red", 50), ("Green", 10), ("Grey", 10)] {
+            scoreboard.record(team, score);
+        }
+        let standings: Vec<_> = scoreboard.leaderboard().iter().map(|s| (s.rank, s.team, s.score)).collect();
+        assert_eq!(
+            standings,
+            [(1, "Blue", 50), (1, "Red", 50), (3, "Yellow", 30), (4, "Green", 10), (4, "Grey", 10)]
+        );
+        assert_eq!(
+            scoreboard.to_string(),
+            "=1. Blue    50\n=1. Red     50\n 3. Yellow  30\n=4. Green   10\n=4. Grey    10\n"
+        );
+        assert_eq!(Scoreboard::new(Policy::Overwrite).to_string(), "");
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let mut scoreboard = Scoreboard::new(Policy::KeepMax);
+        scoreboard.record_at("Blue", 10, at(1_760_000_000_000));
+        scoreboard.record_at("Blue", 5, at(1_760_000_000_001));
+        scoreboard.record_at("Yellow", -3, at(0));
+        let json = scoreboard.to_json();
+        assert_eq!(Scoreboard::from_json(&json), Ok(scoreboard.clone()));
+        assert_eq!(Scoreboard::from_json(&Json::parse(&json.to_pretty_string()).unwrap()), Ok(scoreboard));
+    }
+
+    #[test]
+    fn from_json_errors() {
+        let error = |text: &str| Scoreboard::from_json(&Json::parse(text).unwrap()).unwrap_err();
+        assert_eq!(error(r#"{"changes": []}"#), "missing \"policy\"");
+        assert_eq!(error(r#"{"policy": 1, "changes": []}"#), "missing \"policy\"");
+        assert_eq!(
+            error(r#"{"policy": "best", "changes": []}"#),
+            "unknown policy 'best' (try overwrite, keep-max or accumulate)"
+        );
+        assert_eq!(error(r#"{"policy": "overwrite"}"#), "missing \"changes\" list");
+        assert_eq!(error(r#"{"policy": "overwrite", "changes": {}}"#), "missing \"changes\" list");
+        let change = |fields: &str| format!(r#"{{"policy": "overwrite", "changes": [{{"team": "Blue", "points": 1, "score": 1, "at": 0}}, {{{}}}]}}"#, fields);
+        assert_eq!(
+            error(&change(r#""team": "Red", "points": 1, "score": 1"#)),
+            "change 2 needs a team, points, score and at"
+        );
+        assert_eq!(
+            error(&change(r#""team": "Red", "points": 1.5, "score": 1, "at": 0"#)),
+            "change 2 needs a team, points, score and at"
+        );
+        assert_eq!(
+            error(&change(r#""team": "Red", "points": 3000000000, "score": 1, "at": 0"#)),
+            "change 2 has a number out of range"
+        );
+        assert_eq!(
+            error(&change(r#""team": "Red", "points": 1, "score": 1, "at": -1"#)),
+            "change 2 has a number out of range"
+        );
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir().join(format!("scoreboard-test-{}.json", std::process::id()));
+        let mut scoreboard = Scoreboard::new(Policy::Accumulate);
+        scoreboard.record_at("Blue", 10, at(1_000));
+        scoreboard.save(&path).unwrap();
+        assert_eq!(Scoreboard::load(&path).unwrap(), scoreboard);
+
+        for text in [String::from("{\"policy\": "), String::from("[]"), "[".repeat(200_000)] {
+            fs::write(&path, text).unwrap();
+            assert_eq!(Scoreboard::load(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        }
+        fs::remove_file(&path).unwrap();
+        assert_eq!(Scoreboard::load(&path).unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,792 @@
+// An inverted index over many documents, grown from chapter 8.3's word counts.
+//
+// word_counts answers "how often is each word in this one text". An index turns that around: for
+// each term it keeps the documents the term is in and the positions it is at, so a query only
+// looks at documents that can match and phrases can be checked without the original text.
+// Terms are words::tokens folded with words::fold_case. Stop words stay in, so a phrase like
+// "to be or not to be" can still be found.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use crate::words;
+
+pub type DocId = usize;
+
+/// Where one term appears in one document. Positions count words from 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Posting {
+    pub doc: DocId,
+    pub positions: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Document {
+    name: String,
+    // In words, for BM25's length normalization.
+    length: usize,
+}
+
+/// One search result. Higher scores are better matches.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit<'a> {
+    pub doc: DocId,
+    pub name: &'a str,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Index {
+    documents: BTreeMap<DocId, Document>,
+    // Each list is sorted by document id.
+    postings: HashMap<String, Vec<Posting>>,
+    next_id: DocId,
+    total_length: usize,
+}
+
+impl Index {
+    pub fn new() -> Index {
+        Index::default()
+    }
+
+    /// Indexes `text` under a new id. The name is what results show; it doesn't have to be unique.
+    pub fn add(&mut self, name: &str, text: &str) -> DocId {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let mut terms: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut length = 0;
+        for (position, token) in words::tokens(text).enumerate() {
+            terms.entry(words::fold_case(token)).or_default().push(position);
+            length = position + 1;
+        }
+        for (term, positions) in terms {
+            // Ids only go up, so pushing keeps every list sorted.
+            self.postings.entry(term).or_default().push(Posting { doc: id, positions });
+        }
+
+        self.documents.insert(id, Document { name: String::from(name), length });
+        self.total_length += length;
+        id
+    }
+
+    /// Returns false if there was no document with that id. The index doesn't keep a list of each
+    /// document's terms, so this looks through every term.
+    pub fn remove(&mut self, id: DocId) -> bool {
+        let document = match self.documents.remove(&id) {
+            Some(document) => document,
+            None => return false,
+        };
+        self.total_length -= document.length;
+        self.postings.retain(|_, list| {
+            if let Ok(i) = list.binary_search_by_key(&id, |posting| posting.doc) {
+                list.remove(i);
+            }
+            !list.is_empty()
+        });
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    pub fn name(&self, id: DocId) -> Option<&str> {
+        self.documents.get(&id).map(|document| document.name.as_str())
+    }
+
+    /// Ids and names in id order, which is the order they were added.
+    pub fn documents(&self) -> impl Iterator<Item = (DocId, &str)> {
+        self.documents.iter().map(|(&id, document)| (id, document.name.as_str()))
+    }
+
+    /// How many distinct terms are indexed.
+    pub fn term_count(&self) -> usize {
+        self.postings.len()
+    }
+
+    /// The documents containing `term`, in id order. The term is case folded first.
+    pub fn postings(&self, term: &str) -> &[Posting] {
+        self.list(&words::fold_case(term))
+    }
+
+    fn list(&self, term: &str) -> &[Posting] {
+        self.postings.get(term).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    fn posting(&self, term: &str, doc: DocId) -> Option<&Posting> {
+        let list = self.list(term);
+        list.binary_search_by_key(&doc, |posting| posting.doc).ok().map(|i| &list[i])
+    }
+
+    /// Parses `query` and returns the matching documents, best first.
+    pub fn search(&self, query: &str) -> Result<Vec<Hit<'_>>, QueryError> {
+        Ok(self.rank(&Query::parse(query)?))
+    }
+
+    /// The documents that match, unranked.
+    pub fn matches(&self, query: &Query) -> BTreeSet<DocId> {
+        match query {
+            Query::Term(term) => self.list(term).iter().map(|posting| posting.doc).collect(),
+            Query::Phrase(terms) => self.phrase_matches(terms),
+            Query::And(parts) => {
+                let mut parts = parts.iter().map(|part| self.matches(part));
+                let first = parts.next().unwrap_or_default();
+                parts.fold(first, |docs, part| &docs & &part)
+            }
+            Query::Or(parts) => parts.iter().flat_map(|part| self.matches(part)).collect(),
+            Query::Not(inner) => {
+                let excluded = self.matches(inner);
+                self.documents.keys().filter(|id| !excluded.contains(id)).copied().collect()
+            }
+        }
+    }
+
+    // Documents with every term at consecutive positions.
+    fn phrase_matches(&self, terms: &[String]) -> BTreeSet<DocId> {
+        let mut docs = BTreeSet::new();
+        let (first, rest) = match terms.split_first() {
+            Some(split) => split,
+            None => return docs,
+        };
+        for posting in self.list(first) {
+            let others: Option<Vec<&Posting>> = rest.iter().map(|term| self.posting(term, posting.doc)).collect();
+            let others = match others {
+                Some(others) => others,
+                None => continue,
+            };
+            let found = posting.positions.iter().any(|&start| {
+                others
+                    .iter()
+                    .enumerate()
+                    .all(|(i, other)| other.positions.binary_search(&(start + i + 1)).is_ok())
+            });
+            if found {
+                docs.insert(posting.doc);
+            }
+        }
+        docs
+    }
+
+    /// Scores each match with BM25 over the query's terms, leaving out terms under NOT since a
+    /// match doesn't have them. Equal scores come out in id order.
+    pub fn rank(&self, query: &Query) -> Vec<Hit<'_>> {
+        let mut terms = Vec::new();
+        query.scored_terms(&mut terms);
+        terms.sort_unstable();
+        terms.dedup();
+
+        let mut hits: Vec<Hit> = self
+            .matches(query)
+            .into_iter()
+            .map(|doc| Hit {
+                doc,
+                name: &self.documents[&doc].name,
+                score: terms.iter().fold(0.0, |score, term| score + self.bm25(term, doc)),
+            })
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.doc.cmp(&b.doc)));
+        hits
+    }
+
+    // BM25 with the usual k1 = 1.2 and b = 0.75. The idf is the form with a 1 inside the log, so
+    // terms in more than half the documents still count a little instead of going negative.
+    fn bm25(&self, term: &str, doc: DocId) -> f64 {
+        const K1: f64 = 1.2;
+        const B: f64 = 0.75;
+
+        let posting = match self.posting(term, doc) {
+            Some(posting) => posting,
+            None => return 0.0,
+        };
+        let documents = self.documents.len() as f64;
+        let containing = self.list(term).len() as f64;
+        let idf = (1.0 + (documents - containing + 0.5) / (containing + 0.5)).ln();
+
+        let frequency = posting.positions.len() as f64;
+        let length = self.documents[&doc].length as f64;
+        let average = (self.total_length as f64 / documents).max(1.0);
+        idf * frequency * (K1 + 1.0) / (frequency + K1 * (1.0 - B + B * length / average))
+    }
+
+    /// Writes the index as lines of text: a header, the next id to hand out, one `doc` line per
+    /// document and one `term` line per term, e.g.
+    ///
+    /// ```text
+    /// search-index 1
+    /// next 2
+    /// doc 0 5 notes.txt
+    /// doc 1 9 todo.txt
+    /// term borrow 0:3 1:0,6
+    /// ```
+    ///
+    /// `doc` lines hold the id, the length in words and the name, with backslashes and line
+    /// breaks escaped. Terms are sorted so the same index always writes the same file.
+    pub fn write_to<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "{}", HEADER)?;
+        writeln!(out, "next {}", self.next_id)?;
+        for (id, document) in &self.documents {
+            writeln!(out, "doc {} {} {}", id, document.length, escape(&document.name))?;
+        }
+
+        let mut terms: Vec<&String> = self.postings.keys().collect();
+        terms.sort_unstable();
+        for term in terms {
+            write!(out, "term {}", term)?;
+            for posting in &self.postings[term] {
+                write!(out, " {}:", posting.doc)?;
+                for (i, position) in posting.positions.iter().enumerate() {
+                    if i > 0 {
+                        write!(out, ",")?;
+                    }
+                    write!(out, "{}", position)?;
+                }
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    /// Reads what `write_to` wrote. A malformed file is an `InvalidData` error naming the line.
+    pub fn read_from<R: BufRead>(reader: R) -> io::Result<Index> {
+        let mut lines = reader.lines();
+        match lines.next().transpose()? {
+            Some(line) if line == HEADER => {}
+            _ => return Err(invalid_data(1, String::from("not a search index"))),
+        }
+
+        let mut index = Index::new();
+        for (i, line) in lines.enumerate() {
+            index.read_line(&line?).map_err(|message| invalid_data(i + 2, message))?;
+        }
+        Ok(index)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = io::BufWriter::new(fs::File::create(path)?);
+        self.write_to(&mut out)?;
+        out.flush()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Index> {
+        Index::read_from(io::BufReader::new(fs::File::open(path)?))
+    }
+
+    fn read_line(&mut self, line: &str) -> Result<(), String> {
+        let (kind, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match kind {
+            "next" => self.next_id = number(rest)?,
+            "doc" => {
+                let mut fields = rest.splitn(3, ' ');
+                let id = number(fields.next().unwrap_or(""))?;
+                let length = number(fields.next().unwrap_or(""))?;
+                let name = unescape(fields.next().unwrap_or(""))?;
+                if id >= self.next_id {
+                    return Err(format!("document {} isn't below the next id {}", id, self.next_id));
+                }
+                if self.documents.insert(id, Document { name, length }).is_some() {
+                    return Err(format!("document {} appears twice", id));
+                }
+                self.total_length += length;
+            }
+            "term" => {
+                let mut fields = rest.split(' ');
+                let term = fields.next().unwrap_or("");
+                if term.is_empty() {
+                    return Err(String::from("term line without a term"));
+                }
+                let mut list: Vec<Posting> = Vec::new();
+                for field in fields {
+                    let (doc, positions) = field
+                        .split_once(':')
+                        .ok_or_else(|| format!("expected doc:positions, found '{}'", field))?;
+                    let doc = number(doc)?;
+                    if !self.documents.contains_key(&doc) {
+                        return Err(format!("'{}' refers to unknown document {}", term, doc));
+                    }
+                    if list.last().is_some_and(|last| last.doc >= doc) {
+                        return Err(format!("documents for '{}' are out of order", term));
+                    }
+                    let positions = positions.split(',').map(number).collect::<Result<Vec<usize>, String>>()?;
+                    if positions.windows(2).any(|pair| pair[0] >= pair[1]) {
+                        return Err(format!("positions for '{}' are out of order", term));
+                    }
+                    list.push(Posting { doc, positions });
+                }
+                if list.is_empty() {
+                    return Err(format!("'{}' has no documents", term));
+                }
+                self.postings.insert(String::from(term), list);
+            }
+            _ => return Err(format!("expected 'next', 'doc' or 'term', found '{}'", kind)),
+        }
+        Ok(())
+    }
+}
+
+const HEADER: &str = "search-index 1";
+
+fn number(text: &str) -> Result<usize, String> {
+    text.parse().map_err(|_| format!("'{}' is not a number", text))
+}
+
+fn invalid_data(line: usize, message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", line, message))
+}
+
+fn escape(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for c in name.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape(name: &str) -> Result<String, String> {
+    let mut unescaped = String::with_capacity(name.len());
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => unescaped.push('\\'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            _ => return Err(format!("bad escape in name '{}'", name)),
+        }
+    }
+    Ok(unescaped)
+}
+
+/// A parsed search.
+///
+/// The text form has words, "quoted phrases", AND, OR, NOT and parentheses. Words side by side
+/// must all match, as if joined with AND, and AND binds tighter than OR, so
+/// `borrow checker OR lifetimes` means `(borrow AND checker) OR lifetimes`. The operators have
+/// to be upper case; a lower case "and" is just a word.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Query {
+    /// A single case-folded term.
+    Term(String),
+    /// Terms that must appear one right after another.
+    Phrase(Vec<String>),
+    And(Vec<Query>),
+    Or(Vec<Query>),
+    Not(Box<Query>),
+}
+
+impl Query {
+    pub fn parse(query: &str) -> Result<Query, QueryError> {
+        let mut parser = Parser { lexemes: lex(query)?, next: 0 };
+        let parsed = parser.or()?;
+        match parser.peek() {
+            None => Ok(parsed),
+            Some(&(_, column)) => Err(QueryError { column, kind: ErrorKind::UnmatchedParen }),
+        }
+    }
+
+    // Everything that has to be present for a match to count towards its score.
+    fn scored_terms<'a>(&'a self, terms: &mut Vec<&'a str>) {
+        match self {
+            Query::Term(term) => terms.push(term),
+            Query::Phrase(phrase) => terms.extend(phrase.iter().map(String::as_str)),
+            Query::And(parts) | Query::Or(parts) => {
+                for part in parts {
+                    part.scored_terms(terms);
+                }
+            }
+            Query::Not(_) => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    Empty,
+    UnclosedQuote,
+    UnclosedParen,
+    UnmatchedParen,
+    EmptyParens,
+    /// AND, OR or NOT with nothing to apply to, as in `rust AND` or `OR rust`.
+    MissingOperand(&'static str),
+    /// A word or phrase with no letters or digits, such as `"..."`.
+    NoWords(String),
+}
+
+/// The column is 1-based, counts chars, and points at the part of the query involved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError {
+    pub column: usize,
+    pub kind: ErrorKind,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "column {}: ", self.column)?;
+        match &self.kind {
+            ErrorKind::Empty => write!(f, "nothing to search for"),
+            ErrorKind::UnclosedQuote => write!(f, "'\"' is never closed"),
+            ErrorKind::UnclosedParen => write!(f, "'(' is never closed"),
+            ErrorKind::UnmatchedParen => write!(f, "')' doesn't match an open '('"),
+            ErrorKind::EmptyParens => write!(f, "nothing between '(' and ')'"),
+            ErrorKind::MissingOperand("NOT") => write!(f, "NOT needs something to search for after it"),
+            ErrorKind::MissingOperand(operator) => write!(f, "{} needs something to search for on both sides", operator),
+            ErrorKind::NoWords(text) => write!(f, "'{}' has no words in it", text),
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Lexeme {
+    Open,
+    Close,
+    Quoted(String),
+    Word(String),
+}
+
+fn lex(query: &str) -> Result<Vec<(Lexeme, usize)>, QueryError> {
+    let mut lexemes = Vec::new();
+    let mut chars = query.chars().zip(1..).peekable();
+
+    while let Some((c, column)) = chars.next() {
+        let lexeme = match c {
+            _ if c.is_whitespace() => continue,
+            '(' => Lexeme::Open,
+            ')' => Lexeme::Close,
+            '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some(('"', _)) => break,
+                        Some((c, _)) => text.push(c),
+                        None => return Err(QueryError { column, kind: ErrorKind::UnclosedQuote }),
+                    }
+                }
+                Lexeme::Quoted(text)
+            }
+            _ => {
+                let mut word = String::from(c);
+                while let Some(&(c, _)) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '(' | ')' | '"') {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                Lexeme::Word(word)
+            }
+        };
+        lexemes.push((lexeme, column));
+    }
+    Ok(lexemes)
+}
+
+// Recursive descent, one function per precedence level:
+//
+//     or      = and ("OR" and)*
+//     and     = unary (["AND"] unary)*
+//     unary   = "NOT" unary | primary
+//     primary = word | quoted | "(" or ")"
+struct Parser {
+    lexemes: Vec<(Lexeme, usize)>,
+    next: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&(Lexeme, usize)> {
+        self.lexemes.get(self.next)
+    }
+
+    fn eat_operator(&mut self, operator: &str) -> Option<usize> {
+        match self.peek() {
+            Some((Lexeme::Word(word), column)) if word == operator => {
+                let column = *column;
+                self.next += 1;
+                Some(column)
+            }
+            _ => None,
+        }
+    }
+
+    fn starts_operand(&self) -> bool {
+        match self.peek() {
+            Some((Lexeme::Word(word), _)) => word != "AND" && word != "OR",
+            Some((Lexeme::Open, _)) | Some((Lexeme::Quoted(_), _)) => true,
+            Some((Lexeme::Close, _)) | None => false,
+        }
+    }
+
+    // Called where an operand was needed but the next lexeme can't start one.
+    fn unexpected(&self) -> QueryError {
+        match self.peek() {
+            Some((Lexeme::Word(word), column)) => {
+                let operator = if word == "AND" { "AND" } else { "OR" };
+                QueryError { column: *column, kind: ErrorKind::MissingOperand(operator) }
+            }
+            Some((_, column)) => QueryError { column: *column, kind: ErrorKind::UnmatchedParen },
+            None => QueryError { column: 1, kind: ErrorKind::Empty },
+        }
+    }
+
+    fn operand(&mut self, operator: &'static str, column: usize) -> Result<Query, QueryError> {
+        if !self.starts_operand() {
+            return Err(QueryError { column, kind: ErrorKind::MissingOperand(operator) });
+        }
+        self.unary()
+    }
+
+    fn or(&mut self) -> Result<Query, QueryError> {
+        let mut parts = vec![self.and()?];
+        while let Some(column) = self.eat_operator("OR") {
+            if !self.starts_operand() {
+                return Err(QueryError { column, kind: ErrorKind::MissingOperand("OR") });
+            }
+            parts.push(self.and()?);
+        }
+        Ok(if parts.len() == 1 { parts.remove(0) } else { Query::Or(parts) })
+    }
+
+    fn and(&mut self) -> Result<Query, QueryError> {
+        if !self.starts_operand() {
+            return Err(self.unexpected());
+        }
+        let mut parts = vec![self.unary()?];
+        loop {
+            if let Some(column) = self.eat_operator("AND") {
+                parts.push(self.operand("AND", column)?);
+            } else if self.starts_operand() {
+                parts.push(self.unary()?);
+            } else {
+                break;
+            }
+        }
+        Ok(if parts.len() == 1 { parts.remove(0) } else { Query::And(parts) })
+    }
+
+    fn unary(&mut self) -> Result<Query, QueryError> {
+        match self.eat_operator("NOT") {
+            Some(column) => Ok(Query::Not(Box::new(self.operand("NOT", column)?))),
+            None => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Query, QueryError> {
+        let (lexeme, column) = match self.peek() {
+            Some(next) => next.clone(),
+            None => return Err(self.unexpected()),
+        };
+        self.next += 1;
+        match lexeme {
+            Lexeme::Word(text) | Lexeme::Quoted(text) => {
+                let mut terms: Vec<String> = words::tokens(&text).map(words::fold_case).collect();
+                match terms.len() {
+                    0 => Err(QueryError { column, kind: ErrorKind::NoWords(text) }),
+                    1 => Ok(Query::Term(terms.remove(0))),
+                    _ => Ok(Query::Phrase(terms)),
+                }
+            }
+            Lexeme::Open => {
+                match self.peek() {
+                    None => return Err(QueryError { column, kind: ErrorKind::UnclosedParen }),
+                    Some((Lexeme::Close, _)) => return Err(QueryError { column, kind: ErrorKind::EmptyParens }),
+                    Some(_) => {}
+                }
+                let inner = self.or()?;
+                match self.peek() {
+                    Some((Lexeme::Close, _)) => {
+                        self.next += 1;
+                        Ok(inner)
+                    }
+                    _ => Err(QueryError { column, kind: ErrorKind::UnclosedParen }),
+                }
+            }
+            Lexeme::Close => Err(QueryError { column, kind: ErrorKind::UnmatchedParen }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(text: &str) -> Query {
+        Query::Term(String::from(text))
+    }
+
+    fn error(query: &str) -> (usize, ErrorKind) {
+        let error = Query::parse(query).unwrap_err();
+        (error.column, error.kind)
+    }
+
+    fn sample() -> Index {
+        let mut index = Index::new();
+        index.add("hamlet", "To be, or not to be: that is the question.");
+        index.add("sure", "To be sure, or not.");
+        index.add("trust", "Not to be trusted.");
+        index
+    }
+
+    fn found(index: &Index, query: &str) -> Vec<DocId> {
+        index.matches(&Query::parse(query).unwrap()).into_iter().collect()
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(
+            Query::parse("a b OR c"),
+            Ok(Query::Or(vec![Query::And(vec![term("a"), term("b")]), term("c")]))
+        );
+        assert_eq!(
+            Query::parse("a OR b AND c"),
+            Ok(Query::Or(vec![term("a"), Query::And(vec![term("b"), term("c")])]))
+        );
+        assert_eq!(
+            Query::parse("NOT a b"),
+            Ok(Query::And(vec![Query::Not(Box::new(term("a"))), term("b")]))
+        );
+        assert_eq!(
+            Query::parse("(a OR b) c"),
+            Ok(Query::And(vec![Query::Or(vec![term("a"), term("b")]), term("c")]))
+        );
+        // Lower case operators are words.
+        assert_eq!(Query::parse("a or b"), Ok(Query::And(vec![term("a"), term("or"), term("b")])));
+        assert_eq!(
+            Query::parse("\"Borrow Checker\""),
+            Ok(Query::Phrase(vec![String::from("borrow"), String::from("checker")]))
+        );
+    }
+
+    #[test]
+    fn errors_point_at_their_column() {
+        assert_eq!(error(""), (1, ErrorKind::Empty));
+        assert_eq!(error("   "), (1, ErrorKind::Empty));
+        assert_eq!(error("rust \"borrow"), (6, ErrorKind::UnclosedQuote));
+        assert_eq!(error("a (rust"), (3, ErrorKind::UnclosedParen));
+        assert_eq!(error("rust)"), (5, ErrorKind::UnmatchedParen));
+        assert_eq!(error(") rust"), (1, ErrorKind::UnmatchedParen));
+        assert_eq!(error("a ()"), (3, ErrorKind::EmptyParens));
+        assert_eq!(error("rust AND"), (6, ErrorKind::MissingOperand("AND")));
+        assert_eq!(error("rust AND OR c"), (6, ErrorKind::MissingOperand("AND")));
+        assert_eq!(error("OR rust"), (1, ErrorKind::MissingOperand("OR")));
+        assert_eq!(error("rust OR"), (6, ErrorKind::MissingOperand("OR")));
+        assert_eq!(error("rust NOT"), (6, ErrorKind::MissingOperand("NOT")));
+        assert_eq!(error("rust \"...\""), (6, ErrorKind::NoWords(String::from("..."))));
+        // Columns count chars, not bytes.
+        assert_eq!(error("été )"), (5, ErrorKind::UnmatchedParen));
+        assert_eq!(Query::parse("rust NOT").unwrap_err().to_string(), "column 6: NOT needs something to search for after it");
+    }
+
+    #[test]
+    fn phrases_need_consecutive_words() {
+        let index = sample();
+        assert_eq!(found(&index, "\"to be or not to be\""), [0]);
+        assert_eq!(found(&index, "\"not to be\""), [0, 2]);
+        assert_eq!(found(&index, "\"to be\""), [0, 1, 2]);
+        assert_eq!(found(&index, "\"be to\""), Vec::<DocId>::new());
+        assert_eq!(found(&index, "\"be be\""), Vec::<DocId>::new());
+        assert_eq!(found(&index, "\"TO BE, SURE\""), [1]);
+    }
+
+    #[test]
+    fn boolean_matches_and_ranking() {
+        let index = sample();
+        assert_eq!(found(&index, "not"), [0, 1, 2]);
+        assert_eq!(found(&index, "question OR trusted"), [0, 2]);
+        assert_eq!(found(&index, "be NOT sure"), [0, 2]);
+        assert_eq!(found(&index, "missing"), Vec::<DocId>::new());
+
+        let hits = index.search("question OR be").unwrap();
+        assert_eq!(hits.len(), 3);
+        assert_eq!(hits[0].name, "hamlet");
+        assert!(hits.windows(2).all(|pair| pair[0].score >= pair[1].score));
+    }
+
+    #[test]
+    fn remove_updates_lengths_and_postings() {
+        let mut index = sample();
+        let total = index.total_length;
+        let terms = index.term_count();
+
+        assert!(index.remove(0));
+        assert!(!index.remove(0));
+        assert_eq!(index.total_length, total - 10);
+        assert_eq!(index.len(), 2);
+        assert!(index.postings("question").is_empty());
+        assert_eq!(index.term_count(), terms - 4);
+        let docs: Vec<DocId> = index.postings("be").iter().map(|posting| posting.doc).collect();
+        assert_eq!(docs, [1, 2]);
+        assert_eq!(found(&index, "\"not to be\""), [2]);
+
+        // Ids aren't reused.
+        assert_eq!(index.add("again", "be"), 3);
+        assert!(index.remove(1) && index.remove(2) && index.remove(3));
+        assert_eq!(index.total_length, 0);
+        assert_eq!(index.term_count(), 0);
+    }
+
+    #[test]
+    fn write_and_read_round_trip() {
+        let mut index = sample();
+        index.remove(1);
+        index.add("odd \\ name\r\nhere", "Back slash");
+
+        let mut out = Vec::new();
+        index.write_to(&mut out).unwrap();
+        let text = String::from_utf8(out.clone()).unwrap();
+        assert!(text.starts_with("search-index 1\nnext 4\ndoc 0 10 hamlet\n"));
+        assert!(text.contains("doc 3 2 odd \\\\ name\\r\\nhere\n"));
+        assert!(text.contains("\nterm be 0:1,5 2:2\n"));
+
+        let read = Index::read_from(out.as_slice()).unwrap();
+        assert_eq!(read, index);
+        let mut again = Vec::new();
+        read.write_to(&mut again).unwrap();
+        assert_eq!(again, out);
+
+        let path = std::env::temp_dir().join(format!("search-index-{}", std::process::id()));
+        index.save(&path).unwrap();
+        assert_eq!(Index::load(&path).unwrap(), index);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn read_rejects_bad_files() {
+        let read = |text: &str| Index::read_from(text.as_bytes()).unwrap_err();
+        let message = |text: &str| {
+            let error = read(text);
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+            error.to_string()
+        };
+        let docs = "search-index 1\nnext 3\ndoc 0 1 a\ndoc 1 1 b\n";
+
+        assert_eq!(message(""), "line 1: not a search index");
+        assert_eq!(message("search-index 2\n"), "line 1: not a search index");
+        assert_eq!(message(&format!("{}term x 1:0 0:0\n", docs)), "line 5: documents for 'x' are out of order");
+        assert_eq!(message(&format!("{}term x 0:0 0:1\n", docs)), "line 5: documents for 'x' are out of order");
+        assert_eq!(message(&format!("{}term x 2:0\n", docs)), "line 5: 'x' refers to unknown document 2");
+        assert_eq!(message(&format!("{}term x 0:3,1\n", docs)), "line 5: positions for 'x' are out of order");
+        assert_eq!(message(&format!("{}term x\n", docs)), "line 5: 'x' has no documents");
+        assert_eq!(message(&format!("{}term x 0\n", docs)), "line 5: expected doc:positions, found '0'");
+        assert_eq!(message(&format!("{}doc 0 1 c\n", docs)), "line 5: document 0 appears twice");
+        assert_eq!(message("search-index 1\nnext 1\ndoc 1 1 a\n"), "line 3: document 1 isn't below the next id 1");
+        assert_eq!(message("search-index 1\nnext x\n"), "line 2: 'x' is not a number");
+        assert_eq!(message("search-index 1\nnext 1\ndoc 0 1 a\\q\n"), "line 3: bad escape in name 'a\\q'");
+        assert_eq!(message("search-index 1\nwhat\n"), "line 2: expected 'next', 'doc' or 'term', found 'what'");
+    }
+}
@@ -0,0 +1,464 @@
+// Vectors that stay sorted.
+//
+// SortedVec keeps duplicates, SortedSet drops them. Both are a plain Vec underneath, so lookups
+// are binary searches and iteration is as fast as a slice. Inserting and removing shift elements,
+// which is cheap for the small to medium sizes we use them for (see examples/bench_sorted.rs).
+
+use std::ops::{Bound, Deref, RangeBounds};
+use std::vec;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SortedVec<T: Ord> {
+    items: Vec<T>,
+}
+
+impl<T: Ord> SortedVec<T> {
+    pub fn new() -> SortedVec<T> {
+        SortedVec { items: Vec::new() }
+    }
+
+    pub fn with_capacity(capacity: usize) -> SortedVec<T> {
+        SortedVec {
+            items: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Sorts the Vec once up front.
+    pub fn from_vec(mut items: Vec<T>) -> SortedVec<T> {
+        items.sort();
+        SortedVec { items }
+    }
+
+    /// Inserts after any equal elements and returns the position used.
+    pub fn insert(&mut self, item: T) -> usize {
+        let index = self.items.partition_point(|x| x <= &item);
+        self.items.insert(index, item);
+        index
+    }
+
+    /// Removes one element equal to `item`.
+    pub fn remove(&mut self, item: &T) -> Option<T> {
+        let index = self.find(item)?;
+        Some(self.items.remove(index))
+    }
+
+    pub fn remove_index(&mut self, index: usize) -> Option<T> {
+        if index < self.items.len() {
+            Some(self.items.remove(index))
+        } else {
+            None
+        }
+    }
+
+    /// Removes every element equal to `item` and returns how many there were.
+    pub fn remove_all(&mut self, item: &T) -> usize {
+        let (start, end) = self.equal_range(item);
+        self.items.drain(start..end);
+        end - start
+    }
+
+    pub fn contains(&self, item: &T) -> bool {
+        self.items.binary_search(item).is_ok()
+    }
+
+    /// Position of the first element equal to `item`.
+    pub fn find(&self, item: &T) -> Option<usize> {
+        let index = self.items.partition_point(|x| x < item);
+        match self.items.get(index) {
+            Some(found) if found == item => Some(index),
+            _ => None,
+        }
+    }
+
+    pub fn count(&self, item: &T) -> usize {
+        let (start, end) = self.equal_range(item);
+        end - start
+    }
+
+    /// The elements that fall inside `range`, e.g. `sorted.range(10..20)`.
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> &[T] {
+        let (start, end) = self.bounds(&range);
+        &self.items[start..end]
+    }
+
+    /// Merges two sorted vecs in one linear pass.
+    pub fn merge(self, other: SortedVec<T>) -> SortedVec<T> {
+        let mut merged = Vec::with_capacity(self.items.len() + other.items.len());
+        let mut left = self.items.into_iter().peekable();
+        let mut right = other.items.into_iter().peekable();
+
+        loop {
+            let take_left = match (left.peek(), right.peek()) {
+                (Some(a), Some(b)) => a <= b,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => break,
+            };
+            let next = if take_left { left.next() } else { right.next() };
+            merged.extend(next);
+        }
+
+        SortedVec { items: merged }
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.items.first()
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.items.last()
+    }
+
+    pub fn pop_first(&mut self) -> Option<T> {
+        self.remove_index(0)
+    }
+
+    pub fn pop_last(&mut self) -> Option<T> {
+        self.items.pop()
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, keep: F) {
+        self.items.retain(keep);
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.items
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.items
+    }
+
+    fn equal_range(&self, item: &T) -> (usize, usize) {
+        (
+            self.items.partition_point(|x| x < item),
+            self.items.partition_point(|x| x <= item),
+        )
+    }
+
+    fn bounds<R: RangeBounds<T>>(&self, range: &R) -> (usize, usize) {
+        let start = match range.start_bound() {
+            Bound::Included(start) => self.items.partition_point(|x| x < start),
+            Bound::Excluded(start) => self.items.partition_point(|x| x <= start),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => self.items.partition_point(|x| x <= end),
+            Bound::Excluded(end) => self.items.partition_point(|x| x < end),
+            Bound::Unbounded => self.items.len(),
+        };
+        // A backwards range like 5..2 is just empty.
+        (start, end.max(start))
+    }
+}
+
+impl<T: Ord> Default for SortedVec<T> {
+    fn default() -> SortedVec<T> {
+        SortedVec::new()
+    }
+}
+
+// Only Deref, not DerefMut: handing out `&mut [T]` would let callers break the order.
+impl<T: Ord> Deref for SortedVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.items
+    }
+}
+
+impl<T: Ord> From<Vec<T>> for SortedVec<T> {
+    fn from(items: Vec<T>) -> SortedVec<T> {
+        SortedVec::from_vec(items)
+    }
+}
+
+impl<T: Ord> FromIterator<T> for SortedVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> SortedVec<T> {
+        SortedVec::from_vec(iter.into_iter().collect())
+    }
+}
+
+/// Appends everything and re-sorts once. The stable sort notices the two sorted runs, so this
+/// is close to a merge.
+impl<T: Ord> Extend<T> for SortedVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.items.extend(iter);
+        self.items.sort();
+    }
+}
+
+impl<T: Ord> IntoIterator for SortedVec<T> {
+    type Item = T;
+    type IntoIter = vec::IntoIter<T>;
+
+    fn into_iter(self) -> vec::IntoIter<T> {
+        self.items.into_iter()
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a SortedVec<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> std::slice::Iter<'a, T> {
+        self.items.iter()
+    }
+}
+
+/// A SortedVec without duplicates.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SortedSet<T: Ord> {
+    items: SortedVec<T>,
+}
+
+impl<T: Ord> SortedSet<T> {
+    pub fn new() -> SortedSet<T> {
+        SortedSet {
+            items: SortedVec::new(),
+        }
+    }
+
+    pub fn from_vec(items: Vec<T>) -> SortedSet<T> {
+        let mut items = SortedVec::from_vec(items);
+        items.items.dedup();
+        SortedSet { items }
+    }
+
+    /// Returns false if the value was already there.
+    pub fn insert(&mut self, item: T) -> bool {
+        match self.items.items.binary_search(&item) {
+            Ok(_) => false,
+            Err(index) => {
+                self.items.items.insert(index, item);
+                true
+            }
+        }
+    }
+
+    /// Returns false if the value wasn't there.
+    pub fn remove(&mut self, item: &T) -> bool {
+        self.items.remove(item).is_some()
+    }
+
+    pub fn contains(&self, item: &T) -> bool {
+        self.items.contains(item)
+    }
+
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> &[T] {
+        self.items.range(range)
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.items.first()
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.items.last()
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        self.items.as_slice()
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.items.into_vec()
+    }
+
+    /// Everything in either set.
+    pub fn union(self, other: SortedSet<T>) -> SortedSet<T> {
+        let mut items = self.items.merge(other.items);
+        items.items.dedup();
+        SortedSet { items }
+    }
+
+    /// Everything in both sets.
+    pub fn intersection(self, other: &SortedSet<T>) -> SortedSet<T> {
+        self.keep(|item| other.contains(item))
+    }
+
+    /// Everything in this set but not `other`.
+    pub fn difference(self, other: &SortedSet<T>) -> SortedSet<T> {
+        self.keep(|item| !other.contains(item))
+    }
+
+    pub fn is_subset(&self, other: &SortedSet<T>) -> bool {
+        self.len() <= other.len() && self.iter().all(|item| other.contains(item))
+    }
+
+    fn keep<F: FnMut(&T) -> bool>(mut self, keep: F) -> SortedSet<T> {
+        self.items.retain(keep);
+        self
+    }
+}
+
+impl<T: Ord> Default for SortedSet<T> {
+    fn default() -> SortedSet<T> {
+        SortedSet::new()
+    }
+}
+
+impl<T: Ord> Deref for SortedSet<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.items
+    }
+}
+
+impl<T: Ord> From<Vec<T>> for SortedSet<T> {
+    fn from(items: Vec<T>) -> SortedSet<T> {
+        SortedSet::from_vec(items)
+    }
+}
+
+impl<T: Ord> FromIterator<T> for SortedSet<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> SortedSet<T> {
+        SortedSet::from_vec(iter.into_iter().collect())
+    }
+}
+
+impl<T: Ord> Extend<T> for SortedSet<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.items.extend(iter);
+        self.items.items.dedup();
+    }
+}
+
+impl<T: Ord> IntoIterator for SortedSet<T> {
+    type Item = T;
+    type IntoIter = vec::IntoIter<T>;
+
+    fn into_iter(self) -> vec::IntoIter<T> {
+        self.items.into_iter()
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a SortedSet<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> std::slice::Iter<'a, T> {
+        self.items.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    fn random_bound(rng: &mut Rng) -> Bound<i32> {
+        let value = rng.below(60) as i32 - 5;
+        match rng.below(3) {
+            0 => Bound::Included(value),
+            1 => Bound::Excluded(value),
+            _ => Bound::Unbounded,
+        }
+    }
+
+    #[test]
+    fn sorted_vec_matches_a_btree_map_of_counts() {
+        let mut rng = Rng(0x5EED_50F7);
+        let mut sorted = SortedVec::new();
+        let mut counts: BTreeMap<i32, usize> = BTreeMap::new();
+
+        for _ in 0..5_000 {
+            let value = rng.below(50) as i32;
+            match rng.below(6) {
+                0 | 1 => {
+                    let index = sorted.insert(value);
+                    let before: usize = counts.range(..=value).map(|(_, count)| count).sum();
+                    assert_eq!(index, before);
+                    *counts.entry(value).or_default() += 1;
+                }
+                2 => {
+                    let expected = counts.get(&value).is_some_and(|&count| count > 0);
+                    assert_eq!(sorted.remove(&value).is_some(), expected);
+                    if let Some(count) = counts.get_mut(&value) {
+                        *count -= 1;
+                        if *count == 0 {
+                            counts.remove(&value);
+                        }
+                    }
+                }
+                3 => assert_eq!(sorted.remove_all(&value), counts.remove(&value).unwrap_or(0)),
+                4 => {
+                    // Filtered by hand, since BTreeMap::range panics on backwards ranges.
+                    let range = (random_bound(&mut rng), random_bound(&mut rng));
+                    let expected: Vec<i32> = counts
+                        .iter()
+                        .filter(|(v, _)| range.contains(v))
+                        .flat_map(|(&v, &count)| std::iter::repeat_n(v, count))
+                        .collect();
+                    assert_eq!(sorted.range(range), expected.as_slice(), "{:?}", range);
+                }
+                _ => {
+                    assert_eq!(sorted.contains(&value), counts.contains_key(&value));
+                    assert_eq!(sorted.count(&value), counts.get(&value).copied().unwrap_or(0));
+                    let first: usize = counts.range(..value).map(|(_, count)| count).sum();
+                    assert_eq!(sorted.find(&value), counts.contains_key(&value).then_some(first));
+                }
+            }
+            assert_eq!(sorted.len(), counts.values().sum::<usize>());
+        }
+        assert!(sorted.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn sorted_set_matches_a_btree_set() {
+        let mut rng = Rng(42);
+        let mut set = SortedSet::new();
+        let mut oracle = BTreeSet::new();
+
+        for _ in 0..5_000 {
+            let value = rng.below(50) as i32;
+            match rng.below(4) {
+                0 => assert_eq!(set.insert(value), oracle.insert(value)),
+                1 => assert_eq!(set.remove(&value), oracle.remove(&value)),
+                2 => {
+                    let range = (random_bound(&mut rng), random_bound(&mut rng));
+                    let expected: Vec<i32> = oracle.iter().copied().filter(|v| range.contains(v)).collect();
+                    assert_eq!(set.range(range), expected.as_slice(), "{:?}", range);
+                }
+                _ => assert_eq!(set.contains(&value), oracle.contains(&value)),
+            }
+            assert_eq!(set.len(), oracle.len());
+        }
+        assert!(set.iter().eq(oracle.iter()));
+    }
+
+    #[test]
+    fn set_operations_match_btree_sets() {
+        let mut rng = Rng(7);
+        for _ in 0..200 {
+            let a: Vec<i32> = (0..rng.below(30)).map(|_| rng.below(40) as i32).collect();
+            let b: Vec<i32> = (0..rng.below(30)).map(|_| rng.below(40) as i32).collect();
+            let (set_a, set_b) = (SortedSet::from_vec(a.clone()), SortedSet::from_vec(b.clone()));
+            let (tree_a, tree_b): (BTreeSet<i32>, BTreeSet<i32>) = (a.into_iter().collect(), b.into_iter().collect());
+
+            assert!(set_a.clone().union(set_b.clone()).iter().eq(tree_a.union(&tree_b)));
+            assert!(set_a.clone().intersection(&set_b).iter().eq(tree_a.intersection(&tree_b)));
+            assert!(set_a.clone().difference(&set_b).iter().eq(tree_a.difference(&tree_b)));
+            assert_eq!(set_a.is_subset(&set_b), tree_a.is_subset(&tree_b));
+        }
+    }
+
+    #[test]
+    fn merge_keeps_duplicates_in_order() {
+        let left = SortedVec::from_vec(vec![5, 1, 3, 3]);
+        let right = SortedVec::from_vec(vec![3, 0, 6]);
+        assert_eq!(left.merge(right).into_vec(), [0, 1, 3, 3, 3, 5, 6]);
+        assert_eq!(SortedVec::<i32>::new().merge(SortedVec::new()).len(), 0);
+
+        let mut sorted: SortedVec<i32> = [4, 2, 2].into_iter().collect();
+        assert_eq!(sorted.range((Bound::Included(3), Bound::Excluded(2))), &[] as &[i32]);
+        assert_eq!((sorted.pop_first(), sorted.pop_last()), (Some(2), Some(4)));
+        assert_eq!(sorted.remove_index(5), None);
+    }
+}
@@ -0,0 +1,315 @@
+// Descriptive statistics over slices, for the vectors built in chapter 8.
+//
+// Every function takes any primitive number through the Value trait and returns None (or an empty
+// Vec) for empty input rather than panicking the way `&v[100]` does. Values are converted to f64,
+// so integers beyond 2^53 lose precision. Sorting uses f64::total_cmp, which puts NaN above
+// infinity, so a NaN shows up as the max and makes the mean NaN.
+
+use std::cmp::Ordering;
+
+pub trait Value: Copy {
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! impl_value {
+    ($($t:ty),*) => {
+        $(
+            impl Value for $t {
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+            }
+        )*
+    };
+}
+
+impl_value!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variance {
+    /// Divide by n. Use when the data is the whole population.
+    Population,
+    /// Divide by n - 1. Use when the data is a sample of something bigger.
+    Sample,
+}
+
+/// How to pick a value when a percentile falls between two data points. These match the
+/// methods of the same names in NumPy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    Linear,
+    Lower,
+    Higher,
+    Nearest,
+    Midpoint,
+}
+
+pub fn sum<T: Value>(data: &[T]) -> f64 {
+    data.iter().map(|v| v.to_f64()).sum()
+}
+
+pub fn mean<T: Value>(data: &[T]) -> Option<f64> {
+    if data.is_empty() {
+        return None;
+    }
+    Some(sum(data) / data.len() as f64)
+}
+
+pub fn min<T: Value>(data: &[T]) -> Option<f64> {
+    data.iter().map(|v| v.to_f64()).min_by(f64::total_cmp)
+}
+
+pub fn max<T: Value>(data: &[T]) -> Option<f64> {
+    data.iter().map(|v| v.to_f64()).max_by(f64::total_cmp)
+}
+
+pub fn median<T: Value>(data: &[T]) -> Option<f64> {
+    quantile(data, 0.5, Interpolation::Midpoint)
+}
+
+/// Every value that appears the most often, smallest first.
+pub fn modes<T: Value>(data: &[T]) -> Vec<T> {
+    let mut sorted = data.to_vec();
+    sorted.sort_by(compare);
+
+    let mut modes = Vec::new();
+    let mut best = 0;
+    let mut i = 0;
+    while i < sorted.len() {
+        let run = sorted[i..]
+            .iter()
+            .take_while(|v| compare(*v, &sorted[i]) == Ordering::Equal)
+            .count();
+
+        match run.cmp(&best) {
+            Ordering::Greater => {
+                best = run;
+                modes.clear();
+                modes.push(sorted[i]);
+            }
+            Ordering::Equal => modes.push(sorted[i]),
+            Ordering::Less => {}
+        }
+        i += run;
+    }
+    modes
+}
+
+/// The smallest of the modes.
+pub fn mode<T: Value>(data: &[T]) -> Option<T> {
+    modes(data).first().copied()
+}
+
+/// `None` for empty input, and for a sample of fewer than two values.
+pub fn variance<T: Value>(data: &[T], kind: Variance) -> Option<f64> {
+    let mean = mean(data)?;
+    let divisor = match kind {
+        Variance::Population => data.len(),
+        Variance::Sample => data.len().checked_sub(1).filter(|&n| n > 0)?,
+    };
+
+    let squares: f64 = data
+        .iter()
+        .map(|v| {
+            let d = v.to_f64() - mean;
+            d * d
+        })
+        .sum();
+    Some(squares / divisor as f64)
+}
+
+pub fn std_dev<T: Value>(data: &[T], kind: Variance) -> Option<f64> {
+    variance(data, kind).map(f64::sqrt)
+}
+
+/// `q` runs from 0.0 (the minimum) to 1.0 (the maximum). `None` for empty input or a `q` outside
+/// that range.
+pub fn quantile<T: Value>(data: &[T], q: f64, interpolation: Interpolation) -> Option<f64> {
+    if data.is_empty() || !(0.0..=1.0).contains(&q) {
+        return None;
+    }
+
+    let mut sorted: Vec<f64> = data.iter().map(|v| v.to_f64()).collect();
+    sorted.sort_by(f64::total_cmp);
+
+    let position = q * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let higher = position.ceil() as usize;
+    let fraction = position - lower as f64;
+    let (a, b) = (sorted[lower], sorted[higher]);
+
+    Some(match interpolation {
+        Interpolation::Linear => a + (b - a) * fraction,
+        Interpolation::Lower => a,
+        Interpolation::Higher => b,
+        // Ties go to the even index, like NumPy.
+        Interpolation::Nearest => {
+            if fraction < 0.5 || (fraction == 0.5 && lower.is_multiple_of(2)) {
+                a
+            } else {
+                b
+            }
+        }
+        Interpolation::Midpoint => (a + b) / 2.0,
+    })
+}
+
+/// `p` runs from 0.0 to 100.0.
+pub fn percentile<T: Value>(data: &[T], p: f64, interpolation: Interpolation) -> Option<f64> {
+    quantile(data, p / 100.0, interpolation)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    pub min: f64,
+    pub max: f64,
+    /// Width of every bucket.
+    pub width: f64,
+    pub counts: Vec<usize>,
+}
+
+impl Histogram {
+    /// Lower and upper edge of bucket `i`. The last bucket includes its upper edge.
+    pub fn bucket_range(&self, i: usize) -> (f64, f64) {
+        let start = self.min + self.width * i as f64;
+        (start, start + self.width)
+    }
+
+    /// One line per bucket with a bar of `#`s scaled to `bar_width`.
+    pub fn render(&self, bar_width: usize) -> String {
+        let most = self.counts.iter().copied().max().unwrap_or(0).max(1);
+        let mut text = String::new();
+        for (i, count) in self.counts.iter().enumerate() {
+            let (start, end) = self.bucket_range(i);
+            let close = if i + 1 == self.counts.len() { ']' } else { ')' };
+            let bar = "#".repeat(count * bar_width / most);
+            text.push_str(&format!("[{:>10.2}, {:>10.2}{} {:>6} {}\n", start, end, close, count, bar));
+        }
+        text
+    }
+}
+
+/// Splits the range of the data into `buckets` equal-width buckets. NaNs aren't in any bucket.
+/// `None` for zero buckets or when there's nothing but NaN to count.
+pub fn histogram<T: Value>(data: &[T], buckets: usize) -> Option<Histogram> {
+    if buckets == 0 {
+        return None;
+    }
+    let values: Vec<f64> = data.iter().map(|v| v.to_f64()).filter(|v| !v.is_nan()).collect();
+    let min = min(&values)?;
+    let max = max(&values)?;
+
+    // All-equal data still needs a non-zero width.
+    let width = if max > min { (max - min) / buckets as f64 } else { 1.0 };
+
+    let mut counts = vec![0; buckets];
+    for value in values {
+        let i = ((value - min) / width) as usize;
+        counts[i.min(buckets - 1)] += 1;
+    }
+
+    Some(Histogram {
+        min,
+        max,
+        width,
+        counts,
+    })
+}
+
+fn compare<T: Value>(a: &T, b: &T) -> Ordering {
+    a.to_f64().total_cmp(&b.to_f64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_input() {
+        let empty: [i32; 0] = [];
+        assert_eq!(sum(&empty), 0.0);
+        assert_eq!(mean(&empty), None);
+        assert_eq!((min(&empty), max(&empty), median(&empty)), (None, None, None));
+        assert_eq!(modes(&empty), []);
+        assert_eq!(mode(&empty), None);
+        assert_eq!(variance(&empty, Variance::Population), None);
+        assert_eq!(quantile(&empty, 0.5, Interpolation::Linear), None);
+        assert_eq!(histogram(&empty, 4), None);
+    }
+
+    #[test]
+    fn one_value() {
+        let one = [7u8];
+        assert_eq!(mean(&one), Some(7.0));
+        assert_eq!(median(&one), Some(7.0));
+        assert_eq!(mode(&one), Some(7));
+        assert_eq!(variance(&one, Variance::Population), Some(0.0));
+        assert_eq!(variance(&one, Variance::Sample), None);
+        assert_eq!(percentile(&one, 90.0, Interpolation::Nearest), Some(7.0));
+
+        let histogram = histogram(&one, 3).unwrap();
+        assert_eq!((histogram.min, histogram.max, histogram.width), (7.0, 7.0, 1.0));
+        assert_eq!(histogram.counts, [1, 0, 0]);
+    }
+
+    #[test]
+    fn medians_and_quantiles() {
+        assert_eq!(median(&[4, 1, 3, 2]), Some(2.5));
+        assert_eq!(median(&[5, 1, 3]), Some(3.0));
+        assert_eq!(median(&[-1.5, 1.5]), Some(0.0));
+
+        let data = [1, 2, 3, 4];
+        assert_eq!(quantile(&data, 0.5, Interpolation::Linear), Some(2.5));
+        assert_eq!(quantile(&data, 0.5, Interpolation::Lower), Some(2.0));
+        assert_eq!(quantile(&data, 0.5, Interpolation::Higher), Some(3.0));
+        assert_eq!(quantile(&data, 0.5, Interpolation::Nearest), Some(3.0));
+        assert_eq!(quantile(&[1, 2, 3, 4, 5, 6], 0.5, Interpolation::Nearest), Some(3.0));
+        assert_eq!(quantile(&data, 1.0, Interpolation::Linear), Some(4.0));
+        assert_eq!(quantile(&data, 1.5, Interpolation::Linear), None);
+        assert_eq!(quantile(&data, f64::NAN, Interpolation::Linear), None);
+    }
+
+    #[test]
+    fn modes_and_spread() {
+        assert_eq!(modes(&[3, 1, 3, 1, 2]), [1, 3]);
+        assert_eq!(mode(&[3, 1, 3, 1, 2]), Some(1));
+        let data = [2, 4, 4, 4, 5, 5, 7, 9];
+        assert_eq!(std_dev(&data, Variance::Population), Some(2.0));
+        assert_eq!(variance(&data, Variance::Sample), Some(32.0 / 7.0));
+    }
+
+    #[test]
+    fn nan_sorts_above_everything() {
+        let data = [1.0, f64::NAN, 3.0];
+        assert!(mean(&data).unwrap().is_nan());
+        assert!(max(&data).unwrap().is_nan());
+        assert_eq!(min(&data), Some(1.0));
+        assert_eq!(median(&data), Some(3.0));
+        assert_eq!(modes(&[f64::NAN, 2.0, 2.0]), [2.0]);
+
+        let histogram = histogram(&data, 2).unwrap();
+        assert_eq!((histogram.min, histogram.max), (1.0, 3.0));
+        assert_eq!(histogram.counts, [1, 1]);
+        assert_eq!(super::histogram(&[f64::NAN], 2), None);
+    }
+
+    #[test]
+    fn histogram_edges() {
+        assert_eq!(histogram(&[1], 0), None);
+
+        // An edge value goes in the bucket it starts, except the max, which closes the last one.
+        let histogram = histogram(&[0, 5, 10, 2, 7], 2).unwrap();
+        assert_eq!(histogram.width, 5.0);
+        assert_eq!(histogram.counts, [2, 3]);
+        assert_eq!(histogram.bucket_range(1), (5.0, 10.0));
+        assert_eq!(
+            histogram.render(6),
+            "[      0.00,       5.00)      2 ####\n[      5.00,      10.00]      3 ######\n"
+        );
+
+        // 0.1 * 3 isn't exactly 0.3, but the max still lands in the last bucket.
+        let histogram = super::histogram(&[0.0, 0.1, 0.2, 0.3], 3).unwrap();
+        assert_eq!(histogram.counts.iter().sum::<usize>(), 4);
+        assert_eq!(histogram.counts[2], 2);
+    }
+}
@@ -0,0 +1,321 @@
+// Checked slicing for strings.
+//
+// Chapter 4 slices "hello world" with `&s[..5]` and `&s[6..]`. Those are byte offsets, so the
+// same code panics as soon as a character takes more than one byte: `&"héllo"[..2]` lands in the
+// middle of 'é'. The SafeSlice methods never panic. They slice by byte, char or grapheme index
+// and return a SliceError that says what was wrong and where the nearest valid offsets are.
+
+use std::fmt;
+use std::ops::RangeBounds;
+
+use crate::access;
+use crate::grapheme;
+
+/// What a slice index counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Byte,
+    Char,
+    Grapheme,
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Unit::Byte => "byte",
+            Unit::Char => "char",
+            Unit::Grapheme => "grapheme",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SliceError {
+    /// `end` is exclusive, and `len` is counted in `unit`s.
+    OutOfRange {
+        unit: Unit,
+        start: usize,
+        end: usize,
+        len: usize,
+    },
+    /// A byte offset that falls inside a multi-byte character, which starts at `char_start`
+    /// and is `char_len` bytes long.
+    NotCharBoundary {
+        index: usize,
+        char_start: usize,
+        char_len: usize,
+    },
+}
+
+impl fmt::Display for SliceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SliceError::OutOfRange {
+                unit,
+                start,
+                end,
+                len,
+            } if start > end => write!(
+                f,
+                "{} range {}..{} runs backwards (length {})",
+                unit, start, end, len
+            ),
+            SliceError::OutOfRange {
+                unit,
+                start,
+                end,
+                len,
+            } => write!(
+                f,
+                "{} range {}..{} goes past the end (length {})",
+                unit, start, end, len
+            ),
+            SliceError::NotCharBoundary {
+                index,
+                char_start,
+                char_len,
+            } => write!(
+                f,
+                "byte {} is inside a {}-byte character; the nearest boundaries are {} and {}",
+                index,
+                char_len,
+                char_start,
+                char_start + char_len
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SliceError {}
+
+pub trait SafeSlice {
+    /// Like `&s[range]`, but an error instead of a panic.
+    fn byte_slice<R: RangeBounds<usize>>(&self, range: R) -> Result<&str, SliceError>;
+    /// Slices by char index: `"héllo".char_slice(1..3)` is "él".
+    fn char_slice<R: RangeBounds<usize>>(&self, range: R) -> Result<&str, SliceError>;
+    /// Slices by grapheme index, so accents and emoji are never split.
+    fn grapheme_slice<R: RangeBounds<usize>>(&self, range: R) -> Result<&str, SliceError>;
+    /// Everything up to the first whitespace, skipping any leading whitespace. The whole string
+    /// if there is no whitespace, and "" if there are no words.
+    fn first_word(&self) -> &str;
+    /// The word at `n`, counting from 0, with words separated by any Unicode whitespace.
+    fn nth_word(&self, n: usize) -> Option<&str>;
+}
+
+impl SafeSlice for str {
+    fn byte_slice<R: RangeBounds<usize>>(&self, range: R) -> Result<&str, SliceError> {
+        let (start, end) = resolve(&range, Unit::Byte, self.len())?;
+        check_boundary(self, start)?;
+        check_boundary(self, end)?;
+        Ok(&self[start..end])
+    }
+
+    fn char_slice<R: RangeBounds<usize>>(&self, range: R) -> Result<&str, SliceError> {
+        let offsets = self.char_indices().map(|(offset, _)| offset);
+        slice_by(self, &range, Unit::Char, offsets)
+    }
+
+    fn grapheme_slice<R: RangeBounds<usize>>(&self, range: R) -> Result<&str, SliceError> {
+        let offsets = grapheme::grapheme_indices(self).map(|(offset, _)| offset);
+        slice_by(self, &range, Unit::Grapheme, offsets)
+    }
+
+    fn first_word(&self) -> &str {
+        self.nth_word(0).unwrap_or("")
+    }
+
+    fn nth_word(&self, n: usize) -> Option<&str> {
+        self.split_whitespace().nth(n)
+    }
+}
+
+// Slices using the byte offsets where each char or grapheme starts.
+fn slice_by<'a, R, I>(text: &'a str, range: &R, unit: Unit, starts: I) -> Result<&'a str, SliceError>
+where
+    R: RangeBounds<usize>,
+    I: Iterator<Item = usize>,
+{
+    // One extra offset for the end of the string, so `len..len` and `..len` work.
+    let offsets: Vec<usize> = starts.chain([text.len()]).collect();
+    let len = offsets.len() - 1;
+    let (start, end) = resolve(range, unit, len)?;
+    Ok(&text[offsets[start]..offsets[end]])
+}
+
+fn resolve<R: RangeBounds<usize>>(range: &R, unit: Unit, len: usize) -> Result<(usize, usize), SliceError> {
+    let (start, end) = access::bounds(range, len);
+    if start > end || end > len {
+        return Err(SliceError::OutOfRange {
+            unit,
+            start,
+            end,
+            len,
+        });
+    }
+    Ok((start, end))
+}
+
+fn check_boundary(text: &str, index: usize) -> Result<(), SliceError> {
+    if text.is_char_boundary(index) {
+        return Ok(());
+    }
+    // A char is at most four bytes, so its start is at most three bytes back.
+    let char_start = (index.saturating_sub(3)..index)
+        .rev()
+        .find(|&i| text.is_char_boundary(i))
+        .unwrap_or(0);
+    let char_len = text[char_start..].chars().next().map_or(1, char::len_utf8);
+    Err(SliceError::NotCharBoundary {
+        index,
+        char_start,
+        char_len,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use std::ops::Bound;
+
+    // One to four bytes per char, plus the pieces that glue graphemes together: combining marks,
+    // joiners, skin tones, regional indicators, virama and CR LF.
+    const PIECES: [&str; 18] = [
+        "a", " ", "\0", "é", "e\u{301}", "\u{301}", "\u{200d}", "👨", "\u{1f3fb}", "🇺", "🇸", "क",
+        "्", "ष", "\r", "\n", "한", "\u{fe0f}",
+    ];
+
+    fn random_string(rng: &mut Rng) -> String {
+        let pieces = rng.below(12);
+        (0..pieces).map(|_| PIECES[rng.below(PIECES.len())]).collect()
+    }
+
+    // Byte offsets where each unit starts, plus one for the end of the string.
+    fn starts(text: &str, unit: Unit) -> Vec<usize> {
+        let starts: Vec<usize> = match unit {
+            Unit::Byte => (0..text.len()).collect(),
+            Unit::Char => text.char_indices().map(|(offset, _)| offset).collect(),
+            Unit::Grapheme => grapheme::grapheme_indices(text).map(|(offset, _)| offset).collect(),
+        };
+        starts.into_iter().chain([text.len()]).collect()
+    }
+
+    fn slice(text: &str, unit: Unit, range: (Bound<usize>, Bound<usize>)) -> Result<&str, SliceError> {
+        match unit {
+            Unit::Byte => text.byte_slice(range),
+            Unit::Char => text.char_slice(range),
+            Unit::Grapheme => text.grapheme_slice(range),
+        }
+    }
+
+    fn random_bound(rng: &mut Rng, len: usize) -> Bound<usize> {
+        let index = match rng.below(8) {
+            0 => usize::MAX,
+            _ => rng.below(len + 3),
+        };
+        match rng.below(4) {
+            0 => Bound::Unbounded,
+            1 => Bound::Excluded(index),
+            _ => Bound::Included(index),
+        }
+    }
+
+    // Checks one slice against &text[a..b] on the unit's offsets, which is the definition each
+    // method promises, and checks the error says what went wrong.
+    fn check(text: &str, unit: Unit, range: (Bound<usize>, Bound<usize>)) {
+        let offsets = starts(text, unit);
+        let len = offsets.len() - 1;
+        let start = match range.0 {
+            Bound::Included(start) => Some(start),
+            Bound::Excluded(start) => start.checked_add(1),
+            Bound::Unbounded => Some(0),
+        };
+        let end = match range.1 {
+            Bound::Included(end) => end.checked_add(1),
+            Bound::Excluded(end) => Some(end),
+            Bound::Unbounded => Some(len),
+        };
+
+        let result = slice(text, unit, range);
+        match (start, end) {
+            (Some(start), Some(end)) if start <= end && end <= len => {
+                let expected = text.get(offsets[start]..offsets[end]);
+                match (result, expected) {
+                    (Ok(got), Some(expected)) => assert_eq!(got, expected, "{:?} {:?}", text, range),
+                    (Err(SliceError::NotCharBoundary { index, char_start, char_len }), None) => {
+                        assert_eq!(unit, Unit::Byte);
+                        assert!(index == start || index == end);
+                        assert!(char_start < index && index < char_start + char_len);
+                        assert!(text.is_char_boundary(char_start));
+                        assert!(text.is_char_boundary(char_start + char_len));
+                    }
+                    (result, expected) => {
+                        panic!("{:?} {:?}: got {:?}, expected {:?}", text, range, result, expected)
+                    }
+                }
+            }
+            _ => assert!(
+                matches!(result, Err(SliceError::OutOfRange { unit: u, len: l, .. }) if u == unit && l == len),
+                "{:?} {:?}: got {:?}",
+                text,
+                range,
+                result
+            ),
+        }
+    }
+
+    #[test]
+    fn random_slices_match_str_indexing() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..3000 {
+            let text = random_string(&mut rng);
+            for _ in 0..10 {
+                for unit in [Unit::Byte, Unit::Char, Unit::Grapheme] {
+                    let range = (random_bound(&mut rng, text.len()), random_bound(&mut rng, text.len()));
+                    check(&text, unit, range);
+                }
+            }
+
+            // Every valid byte range agrees with plain indexing.
+            for a in 0..=text.len() {
+                for b in a..=text.len() {
+                    assert_eq!(text.byte_slice(a..b).ok(), text.get(a..b));
+                }
+            }
+            // Graphemes cover the string, and slicing them one at a time gives them back.
+            let graphemes: Vec<&str> = grapheme::graphemes(&text).collect();
+            for (i, expected) in graphemes.iter().enumerate() {
+                assert_eq!(text.grapheme_slice(i..=i), Ok(*expected));
+            }
+            assert_eq!(text.grapheme_slice(..), Ok(text.as_str()));
+            assert_eq!(text.char_slice(..), Ok(text.as_str()));
+        }
+    }
+
+    #[test]
+    fn errors_name_the_nearest_boundaries() {
+        assert_eq!(
+            "héllo".byte_slice(..2),
+            Err(SliceError::NotCharBoundary {
+                index: 2,
+                char_start: 1,
+                char_len: 2
+            })
+        );
+        assert_eq!("héllo".char_slice(1..3), Ok("él"));
+        assert_eq!(
+            "héllo".char_slice(3..9),
+            Err(SliceError::OutOfRange {
+                unit: Unit::Char,
+                start: 3,
+                end: 9,
+                len: 5
+            })
+        );
+        assert_eq!(
+            "héllo".byte_slice((Bound::Included(3), Bound::Excluded(1))).unwrap_err().to_string(),
+            "byte range 3..1 runs backwards (length 6)"
+        );
+    }
+}
@@ -0,0 +1,694 @@
+// Small tables of mixed values, the "spreadsheet row" idea from chapter 8.
+//
+// A Vec can only hold one type, so chapter 8 suggests wrapping the different kinds of value in an
+// enum. Cell is that enum, a Row is a Vec<Cell>, and a Table is a header plus rows with CSV
+// import and export, per-column type inference, sorting and filtering.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::grapheme;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Int(i64),
+    Float(f64),
+    Text(String),
+    Bool(bool),
+    Empty,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CellType {
+    Int,
+    Float,
+    Text,
+    Bool,
+    Empty,
+}
+
+impl fmt::Display for CellType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            CellType::Int => "integer",
+            CellType::Float => "number",
+            CellType::Text => "text",
+            CellType::Bool => "true/false",
+            CellType::Empty => "empty",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Cell {
+    /// Works out the most specific type for a piece of text: empty, then true/false, then an
+    /// integer, then a number, and text if nothing else fits.
+    pub fn parse(text: &str) -> Cell {
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            return Cell::Empty;
+        }
+        if trimmed.eq_ignore_ascii_case("true") {
+            return Cell::Bool(true);
+        }
+        if trimmed.eq_ignore_ascii_case("false") {
+            return Cell::Bool(false);
+        }
+        if let Ok(i) = trimmed.parse::<i64>() {
+            return Cell::Int(i);
+        }
+        match trimmed.parse::<f64>() {
+            // "inf" and "NaN" parse as f64 but are almost always meant as words.
+            Ok(f) if f.is_finite() => Cell::Float(f),
+            _ => Cell::Text(String::from(text)),
+        }
+    }
+
+    pub fn cell_type(&self) -> CellType {
+        match self {
+            Cell::Int(_) => CellType::Int,
+            Cell::Float(_) => CellType::Float,
+            Cell::Text(_) => CellType::Text,
+            Cell::Bool(_) => CellType::Bool,
+            Cell::Empty => CellType::Empty,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Cell::Empty
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Cell::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// Integers count as numbers too.
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            Cell::Int(i) => Some(*i as f64),
+            Cell::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Cell::Text(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Cell::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Numbers compare by value whatever their type, empty cells sort last, and otherwise
+    /// different types sort bool, number, text.
+    pub fn compare(&self, other: &Cell) -> Ordering {
+        match (self, other) {
+            (Cell::Empty, Cell::Empty) => Ordering::Equal,
+            (Cell::Empty, _) => Ordering::Greater,
+            (_, Cell::Empty) => Ordering::Less,
+            (Cell::Int(a), Cell::Int(b)) => a.cmp(b),
+            (Cell::Bool(a), Cell::Bool(b)) => a.cmp(b),
+            (Cell::Text(a), Cell::Text(b)) => a.cmp(b),
+            (Cell::Float(a), Cell::Float(b)) => a.total_cmp(b),
+            (Cell::Int(a), Cell::Float(b)) => compare_int_float(*a, *b),
+            (Cell::Float(a), Cell::Int(b)) => compare_int_float(*b, *a).reverse(),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Cell::Bool(_) => 0,
+            Cell::Int(_) | Cell::Float(_) => 1,
+            Cell::Text(_) => 2,
+            Cell::Empty => 3,
+        }
+    }
+}
+
+// Exact, where converting the integer to f64 would round above 2^53 and call 2^53 + 1 equal to
+// 2^53. The float's whole part goes to i128, which holds every i64 and saturates beyond them, and
+// its fraction breaks ties. NaN sorts where `f64::total_cmp` puts it.
+fn compare_int_float(int: i64, float: f64) -> Ordering {
+    if float.is_nan() {
+        return if float.is_sign_negative() { Ordering::Greater } else { Ordering::Less };
+    }
+    let whole = float.trunc();
+    (int as i128).cmp(&(whole as i128)).then(whole.total_cmp(&float))
+}
+
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Cell::Int(i) => write!(f, "{}", i),
+            // Always with a decimal point, so 1.0 doesn't come back from CSV as the integer 1.
+            Cell::Float(x) if x.is_finite() && x.fract() == 0.0 => write!(f, "{:.1}", x),
+            Cell::Float(x) => write!(f, "{}", x),
+            Cell::Text(s) => write!(f, "{}", s),
+            Cell::Bool(b) => write!(f, "{}", b),
+            Cell::Empty => Ok(()),
+        }
+    }
+}
+
+impl From<i64> for Cell {
+    fn from(i: i64) -> Cell {
+        Cell::Int(i)
+    }
+}
+
+impl From<f64> for Cell {
+    fn from(f: f64) -> Cell {
+        Cell::Float(f)
+    }
+}
+
+impl From<bool> for Cell {
+    fn from(b: bool) -> Cell {
+        Cell::Bool(b)
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(s: &str) -> Cell {
+        Cell::Text(String::from(s))
+    }
+}
+
+impl From<String> for Cell {
+    fn from(s: String) -> Cell {
+        Cell::Text(s)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Row {
+    pub cells: Vec<Cell>,
+}
+
+impl Row {
+    pub fn new(cells: Vec<Cell>) -> Row {
+        Row { cells }
+    }
+
+    /// Missing cells read as Empty, so short rows are fine.
+    pub fn get(&self, column: usize) -> &Cell {
+        self.cells.get(column).unwrap_or(&Cell::Empty)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TableError {
+    UnknownColumn(String),
+    WrongType {
+        column: String,
+        row: usize,
+        expected: CellType,
+        found: CellType,
+    },
+    WrongWidth {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// CSV problems, with 1-based line and column numbers.
+    Csv {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for TableError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TableError::UnknownColumn(name) => write!(f, "no column named '{}'", name),
+            TableError::WrongType {
+                column,
+                row,
+                expected,
+                found,
+            } => write!(f, "row {} of '{}' is {}, expected {}", row, column, found, expected),
+            TableError::WrongWidth {
+                row,
+                expected,
+                found,
+            } => write!(f, "row {} has {} cells, expected {}", row, found, expected),
+            TableError::Csv {
+                line,
+                column,
+                message,
+            } => write!(f, "CSV line {}, column {}: {}", line, column, message),
+        }
+    }
+}
+
+impl std::error::Error for TableError {}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Table {
+    pub headers: Vec<String>,
+    pub rows: Vec<Row>,
+}
+
+impl Table {
+    pub fn new(headers: Vec<String>) -> Table {
+        Table {
+            headers,
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, row: Row) -> Result<(), TableError> {
+        if row.cells.len() != self.headers.len() {
+            return Err(TableError::WrongWidth {
+                row: self.rows.len(),
+                expected: self.headers.len(),
+                found: row.cells.len(),
+            });
+        }
+        self.rows.push(row);
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn column_index(&self, name: &str) -> Result<usize, TableError> {
+        self.headers
+            .iter()
+            .position(|h| h == name)
+            .ok_or_else(|| TableError::UnknownColumn(String::from(name)))
+    }
+
+    pub fn column(&self, name: &str) -> Result<Vec<&Cell>, TableError> {
+        let i = self.column_index(name)?;
+        Ok(self.rows.iter().map(|row| row.get(i)).collect())
+    }
+
+    /// The narrowest type that fits every non-empty cell: Int, then Float if integers and
+    /// numbers are mixed, otherwise Text. A column with nothing in it is Empty.
+    pub fn column_type(&self, name: &str) -> Result<CellType, TableError> {
+        let mut inferred = CellType::Empty;
+        for cell in self.column(name)? {
+            inferred = match (inferred, cell.cell_type()) {
+                (current, CellType::Empty) => current,
+                (CellType::Empty, found) => found,
+                (current, found) if current == found => current,
+                (CellType::Int, CellType::Float) | (CellType::Float, CellType::Int) => CellType::Float,
+                _ => CellType::Text,
+            };
+        }
+        Ok(inferred)
+    }
+
+    /// Every column with its inferred type.
+    pub fn schema(&self) -> Vec<(String, CellType)> {
+        self.headers
+            .iter()
+            .map(|name| {
+                let cell_type = self.column_type(name).unwrap_or(CellType::Empty);
+                (name.clone(), cell_type)
+            })
+            .collect()
+    }
+
+    /// Empty cells come back as None. Any other non-integer is an error.
+    pub fn ints(&self, name: &str) -> Result<Vec<Option<i64>>, TableError> {
+        self.typed(name, CellType::Int, Cell::as_int)
+    }
+
+    /// Integers are widened to f64.
+    pub fn floats(&self, name: &str) -> Result<Vec<Option<f64>>, TableError> {
+        self.typed(name, CellType::Float, Cell::as_float)
+    }
+
+    pub fn bools(&self, name: &str) -> Result<Vec<Option<bool>>, TableError> {
+        self.typed(name, CellType::Bool, Cell::as_bool)
+    }
+
+    /// Every cell as text, whatever its type.
+    pub fn texts(&self, name: &str) -> Result<Vec<Option<String>>, TableError> {
+        Ok(self
+            .column(name)?
+            .into_iter()
+            .map(|cell| if cell.is_empty() { None } else { Some(cell.to_string()) })
+            .collect())
+    }
+
+    fn typed<T, F>(&self, name: &str, expected: CellType, read: F) -> Result<Vec<Option<T>>, TableError>
+    where
+        F: Fn(&Cell) -> Option<T>,
+    {
+        let mut values = Vec::with_capacity(self.rows.len());
+        for (row, cell) in self.column(name)?.into_iter().enumerate() {
+            if cell.is_empty() {
+                values.push(None);
+                continue;
+            }
+            match read(cell) {
+                Some(value) => values.push(Some(value)),
+                None => {
+                    return Err(TableError::WrongType {
+                        column: String::from(name),
+                        row,
+                        expected,
+                        found: cell.cell_type(),
+                    })
+                }
+            }
+        }
+        Ok(values)
+    }
+
+    /// Stable sort on one column. See `Cell::compare` for how mixed types are ordered.
+    pub fn sort_by_column(&mut self, name: &str, descending: bool) -> Result<(), TableError> {
+        let i = self.column_index(name)?;
+        self.rows.sort_by(|a, b| {
+            let order = a.get(i).compare(b.get(i));
+            if descending {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+        Ok(())
+    }
+
+    /// A new table with only the rows the predicate keeps.
+    pub fn filter<F: Fn(&Row) -> bool>(&self, keep: F) -> Table {
+        Table {
+            headers: self.headers.clone(),
+            rows: self.rows.iter().filter(|row| keep(row)).cloned().collect(),
+        }
+    }
+
+    /// Like `filter`, but the predicate only sees one column.
+    pub fn filter_column<F: Fn(&Cell) -> bool>(&self, name: &str, keep: F) -> Result<Table, TableError> {
+        let i = self.column_index(name)?;
+        Ok(self.filter(|row| keep(row.get(i))))
+    }
+
+    /// Rows where the column equals `value`. Numbers match across Int and Float.
+    pub fn filter_eq(&self, name: &str, value: &Cell) -> Result<Table, TableError> {
+        self.filter_column(name, |cell| cell.compare(value) == Ordering::Equal)
+    }
+
+    /// Parses CSV with a header line. Fields may be quoted with `"`, and a quoted field can hold
+    /// commas, newlines and `""` for a literal quote. Each field's type is inferred with
+    /// `Cell::parse`.
+    pub fn from_csv(text: &str) -> Result<Table, TableError> {
+        let records = parse_csv(text)?;
+        let mut records = records.into_iter();
+
+        let (_, header) = match records.next() {
+            Some(header) => header,
+            None => return Ok(Table::default()),
+        };
+        let mut table = Table::new(header);
+
+        for (line, fields) in records {
+            if fields.len() != table.headers.len() {
+                return Err(TableError::Csv {
+                    line,
+                    column: 1,
+                    message: format!("expected {} fields, found {}", table.headers.len(), fields.len()),
+                });
+            }
+            table.rows.push(Row::new(fields.iter().map(|f| Cell::parse(f)).collect()));
+        }
+
+        Ok(table)
+    }
+
+    /// Quotes only the fields that need it. Types aren't recorded, so text that looks like a
+    /// number comes back as a number when read with `from_csv`.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        write_record(&mut csv, self.headers.iter().map(|h| h.as_str()));
+        for row in &self.rows {
+            let fields: Vec<String> = (0..self.headers.len()).map(|i| row.get(i).to_string()).collect();
+            write_record(&mut csv, fields.iter().map(|f| f.as_str()));
+        }
+        csv
+    }
+}
+
+impl fmt::Display for Table {
+    /// Aligned columns for printing to a terminal.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut widths: Vec<usize> = self.headers.iter().map(|h| grapheme::width(h)).collect();
+        let rendered: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| (0..self.headers.len()).map(|i| row.get(i).to_string()).collect())
+            .collect();
+        for row in &rendered {
+            for (i, cell) in row.iter().enumerate() {
+                widths[i] = widths[i].max(grapheme::width(cell));
+            }
+        }
+
+        let line = |f: &mut fmt::Formatter, cells: &[String]| -> fmt::Result {
+            let padded: Vec<String> = cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, &width)| grapheme::pad_right(cell, width))
+                .collect();
+            writeln!(f, "{}", padded.join(" | ").trim_end())
+        };
+
+        line(f, &self.headers)?;
+        let rule: Vec<String> = widths.iter().map(|&w| "-".repeat(w)).collect();
+        writeln!(f, "{}", rule.join("-+-"))?;
+        for row in &rendered {
+            line(f, row)?;
+        }
+        Ok(())
+    }
+}
+
+// Returns each record with the line it started on.
+fn parse_csv(text: &str) -> Result<Vec<(usize, Vec<String>)>, TableError> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    // The current field was quoted and its closing quote has been seen.
+    let mut quoted = false;
+    let mut quote_start = (0, 0);
+    // How many records at the end are blank lines.
+    let mut trailing_blanks = 0;
+    let mut line = 1;
+    let mut column = 0;
+    let mut record_line = 1;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        column += 1;
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    column += 1;
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    field.push(c);
+                    line += 1;
+                    column = 0;
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        let ends_field = c == ',' || c == '\n' || (c == '\r' && chars.peek() == Some(&'\n'));
+        if quoted && !ends_field {
+            return Err(TableError::Csv {
+                line,
+                column,
+                message: String::from("text after the closing quote of a field"),
+            });
+        }
+
+        match c {
+            '"' if field.is_empty() => {
+                in_quotes = true;
+                quoted = true;
+                quote_start = (line, column);
+            }
+            '"' => {
+                return Err(TableError::Csv {
+                    line,
+                    column,
+                    message: String::from("quote in the middle of an unquoted field"),
+                })
+            }
+            ',' => {
+                fields.push(std::mem::take(&mut field));
+                quoted = false;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                let blank = fields.is_empty() && field.is_empty() && !quoted;
+                trailing_blanks = if blank { trailing_blanks + 1 } else { 0 };
+                fields.push(std::mem::take(&mut field));
+                records.push((record_line, std::mem::take(&mut fields)));
+                quoted = false;
+                line += 1;
+                column = 0;
+                record_line = line;
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(TableError::Csv {
+            line: quote_start.0,
+            column: quote_start.1,
+            message: String::from("quoted field is never closed"),
+        });
+    }
+    if !field.is_empty() || !fields.is_empty() || quoted {
+        fields.push(field);
+        records.push((record_line, fields));
+        trailing_blanks = 0;
+    }
+
+    // Blank lines at the end are dropped. Anywhere else a blank line is a record with one empty
+    // field, which is real data in a one-column table.
+    records.truncate(records.len() - trailing_blanks);
+    Ok(records)
+}
+
+fn write_record<'a, I: Iterator<Item = &'a str>>(csv: &mut String, fields: I) {
+    let mut count = 0;
+    let mut empty = false;
+    for (i, field) in fields.enumerate() {
+        count += 1;
+        empty = field.is_empty();
+        if i > 0 {
+            csv.push(',');
+        }
+        if field.contains([',', '"', '\n', '\r']) || field.trim() != field {
+            csv.push('"');
+            csv.push_str(&field.replace('"', "\"\""));
+            csv.push('"');
+        } else {
+            csv.push_str(field);
+        }
+    }
+    // A record of one empty field would otherwise be written as a blank line.
+    if count == 1 && empty {
+        csv.push_str("\"\"");
+    }
+    csv.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn csv_error(text: &str) -> (usize, usize, String) {
+        match Table::from_csv(text) {
+            Err(TableError::Csv { line, column, message }) => (line, column, message),
+            other => panic!("expected a CSV error for {:?}, got {:?}", text, other),
+        }
+    }
+
+    #[test]
+    fn text_after_a_closing_quote_is_an_error() {
+        let (line, column, message) = csv_error("name\n\"abc\"def\n");
+        assert_eq!((line, column), (2, 6));
+        assert_eq!(message, "text after the closing quote of a field");
+        assert_eq!(csv_error("a,b\n\"x\" ,y\n").0, 2);
+        assert_eq!(csv_error("a\n\"\"x\n").1, 3);
+        assert_eq!(csv_error("a,b\n\"x\"\"y\"z,1").1, 7);
+
+        let table = Table::from_csv("a,b\r\n\"x, \"\"y\"\"\",\"\"\r\n\"z\",1").unwrap();
+        assert_eq!(table.rows[0].get(0), &Cell::Text(String::from("x, \"y\"")));
+        assert_eq!(table.rows[0].get(1), &Cell::Empty);
+        assert_eq!(table.rows[1].get(1), &Cell::Int(1));
+    }
+
+    #[test]
+    fn blank_lines_are_data_except_at_the_end() {
+        let table = Table::from_csv("note\nfirst\n\nthird\n\n\n").unwrap();
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.rows[1].get(0), &Cell::Empty);
+
+        // With more than one column a blank line in the middle is a short record.
+        assert_eq!(csv_error("a,b\n1,2\n\n3,4\n").0, 3);
+        assert_eq!(Table::from_csv("a,b\n1,2\n\n").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn one_column_tables_round_trip() {
+        let mut table = Table::new(vec![String::from("note")]);
+        for cell in [Cell::Empty, Cell::Text(String::from("x")), Cell::Empty] {
+            table.push(Row::new(vec![cell])).unwrap();
+        }
+        let csv = table.to_csv();
+        assert_eq!(csv, "note\n\"\"\nx\n\"\"\n");
+        assert_eq!(Table::from_csv(&csv).unwrap(), table);
+        assert_eq!(Table::from_csv("note\n\"\"").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn ints_and_floats_compare_exactly() {
+        let big = 1i64 << 53;
+        assert_eq!(Cell::Int(big + 1).compare(&Cell::Float(big as f64)), Ordering::Greater);
+        assert_eq!(Cell::Float(big as f64).compare(&Cell::Int(big + 1)), Ordering::Less);
+        assert_eq!(Cell::Int(big).compare(&Cell::Float(big as f64)), Ordering::Equal);
+        assert_eq!(Cell::Int(i64::MAX).compare(&Cell::Float(i64::MAX as f64)), Ordering::Less);
+        assert_eq!(Cell::Int(i64::MIN).compare(&Cell::Float(i64::MIN as f64)), Ordering::Equal);
+        assert_eq!(Cell::Int(i64::MIN).compare(&Cell::Float(-1e300)), Ordering::Greater);
+
+        assert_eq!(Cell::Int(2).compare(&Cell::Float(2.5)), Ordering::Less);
+        assert_eq!(Cell::Int(-2).compare(&Cell::Float(-2.5)), Ordering::Greater);
+        assert_eq!(Cell::Int(0).compare(&Cell::Float(-0.5)), Ordering::Greater);
+        assert_eq!(Cell::Int(0).compare(&Cell::Float(-0.0)), Ordering::Equal);
+        assert_eq!(Cell::Int(3).compare(&Cell::Float(3.0)), Ordering::Equal);
+        assert_eq!(Cell::Int(i64::MAX).compare(&Cell::Float(f64::INFINITY)), Ordering::Less);
+        assert_eq!(Cell::Int(i64::MIN).compare(&Cell::Float(f64::NEG_INFINITY)), Ordering::Greater);
+        assert_eq!(Cell::Int(0).compare(&Cell::Float(f64::NAN)), Ordering::Less);
+        assert_eq!(Cell::Int(0).compare(&Cell::Float(-f64::NAN)), Ordering::Greater);
+
+        let table = Table::from_csv("n\n9007199254740993\n9007199254740992.0\n2").unwrap();
+        assert_eq!(table.filter_eq("n", &Cell::Float(9_007_199_254_740_992.0)).unwrap().len(), 1);
+        assert_eq!(table.filter_eq("n", &Cell::Int(2)).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn floats_keep_their_decimal_point() {
+        assert_eq!(Cell::Float(1.0).to_string(), "1.0");
+        assert_eq!(Cell::Float(-0.0).to_string(), "-0.0");
+        assert_eq!(Cell::Float(2.5).to_string(), "2.5");
+        assert_eq!(Cell::Float(1e20).to_string(), "100000000000000000000.0");
+
+        let mut table = Table::new(vec![String::from("x")]);
+        for cell in [Cell::Float(1.0), Cell::Int(1), Cell::Float(0.1), Cell::Float(-3e15)] {
+            table.push(Row::new(vec![cell])).unwrap();
+        }
+        let csv = table.to_csv();
+        assert_eq!(csv, "x\n1.0\n1\n0.1\n-3000000000000000.0\n");
+        assert_eq!(Table::from_csv(&csv).unwrap(), table);
+    }
+}
@@ -0,0 +1,619 @@
+// Templates filled in at runtime, for text that people edit without recompiling.
+//
+// Chapter 8's `format!("{s1}-{s2}-{s3}")` only works with names the compiler can see. A Template
+// is parsed from ordinary text instead, so receipts and emails can live in files:
+//
+//     Dear {name},
+//     {#if items}Your order:
+//     {#each items}  - {title} x{quantity}
+//     {/each}{#else}Your basket is empty.
+//     {/if}Delivery: {delivery|standard}
+//
+// `{name}` is replaced by a value, `{name|text}` falls back to `text` when `name` is missing,
+// `{{` and `}}` are literal braces, `{#if name}` / `{#if not name}` ... `{#else}` ... `{/if}`
+// choose between blocks, and `{#each name}` ... `{/each}` repeats a block for every item in a
+// list. Inside a loop, names are looked up on the current item first, `{.}` is the item itself,
+// and `a.b` looks up `b` inside `a`. Errors carry the line and column of the tag at fault.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// The data a template is rendered with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Text(String),
+    Bool(bool),
+    List(Vec<Value>),
+    Map(BTreeMap<String, Value>),
+}
+
+impl Value {
+    /// An empty map, ready for `with`.
+    pub fn map() -> Value {
+        Value::Map(BTreeMap::new())
+    }
+
+    /// Adds a field to a map, for building contexts inline. Does nothing to other values.
+    pub fn with<V: ToValue>(mut self, name: &str, value: V) -> Value {
+        if let Value::Map(fields) = &mut self {
+            fields.insert(String::from(name), value.to_value());
+        }
+        self
+    }
+
+    fn get(&self, name: &str) -> Option<&Value> {
+        match self {
+            Value::Map(fields) => fields.get(name),
+            _ => None,
+        }
+    }
+
+    // Missing values are false as well, so templates can test for optional fields.
+    fn is_truthy(&self) -> bool {
+        match self {
+            Value::Text(text) => !text.is_empty(),
+            Value::Bool(b) => *b,
+            Value::List(items) => !items.is_empty(),
+            Value::Map(_) => true,
+        }
+    }
+}
+
+/// Turns a Rust value into template data. Implement it for your own structs to render them.
+pub trait ToValue {
+    fn to_value(&self) -> Value;
+}
+
+impl ToValue for Value {
+    fn to_value(&self) -> Value {
+        self.clone()
+    }
+}
+
+impl ToValue for str {
+    fn to_value(&self) -> Value {
+        Value::Text(String::from(self))
+    }
+}
+
+impl ToValue for String {
+    fn to_value(&self) -> Value {
+        Value::Text(self.clone())
+    }
+}
+
+impl ToValue for bool {
+    fn to_value(&self) -> Value {
+        Value::Bool(*self)
+    }
+}
+
+macro_rules! to_value_with_display {
+    ($($t:ty),*) => {
+        $(
+            impl ToValue for $t {
+                fn to_value(&self) -> Value {
+                    Value::Text(self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+to_value_with_display!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64, char);
+
+impl<T: ToValue + ?Sized> ToValue for &T {
+    fn to_value(&self) -> Value {
+        (**self).to_value()
+    }
+}
+
+impl<T: ToValue> ToValue for [T] {
+    fn to_value(&self) -> Value {
+        Value::List(self.iter().map(ToValue::to_value).collect())
+    }
+}
+
+impl<T: ToValue> ToValue for Vec<T> {
+    fn to_value(&self) -> Value {
+        self.as_slice().to_value()
+    }
+}
+
+impl<T: ToValue> ToValue for HashMap<String, T> {
+    fn to_value(&self) -> Value {
+        Value::Map(self.iter().map(|(k, v)| (k.clone(), v.to_value())).collect())
+    }
+}
+
+impl<T: ToValue> ToValue for BTreeMap<String, T> {
+    fn to_value(&self) -> Value {
+        Value::Map(self.iter().map(|(k, v)| (k.clone(), v.to_value())).collect())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    MissingVariable(String),
+    /// A list or map used where text was expected.
+    NotText(String),
+    NotAList(String),
+    BadName(String),
+    UnknownTag(String),
+    /// A `{` with no matching `}`.
+    UnclosedPlaceholder,
+    /// A lone `}`; literal braces are written `}}`.
+    UnmatchedBrace,
+    /// `{#if}` or `{#each}` without its closing tag.
+    UnclosedBlock(String),
+    /// `{/if}`, `{/each}` or `{#else}` in the wrong place.
+    UnexpectedTag(String),
+}
+
+/// Line and column are 1-based and point at the `{` of the tag involved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ErrorKind,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match &self.kind {
+            ErrorKind::MissingVariable(name) => {
+                write!(f, "no value for '{}' (use {{{}|...}} to give a default)", name, name)
+            }
+            ErrorKind::NotText(name) => write!(f, "'{}' is a list or group, not text", name),
+            ErrorKind::NotAList(name) => write!(f, "'{}' is not a list, so it can't be used with #each", name),
+            ErrorKind::BadName(name) => write!(f, "'{}' is not a valid name", name),
+            ErrorKind::UnknownTag(tag) => write!(f, "unknown tag '{{{}}}'", tag),
+            ErrorKind::UnclosedPlaceholder => write!(f, "'{{' is never closed (write {{{{ for a literal brace)"),
+            ErrorKind::UnmatchedBrace => write!(f, "unmatched '}}' (write }}}} for a literal brace)"),
+            ErrorKind::UnclosedBlock(tag) => write!(f, "'{{#{}}}' has no matching '{{/{}}}'", tag, tag),
+            ErrorKind::UnexpectedTag(tag) => write!(f, "'{{{}}}' doesn't match an open block", tag),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Position {
+    line: usize,
+    column: usize,
+}
+
+impl Position {
+    fn error(self, kind: ErrorKind) -> TemplateError {
+        TemplateError {
+            line: self.line,
+            column: self.column,
+            kind,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Variable {
+        name: String,
+        default: Option<String>,
+        at: Position,
+    },
+    If {
+        name: String,
+        negated: bool,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    Each {
+        name: String,
+        body: Vec<Node>,
+        at: Position,
+    },
+}
+
+// A tag as written, before blocks are matched up.
+enum Token {
+    Text(String),
+    Variable(String, Option<String>),
+    If(String, bool),
+    Else,
+    EndIf,
+    Each(String),
+    EndEach,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Template, TemplateError> {
+        let tokens = tokenize(source)?;
+        let mut tokens = tokens.into_iter();
+        let (nodes, end) = parse_block(&mut tokens, None)?;
+        match end {
+            None => Ok(Template { nodes }),
+            Some((tag, at)) => Err(at.error(ErrorKind::UnexpectedTag(tag))),
+        }
+    }
+
+    pub fn render<V: ToValue + ?Sized>(&self, context: &V) -> Result<String, TemplateError> {
+        let root = context.to_value();
+        let mut scopes = vec![&root];
+        let mut out = String::new();
+        render_nodes(&self.nodes, &mut scopes, &mut out)?;
+        Ok(out)
+    }
+}
+
+/// Parses and renders in one go.
+pub fn render<V: ToValue + ?Sized>(source: &str, context: &V) -> Result<String, TemplateError> {
+    Template::parse(source)?.render(context)
+}
+
+fn tokenize(source: &str) -> Result<Vec<(Token, Position)>, TemplateError> {
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut text_at = Position { line: 1, column: 1 };
+    let mut pos = Position { line: 1, column: 1 };
+    let mut chars = source.chars().peekable();
+
+    let advance = |pos: &mut Position, c: char| {
+        if c == '\n' {
+            pos.line += 1;
+            pos.column = 1;
+        } else {
+            pos.column += 1;
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        let at = pos;
+        advance(&mut pos, c);
+
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                advance(&mut pos, '{');
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                advance(&mut pos, '}');
+                text.push('}');
+            }
+            '}' => return Err(at.error(ErrorKind::UnmatchedBrace)),
+            '{' => {
+                let mut tag = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => {
+                            advance(&mut pos, '}');
+                            break;
+                        }
+                        Some(c) => {
+                            advance(&mut pos, c);
+                            tag.push(c);
+                        }
+                        None => return Err(at.error(ErrorKind::UnclosedPlaceholder)),
+                    }
+                }
+                if !text.is_empty() {
+                    tokens.push((Token::Text(std::mem::take(&mut text)), text_at));
+                }
+                tokens.push((parse_tag(&tag, at)?, at));
+                text_at = pos;
+            }
+            _ => {
+                if text.is_empty() {
+                    text_at = at;
+                }
+                text.push(c);
+            }
+        }
+    }
+
+    if !text.is_empty() {
+        tokens.push((Token::Text(text), text_at));
+    }
+    Ok(tokens)
+}
+
+fn parse_tag(tag: &str, at: Position) -> Result<Token, TemplateError> {
+    let trimmed = tag.trim();
+    let name = |name: &str| -> Result<String, TemplateError> {
+        let name = name.trim();
+        let valid = name == "."
+            || (!name.is_empty()
+                && name
+                    .split('.')
+                    .all(|part| !part.is_empty() && part.chars().all(|c| c.is_alphanumeric() || c == '_')));
+        if valid {
+            Ok(String::from(name))
+        } else {
+            Err(at.error(ErrorKind::BadName(String::from(name))))
+        }
+    };
+
+    if let Some(block) = trimmed.strip_prefix('#') {
+        let (keyword, rest) = block.split_once(char::is_whitespace).unwrap_or((block, ""));
+        return match keyword {
+            "if" => match rest.trim().strip_prefix("not ") {
+                Some(negated) => Ok(Token::If(name(negated)?, true)),
+                None => Ok(Token::If(name(rest)?, false)),
+            },
+            "each" => Ok(Token::Each(name(rest)?)),
+            "else" if rest.trim().is_empty() => Ok(Token::Else),
+            _ => Err(at.error(ErrorKind::UnknownTag(String::from(trimmed)))),
+        };
+    }
+
+    match trimmed {
+        "/if" => return Ok(Token::EndIf),
+        "/each" => return Ok(Token::EndEach),
+        _ if trimmed.starts_with('/') => return Err(at.error(ErrorKind::UnknownTag(String::from(trimmed)))),
+        _ => {}
+    }
+
+    // The default is kept exactly as written, spaces included.
+    match tag.split_once('|') {
+        Some((variable, default)) => Ok(Token::Variable(name(variable)?, Some(String::from(default)))),
+        None => Ok(Token::Variable(name(tag)?, None)),
+    }
+}
+
+type Tokens = std::vec::IntoIter<(Token, Position)>;
+
+// The closing tag that ended a block, if any.
+type Closing = Option<(String, Position)>;
+
+// Parses nodes until the end of the input or a closing tag, which is returned so the caller can
+// check it matches. `open` is the block being parsed, for the unclosed-block error.
+fn parse_block(
+    tokens: &mut Tokens,
+    open: Option<(&str, Position)>,
+) -> Result<(Vec<Node>, Closing), TemplateError> {
+    let mut nodes = Vec::new();
+
+    while let Some((token, at)) = tokens.next() {
+        match token {
+            Token::Text(text) => nodes.push(Node::Text(text)),
+            Token::Variable(name, default) => nodes.push(Node::Variable { name, default, at }),
+            Token::If(name, negated) => {
+                let (then, end) = parse_block(tokens, Some(("if", at)))?;
+                let otherwise = match end {
+                    Some((tag, _)) if tag == "/if" => Vec::new(),
+                    Some((tag, _)) if tag == "#else" => match parse_block(tokens, Some(("if", at)))? {
+                        (otherwise, Some((tag, _))) if tag == "/if" => otherwise,
+                        (_, Some((tag, at))) => return Err(at.error(ErrorKind::UnexpectedTag(tag))),
+                        (_, None) => unreachable!("parse_block reports unclosed blocks"),
+                    },
+                    Some((tag, at)) => return Err(at.error(ErrorKind::UnexpectedTag(tag))),
+                    None => unreachable!("parse_block reports unclosed blocks"),
+                };
+                nodes.push(Node::If {
+                    name,
+                    negated,
+                    then,
+                    otherwise,
+                });
+            }
+            Token::Each(name) => match parse_block(tokens, Some(("each", at)))? {
+                (body, Some((tag, _))) if tag == "/each" => nodes.push(Node::Each { name, body, at }),
+                (_, Some((tag, at))) => return Err(at.error(ErrorKind::UnexpectedTag(tag))),
+                (_, None) => unreachable!("parse_block reports unclosed blocks"),
+            },
+            Token::Else => return Ok((nodes, Some((String::from("#else"), at)))),
+            Token::EndIf => return Ok((nodes, Some((String::from("/if"), at)))),
+            Token::EndEach => return Ok((nodes, Some((String::from("/each"), at)))),
+        }
+    }
+
+    match open {
+        Some((tag, at)) => Err(at.error(ErrorKind::UnclosedBlock(String::from(tag)))),
+        None => Ok((nodes, None)),
+    }
+}
+
+// Looks a dotted name up, innermost scope first.
+fn lookup<'a>(scopes: &[&'a Value], name: &str) -> Option<&'a Value> {
+    if name == "." {
+        return scopes.last().copied();
+    }
+    let mut parts = name.split('.');
+    let first = parts.next()?;
+    let mut value = scopes.iter().rev().find_map(|scope| scope.get(first))?;
+    for part in parts {
+        value = value.get(part)?;
+    }
+    Some(value)
+}
+
+fn render_nodes(nodes: &[Node], scopes: &mut Vec<&Value>, out: &mut String) -> Result<(), TemplateError> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Variable { name, default, at } => match (lookup(scopes, name), default) {
+                (Some(Value::Text(text)), _) => out.push_str(text),
+                (Some(Value::Bool(b)), _) => out.push_str(if *b { "true" } else { "false" }),
+                (Some(_), _) => return Err(at.error(ErrorKind::NotText(name.clone()))),
+                (None, Some(default)) => out.push_str(default),
+                (None, None) => return Err(at.error(ErrorKind::MissingVariable(name.clone()))),
+            },
+            Node::If {
+                name,
+                negated,
+                then,
+                otherwise,
+            } => {
+                let truthy = lookup(scopes, name).is_some_and(Value::is_truthy);
+                let branch = if truthy != *negated { then } else { otherwise };
+                render_nodes(branch, scopes, out)?;
+            }
+            Node::Each { name, body, at } => {
+                let items = match lookup(scopes, name) {
+                    Some(Value::List(items)) => items,
+                    Some(_) => return Err(at.error(ErrorKind::NotAList(name.clone()))),
+                    None => return Err(at.error(ErrorKind::MissingVariable(name.clone()))),
+                };
+                for item in items {
+                    scopes.push(item);
+                    let result = render_nodes(body, scopes, out);
+                    scopes.pop();
+                    result?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_at(source: &str, context: &Value) -> (usize, usize, ErrorKind) {
+        let e = render(source, context).unwrap_err();
+        (e.line, e.column, e.kind)
+    }
+
+    fn order() -> Value {
+        let items = vec![
+            Value::map().with("title", "Tea").with("quantity", 2),
+            Value::map().with("title", "Scones").with("quantity", 6),
+        ];
+        Value::map()
+            .with("name", "Ann")
+            .with("items", items)
+            .with("address", Value::map().with("city", "Leeds"))
+    }
+
+    #[test]
+    fn variables_and_defaults() {
+        let context = order();
+        assert_eq!(render("Dear {name},", &context).unwrap(), "Dear Ann,");
+        assert_eq!(render("{ name }", &context).unwrap(), "Ann");
+        assert_eq!(render("{address.city}", &context).unwrap(), "Leeds");
+        assert_eq!(render("{delivery|standard}", &context).unwrap(), "standard");
+        assert_eq!(render("{delivery| next day }", &context).unwrap(), " next day ");
+        assert_eq!(render("{delivery|}", &context).unwrap(), "");
+        assert_eq!(render("{name|nobody}", &context).unwrap(), "Ann");
+        assert_eq!(render("{address.street|?}", &context).unwrap(), "?");
+        assert_eq!(render("{on}/{off}", &Value::map().with("on", true).with("off", false)).unwrap(), "true/false");
+        assert_eq!(render("no tags", &context).unwrap(), "no tags");
+        assert_eq!(render("", &context).unwrap(), "");
+
+        let mut fields = HashMap::new();
+        fields.insert(String::from("x"), 1.5);
+        assert_eq!(render("x = {x}", &fields).unwrap(), "x = 1.5");
+    }
+
+    #[test]
+    fn conditionals_and_loops_nest() {
+        let source = "{#if items}Your order:\n{#each items}  - {title} x{quantity}{#if big} (big){/if} for {name}\n{/each}{#else}Your basket is empty.\n{/if}";
+        assert_eq!(
+            render(source, &order()).unwrap(),
+            "Your order:\n  - Tea x2 for Ann\n  - Scones x6 for Ann\n"
+        );
+        let empty = Value::map().with("items", Vec::<Value>::new());
+        assert_eq!(render(source, &empty).unwrap(), "Your basket is empty.\n");
+        // A missing list is false for #if.
+        assert_eq!(render(source, &Value::map()).unwrap(), "Your basket is empty.\n");
+
+        assert_eq!(render("{#if not name}anon{#else}{name}{/if}", &order()).unwrap(), "Ann");
+        assert_eq!(render("{#if not name}anon{/if}", &Value::map()).unwrap(), "anon");
+        assert_eq!(render("{#if a}{#if b}ab{#else}a{/if}{#else}-{/if}", &Value::map().with("a", true)).unwrap(), "a");
+
+        let grid = Value::map().with("rows", vec![vec!["a", "b"], vec!["c"]]);
+        assert_eq!(render("{#each rows}[{#each .}{.}{/each}]{/each}", &grid).unwrap(), "[ab][c]");
+        assert_eq!(render("{#each xs}{.},{/each}", &Value::map().with("xs", [1, 2, 3].as_slice())).unwrap(), "1,2,3,");
+    }
+
+    #[test]
+    fn literal_braces() {
+        let context = order();
+        assert_eq!(render("{{name}}", &context).unwrap(), "{name}");
+        assert_eq!(render("{{{name}}}", &context).unwrap(), "{Ann}");
+        assert_eq!(render("fn main() {{ }}", &context).unwrap(), "fn main() { }");
+        assert_eq!(render("{{#if}}", &context).unwrap(), "{#if}");
+        assert_eq!(error_at("}}}", &context), (1, 3, ErrorKind::UnmatchedBrace));
+    }
+
+    #[test]
+    fn syntax_errors_point_at_the_tag() {
+        let context = order();
+        assert_eq!(error_at("a\n  {name", &context), (2, 3, ErrorKind::UnclosedPlaceholder));
+        assert_eq!(error_at("ab}", &context), (1, 3, ErrorKind::UnmatchedBrace));
+        assert_eq!(error_at("é{x", &context), (1, 2, ErrorKind::UnclosedPlaceholder));
+        assert_eq!(
+            error_at("x\n{#if name}hi", &context),
+            (2, 1, ErrorKind::UnclosedBlock(String::from("if")))
+        );
+        assert_eq!(
+            error_at("{#each items}{#if name}{/each}", &context),
+            (1, 24, ErrorKind::UnexpectedTag(String::from("/each")))
+        );
+        assert_eq!(
+            error_at("{#each items}{/if}{/each}", &context),
+            (1, 14, ErrorKind::UnexpectedTag(String::from("/if")))
+        );
+        assert_eq!(
+            error_at("{#each items}{#else}{/each}", &context),
+            (1, 14, ErrorKind::UnexpectedTag(String::from("#else")))
+        );
+        assert_eq!(
+            error_at("{#if name}{#else}{#else}{/if}", &context),
+            (1, 18, ErrorKind::UnexpectedTag(String::from("#else")))
+        );
+        assert_eq!(error_at("ok {/if}", &context), (1, 4, ErrorKind::UnexpectedTag(String::from("/if"))));
+        assert_eq!(error_at("{#else}", &context), (1, 1, ErrorKind::UnexpectedTag(String::from("#else"))));
+        assert_eq!(error_at("{#loop xs}", &context), (1, 1, ErrorKind::UnknownTag(String::from("#loop xs"))));
+        assert_eq!(error_at("{/loop}", &context), (1, 1, ErrorKind::UnknownTag(String::from("/loop"))));
+        assert_eq!(error_at("{#else x}", &context), (1, 1, ErrorKind::UnknownTag(String::from("#else x"))));
+        assert_eq!(error_at("{first name}", &context), (1, 1, ErrorKind::BadName(String::from("first name"))));
+        assert_eq!(error_at("{}", &context), (1, 1, ErrorKind::BadName(String::new())));
+        assert_eq!(error_at("{a..b}", &context), (1, 1, ErrorKind::BadName(String::from("a..b"))));
+        assert_eq!(error_at("{#if }", &context), (1, 1, ErrorKind::BadName(String::new())));
+        // Syntax errors are found before rendering, whatever the data.
+        assert!(Template::parse("{#if x}{x}").is_err());
+    }
+
+    #[test]
+    fn render_errors_point_at_the_tag() {
+        let context = order();
+        assert_eq!(
+            error_at("Hi\n {nickname}", &context),
+            (2, 2, ErrorKind::MissingVariable(String::from("nickname")))
+        );
+        assert_eq!(error_at("{items}", &context), (1, 1, ErrorKind::NotText(String::from("items"))));
+        assert_eq!(
+            error_at("  {#each name}{/each}", &context),
+            (1, 3, ErrorKind::NotAList(String::from("name")))
+        );
+        assert_eq!(
+            error_at("{#each items}{colour}{/each}", &context),
+            (1, 14, ErrorKind::MissingVariable(String::from("colour")))
+        );
+        assert_eq!(
+            render("{nickname}", &context).unwrap_err().to_string(),
+            "line 1, column 1: no value for 'nickname' (use {nickname|...} to give a default)"
+        );
+        assert_eq!(
+            render("{#each x}", &context).unwrap_err().to_string(),
+            "line 1, column 1: '{#each}' has no matching '{/each}'"
+        );
+    }
+
+    #[test]
+    fn parse_once_render_many() {
+        let template = Template::parse("{n}! ").unwrap();
+        let out: String = (1..=3).map(|n| template.render(&Value::map().with("n", n)).unwrap()).collect();
+        assert_eq!(out, "1! 2! 3! ");
+    }
+}
@@ -0,0 +1,315 @@
+// Text transformations built on chapter 8's strings: Pig Latin, ROT13 and Caesar shifts, case
+// conversions and reversal.
+//
+// Everything works on chars rather than bytes, so non-ASCII text passes through intact. Letters
+// outside the Latin script are left alone by the transforms that only make sense for Latin.
+
+use crate::grapheme::{self, GraphemeBreak};
+
+/// Lowercase vowels, including the accented Latin ones, so "éclair" starts with a vowel.
+const VOWELS: &str = "aeiouàáâãäåæèéêëìíîïòóôõöøùúûüýÿāăąēĕėęěīĭįıōŏőœūŭůűų";
+
+fn is_vowel(c: char) -> bool {
+    c.to_lowercase().all(|lower| VOWELS.contains(lower))
+}
+
+// Basic Latin, Latin-1 Supplement and Latin Extended-A/B letters.
+fn is_latin(c: char) -> bool {
+    c.is_ascii_alphabetic() || (c.is_alphabetic() && ('\u{C0}'..='\u{24F}').contains(&c))
+}
+
+// Combining marks like the accent in "e\u{301}", which belong to the letter before them.
+fn is_mark(c: char) -> bool {
+    !c.is_ascii() && grapheme::grapheme_break(c) == GraphemeBreak::Extend
+}
+
+fn is_apostrophe(c: char) -> bool {
+    c == '\'' || c == '’'
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Case {
+    Lower,
+    Capitalized,
+    Upper,
+}
+
+impl Case {
+    fn of(word: &str) -> Case {
+        let mut letters = word.chars().filter(|c| c.is_alphabetic());
+        let first_upper = letters.next().is_some_and(char::is_uppercase);
+        let rest: Vec<char> = letters.collect();
+        if first_upper && !rest.is_empty() && rest.iter().all(|c| c.is_uppercase()) {
+            Case::Upper
+        } else if first_upper {
+            Case::Capitalized
+        } else {
+            Case::Lower
+        }
+    }
+
+    fn apply(self, word: &str) -> String {
+        match self {
+            Case::Lower => word.to_lowercase(),
+            Case::Upper => word.to_uppercase(),
+            Case::Capitalized => capitalize(&word.to_lowercase()),
+        }
+    }
+}
+
+/// Uppercases the first character and leaves the rest alone.
+pub fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Translates every word to Pig Latin and leaves punctuation and spacing where it was.
+///
+/// Words starting with a vowel get "hay" on the end ("apple" → "applehay"). Otherwise the leading
+/// consonants move to the end before "ay" ("string" → "ingstray"), with "qu" kept together
+/// ("queen" → "eenquay") and "y" counted as a vowel after the first letter ("rhythm" →
+/// "ythmrhay"). The word keeps its capitalization: "Hello" → "Ellohay", "NASA" → "ASANAY".
+pub fn pig_latin(text: &str) -> String {
+    map_words(text, pig_latin_word)
+}
+
+fn pig_latin_word(word: &str) -> String {
+    if !word.chars().all(|c| is_latin(c) || is_apostrophe(c) || is_mark(c)) {
+        return String::from(word);
+    }
+
+    let case = Case::of(word);
+    let lower = word.to_lowercase();
+    let chars: Vec<char> = lower.chars().collect();
+
+    let mut split = 0;
+    while split < chars.len() {
+        let c = chars[split];
+        if is_vowel(c) || (c == 'y' && split > 0) {
+            break;
+        }
+        if c == 'q' && chars.get(split + 1) == Some(&'u') {
+            split += 2;
+            break;
+        }
+        split += 1;
+    }
+
+    let translated = if split == 0 {
+        format!("{}hay", lower)
+    } else {
+        let head: String = chars[..split].iter().collect();
+        let tail: String = chars[split..].iter().collect();
+        format!("{}{}ay", tail, head)
+    };
+    case.apply(&translated)
+}
+
+// Runs `f` over each word (letters and their combining marks, plus apostrophes inside a word like
+// "don't") and copies everything else through unchanged.
+fn map_words<F: Fn(&str) -> String>(text: &str, f: F) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut word_start = None;
+
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    for (i, &(offset, c)) in chars.iter().enumerate() {
+        let inner_apostrophe = is_apostrophe(c)
+            && word_start.is_some()
+            && chars.get(i + 1).is_some_and(|&(_, next)| next.is_alphabetic());
+
+        if c.is_alphabetic() || inner_apostrophe || (word_start.is_some() && is_mark(c)) {
+            word_start.get_or_insert(offset);
+            continue;
+        }
+        if let Some(start) = word_start.take() {
+            result.push_str(&f(&text[start..offset]));
+        }
+        result.push(c);
+    }
+    if let Some(start) = word_start {
+        result.push_str(&f(&text[start..]));
+    }
+
+    result
+}
+
+/// Shifts ASCII letters `shift` places round the alphabet. Negative shifts go backwards, and
+/// anything that isn't an ASCII letter is left as it is.
+pub fn caesar(text: &str, shift: i32) -> String {
+    let shift = shift.rem_euclid(26) as u8;
+    text.chars()
+        .map(|c| {
+            let base = match c {
+                'a'..='z' => b'a',
+                'A'..='Z' => b'A',
+                _ => return c,
+            };
+            (base + (c as u8 - base + shift) % 26) as char
+        })
+        .collect()
+}
+
+/// Caesar with a shift of 13, so applying it twice gives back the original.
+pub fn rot13(text: &str) -> String {
+    caesar(text, 13)
+}
+
+/// Splits identifiers and phrases into words for the case conversions. Breaks on anything that
+/// isn't a letter or digit, on lower-to-upper changes ("camelCase"), and at the end of an
+/// acronym ("HTTPServer" → "HTTP", "Server").
+pub fn split_words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let chars: Vec<char> = text.chars().collect();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+
+        if let Some(&prev) = i.checked_sub(1).and_then(|p| chars.get(p)) {
+            let next = chars.get(i + 1).copied();
+            let lower_to_upper = c.is_uppercase() && (prev.is_lowercase() || prev.is_numeric());
+            let acronym_end = c.is_uppercase()
+                && prev.is_uppercase()
+                && next.is_some_and(char::is_lowercase);
+            if !current.is_empty() && (lower_to_upper || acronym_end) {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+}
+
+/// "Hello World" → "hello_world"
+pub fn to_snake_case(text: &str) -> String {
+    join_lowercase(text, "_")
+}
+
+/// "Hello World" → "hello-world"
+pub fn to_kebab_case(text: &str) -> String {
+    join_lowercase(text, "-")
+}
+
+/// "hello_world" → "helloWorld"
+pub fn to_camel_case(text: &str) -> String {
+    split_words(text)
+        .iter()
+        .enumerate()
+        .map(|(i, word)| {
+            let lower = word.to_lowercase();
+            if i == 0 {
+                lower
+            } else {
+                capitalize(&lower)
+            }
+        })
+        .collect()
+}
+
+/// "hello_world" → "Hello World"
+pub fn to_title_case(text: &str) -> String {
+    split_words(text)
+        .iter()
+        .map(|word| capitalize(&word.to_lowercase()))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn join_lowercase(text: &str, separator: &str) -> String {
+    split_words(text)
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<String>>()
+        .join(separator)
+}
+
+/// Reverses the text a grapheme at a time, so accents stay on their letters and emoji stay in
+/// one piece. Plain `chars().rev()` would turn "é" written as 'e' + U+0301 into the accent
+/// followed by the 'e'.
+pub fn reverse(text: &str) -> String {
+    let clusters: Vec<&str> = grapheme::graphemes(text).collect();
+    clusters.into_iter().rev().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pig_latin_words() {
+        assert_eq!(pig_latin("apple string queen rhythm"), "applehay ingstray eenquay ythmrhay");
+        assert_eq!(pig_latin("Hello, NASA!"), "Ellohay, ASANAY!");
+        assert_eq!(pig_latin("éclair"), "éclairhay");
+        assert_eq!(pig_latin("  two\twords\n"), "  otway\tordsway\n");
+        assert_eq!(pig_latin("Привет 123"), "Привет 123");
+        assert_eq!(pig_latin(""), "");
+    }
+
+    #[test]
+    fn pig_latin_apostrophes() {
+        assert_eq!(pig_latin("don't"), "on'tday");
+        assert_eq!(pig_latin("don’t"), "on’tday");
+        assert_eq!(pig_latin("Don’t stop"), "On’tday opstay");
+        // Quotes around a word aren't part of it.
+        assert_eq!(pig_latin("'quoted' ‘curly’"), "'otedquay' ‘urlycay’");
+    }
+
+    #[test]
+    fn pig_latin_keeps_combining_marks_in_the_word() {
+        assert_eq!(pig_latin("cafe\u{301}"), "afe\u{301}cay");
+        assert_eq!(pig_latin("cafe\u{301} au lait"), "afe\u{301}cay auhay aitlay");
+        assert_eq!(pig_latin("c\u{327}a"), "ac\u{327}ay");
+        // A mark with no letter before it isn't a word.
+        assert_eq!(pig_latin("\u{301}ab"), "\u{301}abhay");
+    }
+
+    #[test]
+    fn caesar_shifts() {
+        assert_eq!(caesar("Hello, World!", 3), "Khoor, Zruog!");
+        assert_eq!(caesar("Khoor, Zruog!", -3), "Hello, World!");
+        assert_eq!(caesar("xyz XYZ", 3), "abc ABC");
+        assert_eq!(caesar("abc", 26), "abc");
+        assert_eq!(caesar("abc", -27), "zab");
+        assert_eq!(caesar("abc", i32::MIN), caesar("abc", i32::MIN.rem_euclid(26)));
+        assert_eq!(caesar("é 🦀 1", 5), "é 🦀 1");
+        assert_eq!(rot13("Why did the chicken"), "Jul qvq gur puvpxra");
+        assert_eq!(rot13(&rot13("Round trip")), "Round trip");
+    }
+
+    #[test]
+    fn case_conversions() {
+        assert_eq!(split_words("HTTPServer camelCase snake_case v2Final"), [
+            "HTTP", "Server", "camel", "Case", "snake", "case", "v2", "Final"
+        ]);
+        assert_eq!(to_snake_case("Hello World"), "hello_world");
+        assert_eq!(to_snake_case("parseHTTPResponse"), "parse_http_response");
+        assert_eq!(to_kebab_case("Hello World"), "hello-world");
+        assert_eq!(to_camel_case("hello_world"), "helloWorld");
+        assert_eq!(to_camel_case("XML http request"), "xmlHttpRequest");
+        assert_eq!(to_title_case("hello_world"), "Hello World");
+        assert_eq!(to_title_case("élan vital"), "Élan Vital");
+        assert_eq!(to_snake_case("--"), "");
+        assert_eq!(capitalize("ßtraße"), "SStraße");
+    }
+
+    #[test]
+    fn reverse_keeps_graphemes_whole() {
+        assert_eq!(reverse("hello"), "olleh");
+        assert_eq!(reverse("cafe\u{301}"), "e\u{301}fac");
+        assert_eq!(reverse("a👨‍👩‍👧b"), "b👨‍👩‍👧a");
+        assert_eq!(reverse("🇫🇷🇩🇪"), "🇩🇪🇫🇷");
+        assert_eq!(reverse(""), "");
+    }
+}
@@ -0,0 +1,511 @@
+// Word counting from chapter 8.3, grown into a small text analyzer.
+//
+// word_counts is the chapter's split_whitespace and entry().or_insert(0) loop. Analyzer does the
+// same job for real text: it takes runs of letters, digits and their combining marks as words and
+// drops the punctuation between them (see `tokens`), folds case, skips stop words, counts n-grams,
+// and reads input a line or a bounded chunk at a time so the input can be far larger than memory.
+// Only the counts are kept, in maps hashed with FxHasher by default since SipHash is most of the
+// cost of counting short words; see hashing.rs.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::hash::BuildHasher;
+use std::io::{self, BufRead};
+use std::path::Path;
+
+use crate::grapheme::{self, GraphemeBreak};
+use crate::hashing::{FxBuildHasher, FxHashSet};
+
+/// Counts how often each whitespace-separated word appears.
+pub fn word_counts(text: &str) -> HashMap<&str, i32> {
+    let mut map = HashMap::new();
+
+    for word in text.split_whitespace() {
+        let count = map.entry(word).or_insert(0);
+        *count += 1;
+    }
+
+    map
+}
+
+// Letters, digits and the combining marks that belong to them, so "naïve" written with a
+// combining diaeresis and Devanagari vowel signs stay inside their words.
+fn is_word_char(c: char) -> bool {
+    if c.is_ascii() {
+        return c.is_ascii_alphanumeric();
+    }
+    c.is_alphanumeric()
+        || matches!(
+            grapheme::grapheme_break(c),
+            GraphemeBreak::Extend | GraphemeBreak::SpacingMark | GraphemeBreak::Zwj
+        )
+}
+
+/// Splits text into words, dropping whitespace and punctuation. An apostrophe or hyphen between
+/// two word characters stays in the word, so "don't" and "well-known" are one token each.
+pub fn tokens(text: &str) -> Tokens<'_> {
+    Tokens { text, offset: 0 }
+}
+
+pub struct Tokens<'a> {
+    text: &'a str,
+    offset: usize,
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let rest = &self.text[self.offset..];
+        let start = rest.find(is_word_char)?;
+
+        let mut end = start;
+        let mut chars = rest[start..].char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            let joiner = matches!(c, '\'' | '’' | '-' | '‐')
+                && chars.peek().is_some_and(|&(_, next)| is_word_char(next));
+            if !is_word_char(c) && !joiner {
+                break;
+            }
+            end = start + i + c.len_utf8();
+        }
+
+        self.offset += end;
+        Some(&rest[start..end])
+    }
+}
+
+/// Lowercases for comparison, plus the case folds that lowercasing misses: "Straße" and
+/// "STRASSE" both fold to "strasse", and a final sigma matches a medial one.
+pub fn fold_case(word: &str) -> String {
+    if word.is_ascii() {
+        return word.to_ascii_lowercase();
+    }
+    let lower = word.to_lowercase();
+    if lower.contains(['ß', 'ς']) {
+        lower.replace('ß', "ss").replace('ς', "σ")
+    } else {
+        lower
+    }
+}
+
+/// Words to leave out of the counts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StopWords {
+    words: FxHashSet<String>,
+}
+
+const ENGLISH_STOP_WORDS: &str = "a about above after again against all am an and any are as at be
+because been before being below between both but by can could did do does doing down during each
+few for from further had has have having he her here hers herself him himself his how i if in into
+is it its itself just me more most my myself no nor not now of off on once only or other our ours
+ourselves out over own same she should so some such than that the their theirs them themselves
+then there these they this those through to too under until up very was we were what when where
+which while who whom why will with would you your yours yourself yourselves";
+
+impl StopWords {
+    pub fn none() -> StopWords {
+        StopWords::default()
+    }
+
+    /// A short list of common English function words.
+    pub fn english() -> StopWords {
+        StopWords::parse(ENGLISH_STOP_WORDS)
+    }
+
+    /// Words separated by whitespace. Lines starting with '#' are comments.
+    pub fn parse(list: &str) -> StopWords {
+        let words = list
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('#'))
+            .flat_map(str::split_whitespace)
+            .map(fold_case)
+            .collect();
+        StopWords { words }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<StopWords> {
+        Ok(StopWords::parse(&fs::read_to_string(path)?))
+    }
+
+    /// Ignores case, like the list itself: "The" is a stop word if "the" is.
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(word) || self.words.contains(&fold_case(word))
+    }
+
+    // For a word that has already been through fold_case.
+    fn contains_folded(&self, folded: &str) -> bool {
+        self.words.contains(folded)
+    }
+
+    pub fn insert(&mut self, word: &str) {
+        self.words.insert(fold_case(word));
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub fold_case: bool,
+    pub stop_words: StopWords,
+    /// Words shorter than this many chars are skipped.
+    pub min_length: usize,
+    /// Which n-grams to count, e.g. `vec![2, 3]` for bigrams and trigrams. Sizes below 2 are
+    /// ignored since single words are always counted.
+    pub ngrams: Vec<usize>,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            fold_case: true,
+            stop_words: StopWords::english(),
+            min_length: 1,
+            ngrams: Vec::new(),
+        }
+    }
+}
+
+// The most `add_reader` holds at once when a line goes on and on.
+const READ_CHUNK: usize = 64 * 1024;
+
+/// Word or n-gram counts, hashed with `S`.
+pub type Counts<S = FxBuildHasher> = HashMap<String, u64, S>;
+
+/// Accumulates word and n-gram counts from any amount of text.
+///
+/// N-grams are taken from the words that survive filtering, joined with single spaces, and run
+/// on across line breaks and between calls to `add_text`, as if everything fed in were one text.
+///
+/// `S` is the hasher for the count maps. `Analyzer::new` uses FxHasher; `with_hasher` takes any
+/// other, such as `hashing::SeededState` for the same iteration order in every run or
+/// `RandomState` for std's default.
+#[derive(Debug, Clone)]
+pub struct Analyzer<S = FxBuildHasher> {
+    options: Options,
+    words: Counts<S>,
+    ngrams: HashMap<usize, Counts<S>, S>,
+    total: u64,
+    // The last few words, for n-grams that continue into the next line.
+    window: VecDeque<String>,
+}
+
+impl Analyzer {
+    pub fn new(options: Options) -> Analyzer {
+        Analyzer::with_hasher(options, FxBuildHasher::default())
+    }
+}
+
+impl<S: BuildHasher + Clone> Analyzer<S> {
+    pub fn with_hasher(options: Options, hasher: S) -> Analyzer<S> {
+        let mut ngrams = HashMap::with_hasher(hasher.clone());
+        for &n in options.ngrams.iter().filter(|&&n| n >= 2) {
+            ngrams.insert(n, HashMap::with_hasher(hasher.clone()));
+        }
+        Analyzer {
+            options,
+            words: HashMap::with_hasher(hasher),
+            ngrams,
+            total: 0,
+            window: VecDeque::new(),
+        }
+    }
+
+    pub fn add_text(&mut self, text: &str) {
+        let longest = self.ngrams.keys().copied().max().unwrap_or(0);
+        let mut word = String::new();
+        let mut gram = String::new();
+
+        for token in tokens(text) {
+            if token.chars().count() < self.options.min_length {
+                continue;
+            }
+            // Folded into a reused buffer, so only words seen for the first time allocate.
+            word.clear();
+            if !self.options.fold_case {
+                word.push_str(token);
+            } else if token.is_ascii() {
+                word.push_str(token);
+                word.make_ascii_lowercase();
+            } else {
+                word.push_str(&fold_case(token));
+            }
+            let stop = if self.options.fold_case {
+                self.options.stop_words.contains_folded(&word)
+            } else {
+                self.options.stop_words.contains(&word)
+            };
+            if stop {
+                continue;
+            }
+
+            self.total += 1;
+            match self.words.get_mut(word.as_str()) {
+                Some(count) => *count += 1,
+                None => {
+                    self.words.insert(word.clone(), 1);
+                }
+            }
+
+            if longest == 0 {
+                continue;
+            }
+            self.window.push_back(word.clone());
+            if self.window.len() > longest {
+                self.window.pop_front();
+            }
+            for (&n, counts) in self.ngrams.iter_mut() {
+                if self.window.len() < n {
+                    continue;
+                }
+                gram.clear();
+                for word in self.window.iter().skip(self.window.len() - n) {
+                    if !gram.is_empty() {
+                        gram.push(' ');
+                    }
+                    gram.push_str(word);
+                }
+                match counts.get_mut(gram.as_str()) {
+                    Some(count) => *count += 1,
+                    None => {
+                        counts.insert(gram.clone(), 1);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Reads to the end a line at a time, so memory use doesn't grow with the input. A line longer
+    /// than 64 KiB is taken in pieces split at whitespace, so a file without line breaks doesn't
+    /// have to fit in memory either; only a single word longer than that gets cut in two. Bytes
+    /// that aren't valid UTF-8 are replaced rather than stopping the read.
+    pub fn add_reader<R: BufRead>(&mut self, mut reader: R) -> io::Result<()> {
+        let mut chunk = Vec::new();
+        loop {
+            let available = match reader.fill_buf() {
+                Ok(available) => available,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            if available.is_empty() {
+                self.add_text(&String::from_utf8_lossy(&chunk));
+                return Ok(());
+            }
+
+            let room = READ_CHUNK - chunk.len();
+            let (used, line_end) = match available.iter().take(room).position(|&b| b == b'\n') {
+                Some(newline) => (newline + 1, true),
+                None => (available.len().min(room), false),
+            };
+            chunk.extend_from_slice(&available[..used]);
+            reader.consume(used);
+
+            if line_end {
+                self.add_text(&String::from_utf8_lossy(&chunk));
+                chunk.clear();
+            } else if chunk.len() == READ_CHUNK {
+                // Up to the last whitespace, or failing that the start of the last char, and
+                // keep the rest for the next piece.
+                let split = match chunk.iter().rposition(u8::is_ascii_whitespace) {
+                    Some(space) => space + 1,
+                    None => chunk.iter().rposition(|&b| b & 0xC0 != 0x80).filter(|&i| i > 0).unwrap_or(READ_CHUNK),
+                };
+                self.add_text(&String::from_utf8_lossy(&chunk[..split]));
+                chunk.drain(..split);
+            }
+        }
+    }
+
+    pub fn add_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let file = fs::File::open(path)?;
+        self.add_reader(io::BufReader::new(file))
+    }
+
+    /// Words counted, not including stop words.
+    pub fn total_words(&self) -> u64 {
+        self.total
+    }
+
+    pub fn distinct_words(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn count(&self, word: &str) -> u64 {
+        let key = if self.options.fold_case {
+            fold_case(word)
+        } else {
+            String::from(word)
+        };
+        self.words.get(&key).copied().unwrap_or(0)
+    }
+
+    pub fn word_counts(&self) -> &Counts<S> {
+        &self.words
+    }
+
+    /// The `n` most frequent words, most frequent first. Ties are in alphabetical order so the
+    /// output is the same from run to run.
+    pub fn top(&self, n: usize) -> Vec<(&str, u64)> {
+        top_of(&self.words, n)
+    }
+
+    /// Like `top`, for n-grams of `size` words. Empty unless `size` was in `Options::ngrams`.
+    pub fn top_ngrams(&self, size: usize, n: usize) -> Vec<(&str, u64)> {
+        match self.ngrams.get(&size) {
+            Some(counts) => top_of(counts, n),
+            None => Vec::new(),
+        }
+    }
+}
+
+fn top_of<S>(counts: &Counts<S>, n: usize) -> Vec<(&str, u64)> {
+    let mut sorted: Vec<(&str, u64)> = counts.iter().map(|(word, &count)| (word.as_str(), count)).collect();
+    sorted.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    sorted.truncate(n);
+    sorted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufReader, Cursor};
+
+    fn analyze(text: &str, options: Options) -> Analyzer {
+        let mut analyzer = Analyzer::new(options);
+        analyzer.add_text(text);
+        analyzer
+    }
+
+    #[test]
+    fn chapter_word_counts() {
+        let map = word_counts("hello world wonderful world");
+        assert_eq!(map.get("world"), Some(&2));
+        assert_eq!(map.get("hello"), Some(&1));
+        assert_eq!(map.len(), 3);
+    }
+
+    #[test]
+    fn tokens_drop_punctuation() {
+        let all: Vec<&str> = tokens("Don't panic -- it's a well-known, 'quoted' fact... x2 -y- 'tis").collect();
+        assert_eq!(all, ["Don't", "panic", "it's", "a", "well-known", "quoted", "fact", "x2", "y", "tis"]);
+        assert_eq!(tokens("na\u{308}ive नमस्ते!").collect::<Vec<_>>(), ["na\u{308}ive", "नमस्ते"]);
+        assert_eq!(tokens("l’homme").collect::<Vec<_>>(), ["l’homme"]);
+        assert_eq!(tokens("  ... ").count(), 0);
+        assert_eq!(tokens("").count(), 0);
+    }
+
+    #[test]
+    fn case_folding() {
+        assert_eq!(fold_case("Hello"), "hello");
+        assert_eq!(fold_case("Straße"), fold_case("STRASSE"));
+        assert_eq!(fold_case("ΣΟΦΟΣ"), fold_case("σοφος"));
+        assert_eq!(fold_case("ÉCOLE"), "école");
+    }
+
+    #[test]
+    fn stop_words_ignore_case_whether_or_not_the_analyzer_folds() {
+        let stop = StopWords::english();
+        assert!(stop.contains("the") && stop.contains("The") && stop.contains("THE"));
+        assert!(!stop.contains("cat"));
+
+        let folded = analyze("The cat and THE hat", Options::default());
+        assert_eq!(folded.top(10), [("cat", 1), ("hat", 1)]);
+
+        let options = Options {
+            fold_case: false,
+            ..Options::default()
+        };
+        let kept = analyze("The Cat and THE cat", options);
+        assert_eq!(kept.top(10), [("Cat", 1), ("cat", 1)]);
+        assert_eq!(kept.total_words(), 2);
+
+        let mut custom = StopWords::parse("# comment line\nFoo bar\n  # another\nBAZ");
+        custom.insert("Qux");
+        assert_eq!(custom.len(), 4);
+        assert!(custom.contains("foo") && custom.contains("baz") && custom.contains("QUX"));
+        assert!(!custom.contains("comment"));
+        assert!(StopWords::none().is_empty());
+    }
+
+    #[test]
+    fn counts_top_and_min_length() {
+        let options = Options {
+            min_length: 3,
+            stop_words: StopWords::none(),
+            ..Options::default()
+        };
+        let analyzer = analyze("to be or not to be, the bee said to the sea", options);
+        assert_eq!(analyzer.top(3), [("the", 2), ("bee", 1), ("not", 1)]);
+        assert_eq!(analyzer.count("THE"), 2);
+        assert_eq!(analyzer.count("to"), 0);
+        assert_eq!(analyzer.total_words(), 6);
+        assert_eq!(analyzer.distinct_words(), 5);
+        assert_eq!(analyzer.top(0), []);
+    }
+
+    #[test]
+    fn ngrams_run_on_across_calls() {
+        let options = Options {
+            ngrams: vec![1, 2, 3],
+            ..Options::default()
+        };
+        let mut analyzer = Analyzer::new(options);
+        analyzer.add_text("Quick brown");
+        analyzer.add_text("fox, the quick brown fox!");
+        assert_eq!(analyzer.top_ngrams(2, 2), [("brown fox", 2), ("quick brown", 2)]);
+        // "the" is a stop word, so the trigram joins across it.
+        assert_eq!(
+            analyzer.top_ngrams(3, 10),
+            [("quick brown fox", 2), ("brown fox quick", 1), ("fox quick brown", 1)]
+        );
+        assert_eq!(analyzer.top_ngrams(1, 10), []);
+        assert_eq!(analyzer.top_ngrams(4, 10), []);
+    }
+
+    #[test]
+    fn readers_take_lines_and_replace_bad_bytes() {
+        let mut analyzer = Analyzer::new(Options::default());
+        analyzer.add_reader(Cursor::new(&b"caf\xE9 one\r\ntwo one\nlast"[..])).unwrap();
+        assert_eq!(analyzer.count("one"), 2);
+        assert_eq!(analyzer.count("caf"), 1);
+        assert_eq!(analyzer.count("last"), 1);
+        assert_eq!(analyzer.total_words(), 5);
+    }
+
+    #[test]
+    fn long_lines_are_read_in_pieces_without_cutting_words() {
+        let line = "alpha beta gämma δέλτα ".repeat(READ_CHUNK / 7);
+        assert!(line.len() > 3 * READ_CHUNK);
+        for capacity in [1, 7, 4096, 3 * READ_CHUNK] {
+            let mut analyzer = Analyzer::new(Options::default());
+            analyzer.add_reader(BufReader::with_capacity(capacity, line.as_bytes())).unwrap();
+            let expected = (READ_CHUNK / 7) as u64;
+            for word in ["alpha", "beta", "gämma", "δέλτα"] {
+                assert_eq!(analyzer.count(word), expected, "{} with capacity {}", word, capacity);
+            }
+            assert_eq!(analyzer.distinct_words(), 4);
+        }
+
+        // One word too long for a piece is the only thing that gets cut, and never inside a char.
+        let word = "é".repeat(READ_CHUNK);
+        let mut analyzer = Analyzer::new(Options::default());
+        analyzer.add_reader(word.as_bytes()).unwrap();
+        assert!(analyzer.total_words() > 1);
+        let chars: usize = analyzer
+            .word_counts()
+            .iter()
+            .map(|(piece, &count)| {
+                assert!(piece.chars().all(|c| c == 'é'), "{:?}", piece);
+                piece.chars().count() * count as usize
+            })
+            .sum();
+        assert_eq!(chars, READ_CHUNK);
+    }
+}
@@ -3,9 +3,12 @@ use crate::front_of_house::hosting as front_host;
 pub mod front_of_house {
     pub mod hosting {
         pub fn add_to_waitlist(){}
+        #[allow(dead_code)]
         fn seat_at_table(){}
     }
 
+    // The book never calls these; they only show what private functions look like.
+    #[allow(dead_code)]
     mod serving {
         fn take_order(){}
         fn serve_order(){}
@@ -21,13 +24,14 @@ pub fn eat_at_restaurant(){
     let mut meal = back_of_house::Breakfast::summer("Rye");
     meal.toast = String::from("Wheat");
     println!("I'd like {} toast please", meal.toast);
-    let order1 = back_of_house::Appetizer::Soup;
-    let order2 = back_of_house::Appetizer::Salad;
+    let _order1 = back_of_house::Appetizer::Soup;
+    let _order2 = back_of_house::Appetizer::Salad;
 }
 
 mod back_of_house {
     pub struct Breakfast {
         pub toast: String,
+        #[allow(dead_code)]
         seasonal_fruit: String,
     }
 
@@ -0,0 +1,477 @@
+// Exercise checker for the chapter crates.
+//
+// Every Chapter-* directory with an exercises.txt gets built and run, and its output is checked
+// against the exercises declared there (see Chapter-8/exercises.txt for the format). The latest
+// result for each exercise is kept in .exercise-progress in the repository root so the summary can
+// show what changed since the last run.
+//
+//     cargo run --bin check                 check every chapter
+//     cargo run --bin check -- Chapter-8    check the named chapters only
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const EXERCISES_FILE: &str = "exercises.txt";
+const PROGRESS_FILE: &str = ".exercise-progress";
+
+#[derive(Debug)]
+enum Check {
+    /// Lines that must appear one after another in the output.
+    Lines(Vec<String>),
+    Contains(String),
+    Absent(String),
+    LineCount(usize),
+    ExitCode(i32),
+}
+
+#[derive(Debug)]
+struct Exercise {
+    name: String,
+    description: String,
+    checks: Vec<Check>,
+}
+
+#[derive(Debug)]
+struct ParseError {
+    line: usize,
+    message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+fn parse_exercises(text: &str) -> Result<Vec<Exercise>, ParseError> {
+    let mut exercises: Vec<Exercise> = Vec::new();
+    let mut in_block = false;
+
+    for (i, raw) in text.lines().enumerate() {
+        let error = |message: &str| ParseError {
+            line: i + 1,
+            message: String::from(message),
+        };
+        let line = raw.trim_end();
+
+        if line.trim().is_empty() || line.starts_with('#') {
+            in_block = false;
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix('[') {
+            let (name, description) = header.split_once(']').ok_or_else(|| error("missing ']'"))?;
+            exercises.push(Exercise {
+                name: String::from(name.trim()),
+                description: String::from(description.trim()),
+                checks: Vec::new(),
+            });
+            in_block = false;
+            continue;
+        }
+
+        let exercise = exercises
+            .last_mut()
+            .ok_or_else(|| error("checks must come after an [exercise] header"))?;
+
+        if let Some(expected) = line.strip_prefix('>') {
+            let expected = String::from(expected.strip_prefix(' ').unwrap_or(expected));
+            match exercise.checks.last_mut() {
+                Some(Check::Lines(lines)) if in_block => lines.push(expected),
+                _ => exercise.checks.push(Check::Lines(vec![expected])),
+            }
+            in_block = true;
+            continue;
+        }
+        in_block = false;
+
+        let (key, value) = line.split_once(':').ok_or_else(|| error("expected 'key: value'"))?;
+        let value = value.trim();
+        let check = match key.trim() {
+            "contains" => Check::Contains(String::from(value)),
+            "absent" => Check::Absent(String::from(value)),
+            "lines" => Check::LineCount(value.parse().map_err(|_| error("lines needs a number"))?),
+            "exit" => Check::ExitCode(value.parse().map_err(|_| error("exit needs a number"))?),
+            other => return Err(error(&format!("unknown check '{}'", other))),
+        };
+        exercise.checks.push(check);
+    }
+
+    Ok(exercises)
+}
+
+struct RunOutput {
+    stdout: String,
+    stderr: String,
+    exit_code: Option<i32>,
+}
+
+fn build_and_run(chapter: &Path) -> io::Result<Result<RunOutput, String>> {
+    let cargo = env::var("CARGO").unwrap_or_else(|_| String::from("cargo"));
+
+    let build = Command::new(&cargo)
+        .current_dir(chapter)
+        .args(["build", "--quiet"])
+        .output()?;
+    if !build.status.success() {
+        return Ok(Err(String::from_utf8_lossy(&build.stderr).into_owned()));
+    }
+
+    // Run from the chapter directory, since some chapters open files like hello.txt.
+    let run = Command::new(&cargo)
+        .current_dir(chapter)
+        .args(["run", "--quiet"])
+        .output()?;
+
+    Ok(Ok(RunOutput {
+        stdout: String::from_utf8_lossy(&run.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&run.stderr).into_owned(),
+        exit_code: run.status.code(),
+    }))
+}
+
+/// Returns a list of problems, empty when the exercise passes.
+fn check_exercise(exercise: &Exercise, output: &RunOutput) -> Vec<String> {
+    let lines: Vec<&str> = output.stdout.lines().collect();
+    let mut problems = Vec::new();
+
+    for check in &exercise.checks {
+        match check {
+            Check::Lines(expected) => {
+                if let Some(diff) = diff_block(expected, &lines) {
+                    problems.push(diff);
+                }
+            }
+            Check::Contains(text) => {
+                if !lines.iter().any(|line| line.contains(text.as_str())) {
+                    problems.push(format!("no line contains '{}'", text));
+                }
+            }
+            Check::Absent(text) => {
+                let everything = output.stdout.lines().chain(output.stderr.lines());
+                if let Some(line) = everything.into_iter().find(|line| line.contains(text.as_str())) {
+                    problems.push(format!("unexpected '{}' in: {}", text, line));
+                }
+            }
+            Check::LineCount(count) => {
+                if lines.len() != *count {
+                    problems.push(format!("expected {} lines of output, got {}", count, lines.len()));
+                }
+            }
+            Check::ExitCode(code) => {
+                if output.exit_code != Some(*code) {
+                    let actual = match output.exit_code {
+                        Some(actual) => actual.to_string(),
+                        None => String::from("a signal"),
+                    };
+                    problems.push(format!("expected exit code {}, got {}", code, actual));
+                }
+            }
+        }
+    }
+
+    problems
+}
+
+// Finds the stretch of output that best lines up with the expected block and shows the
+// differences line by line. None means the block appears exactly.
+fn diff_block(expected: &[String], actual: &[&str]) -> Option<String> {
+    let mut best_start = 0;
+    let mut best_matches = 0;
+    for start in 0..actual.len().max(1) {
+        let matches = expected
+            .iter()
+            .enumerate()
+            .filter(|(i, line)| actual.get(start + i) == Some(&line.as_str()))
+            .count();
+        if matches == expected.len() {
+            return None;
+        }
+        if matches > best_matches {
+            best_matches = matches;
+            best_start = start;
+        }
+    }
+
+    let mut diff = String::from("output differs:");
+    for (i, line) in expected.iter().enumerate() {
+        match actual.get(best_start + i) {
+            Some(got) if *got == line => diff.push_str(&format!("\n      {}", got)),
+            Some(got) => diff.push_str(&format!("\n    - {}\n    + {}", line, got)),
+            None => diff.push_str(&format!("\n    - {}", line)),
+        }
+    }
+    Some(diff)
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Progress {
+    /// When each `chapter/exercise` was last checked, and whether it passed.
+    latest: BTreeMap<String, (u64, bool)>,
+}
+
+impl Progress {
+    fn load(path: &Path) -> Progress {
+        Progress::parse(&fs::read_to_string(path).unwrap_or_default())
+    }
+
+    // Each line is `<seconds since epoch> <pass|fail> <chapter>/<exercise>`. Files from before
+    // the checker rewrote them can list an exercise more than once; the later line wins.
+    fn parse(text: &str) -> Progress {
+        let mut progress = Progress::default();
+        for line in text.lines() {
+            let mut parts = line.splitn(3, ' ');
+            let (time, status, key) = match (parts.next(), parts.next(), parts.next()) {
+                (Some(time), Some(status), Some(key)) => (time, status, key),
+                _ => continue,
+            };
+            let time = time.parse().unwrap_or(0);
+            progress.latest.insert(String::from(key), (time, status == "pass"));
+        }
+        progress
+    }
+
+    fn passed(&self, key: &str) -> Option<bool> {
+        self.latest.get(key).map(|&(_, passed)| passed)
+    }
+
+    fn record(&mut self, results: &[(String, bool)], now: u64) {
+        for (key, passed) in results {
+            self.latest.insert(key.clone(), (now, *passed));
+        }
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = String::new();
+        for (key, (time, passed)) in &self.latest {
+            let status = if *passed { "pass" } else { "fail" };
+            text.push_str(&format!("{} {} {}\n", time, status, key));
+        }
+        text
+    }
+
+    /// Replaces the file, so it holds one line per exercise however many runs there have been.
+    fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+}
+
+fn chapters(root: &Path, only: &[String]) -> io::Result<Vec<PathBuf>> {
+    let mut chapters = Vec::new();
+    for entry in fs::read_dir(root)? {
+        let path = entry?.path();
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if !name.starts_with("Chapter-") || !path.join(EXERCISES_FILE).is_file() {
+            continue;
+        }
+        if only.is_empty() || only.iter().any(|o| o == name) {
+            chapters.push(path);
+        }
+    }
+
+    // Chapter-8_2 before Chapter-10_1.
+    chapters.sort_by_key(|path| {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        name.trim_start_matches("Chapter-")
+            .split('_')
+            .map(|part| part.parse::<u32>().unwrap_or(u32::MAX))
+            .collect::<Vec<u32>>()
+    });
+    Ok(chapters)
+}
+
+fn main() {
+    let only: Vec<String> = env::args().skip(1).collect();
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let progress_path = root.join(PROGRESS_FILE);
+    let mut progress = Progress::load(&progress_path);
+
+    let chapters = match chapters(root, &only) {
+        Ok(chapters) if !chapters.is_empty() => chapters,
+        Ok(_) => {
+            eprintln!("No chapters with an {} found.", EXERCISES_FILE);
+            process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Couldn't list chapters: {}", e);
+            process::exit(1);
+        }
+    };
+
+    let mut results: Vec<(String, bool)> = Vec::new();
+    let mut per_chapter: Vec<(String, usize, usize)> = Vec::new();
+
+    for chapter in &chapters {
+        let name = chapter.file_name().and_then(|n| n.to_str()).unwrap_or("?").to_string();
+        println!("{}", name);
+
+        let exercises = match fs::read_to_string(chapter.join(EXERCISES_FILE))
+            .map_err(|e| e.to_string())
+            .and_then(|text| parse_exercises(&text).map_err(|e| e.to_string()))
+        {
+            Ok(exercises) => exercises,
+            Err(e) => {
+                println!("  can't read {}: {}\n", EXERCISES_FILE, e);
+                continue;
+            }
+        };
+
+        let output = match build_and_run(chapter) {
+            Ok(output) => output,
+            Err(e) => {
+                eprintln!("Couldn't run cargo: {}", e);
+                process::exit(1);
+            }
+        };
+
+        let mut passed = 0;
+        for exercise in &exercises {
+            let problems = match &output {
+                Ok(output) => check_exercise(exercise, output),
+                Err(_) => vec![String::from("chapter doesn't build")],
+            };
+
+            let ok = problems.is_empty();
+            println!("  [{}] {:<22} {}", if ok { "pass" } else { "FAIL" }, exercise.name, exercise.description);
+            for problem in &problems {
+                println!("         {}", problem);
+            }
+
+            if ok {
+                passed += 1;
+            }
+            results.push((format!("{}/{}", name, exercise.name), ok));
+        }
+
+        if let Err(stderr) = &output {
+            println!("\n{}", stderr.trim_end());
+        }
+        println!();
+        per_chapter.push((name, passed, exercises.len()));
+    }
+
+    println!("Summary");
+    for (name, passed, total) in &per_chapter {
+        println!("  {:<14} {}/{}", name, passed, total);
+    }
+    let passed = results.iter().filter(|(_, ok)| *ok).count();
+    println!("  {:<14} {}/{}", "total", passed, results.len());
+
+    for (key, ok) in &results {
+        match progress.passed(key) {
+            Some(false) if *ok => println!("  newly passing: {}", key),
+            Some(true) if !*ok => println!("  now failing:   {}", key),
+            _ => {}
+        }
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    progress.record(&results, now);
+    if let Err(e) = progress.save(&progress_path) {
+        eprintln!("Couldn't save progress to {}: {}", progress_path.display(), e);
+    }
+
+    if passed < results.len() {
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error(text: &str) -> (usize, String) {
+        let error = parse_exercises(text).unwrap_err();
+        (error.line, error.message)
+    }
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| String::from(*line)).collect()
+    }
+
+    #[test]
+    fn parses_exercises() {
+        let text = "# comment\n\
+            [first] The first one\n\
+            > one\n\
+            >two\n\
+            >\n\
+            \n\
+            > three\n\
+            contains: thr\n\
+            > four\n\
+            [second]   Checks  \n\
+            absent: panicked\n\
+            lines: 3\n\
+            exit: 101\n";
+        let exercises = parse_exercises(text).unwrap();
+        assert_eq!(exercises.len(), 2);
+        assert_eq!((exercises[0].name.as_str(), exercises[0].description.as_str()), ("first", "The first one"));
+        assert_eq!(exercises[1].description, "Checks");
+
+        // A blank line or another check ends a block of lines.
+        match exercises[0].checks.as_slice() {
+            [Check::Lines(a), Check::Lines(b), Check::Contains(c), Check::Lines(d)] => {
+                assert_eq!(a, &lines(&["one", "two", ""]));
+                assert_eq!(b, &lines(&["three"]));
+                assert_eq!(c, "thr");
+                assert_eq!(d, &lines(&["four"]));
+            }
+            other => panic!("unexpected checks {:?}", other),
+        }
+        assert!(matches!(
+            exercises[1].checks.as_slice(),
+            [Check::Absent(text), Check::LineCount(3), Check::ExitCode(101)] if text == "panicked"
+        ));
+        assert!(parse_exercises("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn exercise_errors() {
+        assert_eq!(parse_error("> orphan\n"), (1, String::from("checks must come after an [exercise] header")));
+        assert_eq!(parse_error("\n[broken\n"), (2, String::from("missing ']'")));
+        assert_eq!(parse_error("[a] A\nwhat\n"), (2, String::from("expected 'key: value'")));
+        assert_eq!(parse_error("[a] A\nmatches: x\n"), (2, String::from("unknown check 'matches'")));
+        assert_eq!(parse_error("[a] A\nlines: many\n"), (2, String::from("lines needs a number")));
+        assert_eq!(parse_error("[a] A\nexit: -\n"), (2, String::from("exit needs a number")));
+    }
+
+    #[test]
+    fn diffs_against_the_closest_stretch() {
+        let output = ["start", "one", "two", "three", "end"];
+        assert_eq!(diff_block(&lines(&["two", "three"]), &output), None);
+        assert_eq!(diff_block(&lines(&["one", "2", "three"]), &output).unwrap(), "output differs:\n      one\n    - 2\n    + two\n      three");
+        // Running off the end of the output.
+        assert_eq!(diff_block(&lines(&["end", "more"]), &output).unwrap(), "output differs:\n      end\n    - more");
+        assert_eq!(diff_block(&lines(&["x"]), &[]).unwrap(), "output differs:\n    - x");
+        assert_eq!(diff_block(&[], &output), None);
+    }
+
+    #[test]
+    fn progress_keeps_the_latest_result_per_exercise() {
+        let mut progress = Progress::parse("10 fail A/one\n20 pass A/one\n15 fail B/two\nbroken\n");
+        assert_eq!(progress.passed("A/one"), Some(true));
+        assert_eq!(progress.passed("B/two"), Some(false));
+        assert_eq!(progress.passed("C/three"), None);
+
+        progress.record(&[(String::from("B/two"), true), (String::from("C/three"), false)], 30);
+        let text = progress.to_text();
+        assert_eq!(text, "20 pass A/one\n30 pass B/two\n30 fail C/three\n");
+        assert_eq!(Progress::parse(&text), progress);
+
+        let path = env::temp_dir().join(format!("exercise-progress-{}", process::id()));
+        progress.save(&path).unwrap();
+        progress.save(&path).unwrap();
+        assert_eq!(Progress::load(&path), progress);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,420 @@
+// Lesson runner for the chapter 4 to 6 walkthrough in main.rs.
+//
+// main.rs keeps the cases that don't compile in comments. Here every case is a named lesson: the
+// runner prints the snippet and an explanation, and then hands the snippet to the local rustc so
+// the real borrow checker message appears next to it. Snippets that do compile are run instead.
+//
+//     cargo run --bin lessons            pick lessons from a menu
+//     cargo run --bin lessons -- moves   run one lesson by name or number
+//     cargo run --bin lessons -- --list  list the lessons
+
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+
+struct Lesson {
+    name: &'static str,
+    title: &'static str,
+    /// Body of `main`.
+    snippet: &'static str,
+    /// Helper items that go outside `main`.
+    items: &'static str,
+    compiles: bool,
+    explanation: &'static str,
+}
+
+const LESSONS: &[Lesson] = &[
+    Lesson {
+        name: "scope",
+        title: "Using a variable after its scope ends",
+        snippet: r#"{
+    let s = "hello";
+}
+println!("{}", s);"#,
+        items: "",
+        compiles: false,
+        explanation: "s only exists inside the braces. Once the scope closes the name is gone, so \
+            the println! after it refers to nothing.",
+    },
+    Lesson {
+        name: "moves",
+        title: "Using a String after it was moved",
+        snippet: r#"let s1 = String::from("Hello");
+let s2 = s1;
+println!("{}, {}", s1, s2);"#,
+        items: "",
+        compiles: false,
+        explanation: "Assigning a String copies the pointer, not the heap data. If s1 and s2 both \
+            stayed valid they would both free the same memory, so Rust treats s1 as moved and \
+            refuses to let you use it again.",
+    },
+    Lesson {
+        name: "clones",
+        title: "Cloning instead of moving",
+        snippet: r#"let s1 = String::from("hello");
+let s2 = s1.clone();
+println!("s1 = {}, s2 = {}", s1, s2);"#,
+        items: "",
+        compiles: true,
+        explanation: "clone copies the heap data too, so s1 and s2 own separate Strings and both \
+            stay valid.",
+    },
+    Lesson {
+        name: "stack-copies",
+        title: "Integers are copied, not moved",
+        snippet: r#"let x = 5;
+let y = x;
+println!("x = {}, y = {}", x, y);"#,
+        items: "",
+        compiles: true,
+        explanation: "Types with a known size that live on the stack implement Copy, so the \
+            assignment duplicates the value and x is still usable.",
+    },
+    Lesson {
+        name: "takes-ownership",
+        title: "Using a value after passing it to a function",
+        snippet: r#"let s = String::from("hello");
+takes_ownership(s);
+println!("{}", s);"#,
+        items: r#"fn takes_ownership(some_string: String) {
+    println!("{}", some_string);
+}"#,
+        compiles: false,
+        explanation: "Passing a String to a function moves it just like an assignment does. The \
+            function frees it when it returns, so s is no longer valid afterwards.",
+    },
+    Lesson {
+        name: "borrowing",
+        title: "Borrowing with a reference",
+        snippet: r#"let s1 = String::from("hello");
+let len = calculate_length(&s1);
+println!("The length of '{}' is {}.", s1, len);"#,
+        items: r#"fn calculate_length(s: &String) -> usize {
+    s.len()
+}"#,
+        compiles: true,
+        explanation: "&s1 lends the String to the function without moving it. The borrower isn't \
+            responsible for freeing it, so s1 is still usable afterwards.",
+    },
+    Lesson {
+        name: "immutable-borrow",
+        title: "Changing something through a shared reference",
+        snippet: r#"let s = String::from("hello");
+change(&s);"#,
+        items: r#"fn change(some_string: &String) {
+    some_string.push_str(", world");
+}"#,
+        compiles: false,
+        explanation: "References are immutable unless you ask otherwise. To change the borrowed \
+            String the function needs &mut String and the caller needs a mut binding.",
+    },
+    Lesson {
+        name: "double-mutable-borrow",
+        title: "Two mutable borrows at the same time",
+        snippet: r#"let mut s = String::from("hello");
+
+let r1 = &mut s;
+let r2 = &mut s;
+
+println!("{}, {}", r1, r2);"#,
+        items: "",
+        compiles: false,
+        explanation: "Only one mutable reference to a value may be live at a time. This rules out \
+            data races at compile time. Borrowing in separate scopes, one after the other, is \
+            fine.",
+    },
+    Lesson {
+        name: "mixed-borrows",
+        title: "A mutable borrow while shared borrows are live",
+        snippet: r#"let mut s = String::from("hello");
+
+let r1 = &s;
+let r2 = &s;
+let r3 = &mut s;
+
+println!("{}, {}, {}", r1, r2, r3);"#,
+        items: "",
+        compiles: false,
+        explanation: "Any number of shared references is fine, but not while someone can also \
+            change the value. r1 and r2 are still used after r3 is created, so their borrows \
+            overlap.",
+    },
+    Lesson {
+        name: "dangling",
+        title: "Returning a reference to a local String",
+        snippet: r#"let reference_to_nothing = dangle();"#,
+        items: r#"fn dangle() -> &String {
+    let s = String::from("hello");
+    &s
+}"#,
+        compiles: false,
+        explanation: "s is dropped when dangle returns, so the reference would point at freed \
+            memory. Rust asks for a lifetime it can't satisfy. Returning the String itself moves \
+            ownership out instead.",
+    },
+    Lesson {
+        name: "slices",
+        title: "String slices",
+        snippet: r#"let s = String::from("hello world");
+let hello = &s[..5];
+let world = &s[6..];
+println!("{}:{}", hello, world);"#,
+        items: "",
+        compiles: true,
+        explanation: "A slice borrows part of a String without owning it. The ranges are byte \
+            offsets, which is why slicing in the middle of a multi-byte character panics.",
+    },
+    Lesson {
+        name: "struct-update",
+        title: "Struct update syntax moves fields",
+        snippet: r#"let user1 = User {
+    active: true,
+    username: String::from("someusername123"),
+    email: String::from("someone@example.com"),
+};
+let user2 = User {
+    email: String::from("another@example.com"),
+    ..user1
+};
+println!("{} {}", user1.username, user2.email);"#,
+        items: r#"struct User {
+    active: bool,
+    username: String,
+    email: String,
+}"#,
+        compiles: false,
+        explanation: "..user1 moves username into user2 because String isn't Copy. user1.active \
+            could still be used, but user1.username can't.",
+    },
+    Lesson {
+        name: "match-exhaustive",
+        title: "A match that misses a variant",
+        snippet: r#"println!("{}", value_in_cents(Coin::Dime));"#,
+        items: r#"enum Coin {
+    Penny,
+    Nickel,
+    Dime,
+    Quarter,
+}
+
+fn value_in_cents(coin: Coin) -> u8 {
+    match coin {
+        Coin::Penny => 1,
+        Coin::Nickel => 5,
+        Coin::Dime => 10,
+    }
+}"#,
+        compiles: false,
+        explanation: "match has to cover every possible value. Forgetting Coin::Quarter is a \
+            compile error instead of a bug found at run time.",
+    },
+    Lesson {
+        name: "option",
+        title: "Matching on Option<T>",
+        snippet: r#"let five = Some(5);
+let six = plus_one(five);
+let none = plus_one(None);
+println!("{:?} {:?}", six, none);"#,
+        items: r#"fn plus_one(x: Option<i32>) -> Option<i32> {
+    match x {
+        None => None,
+        Some(i) => Some(i + 1),
+    }
+}"#,
+        compiles: true,
+        explanation: "Option makes the missing case part of the type, and match makes you handle \
+            it. Watch out: i + 1 still panics in debug builds when i is i32::MAX.",
+    },
+];
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match args.first().map(|arg| arg.as_str()) {
+        Some("--list") => list(),
+        Some(choice) => match find(choice) {
+            Some(lesson) => run(lesson),
+            None => {
+                eprintln!("No lesson called '{}'. Try --list.", choice);
+                process::exit(1);
+            }
+        },
+        None => menu(),
+    }
+}
+
+fn list() {
+    for (i, lesson) in LESSONS.iter().enumerate() {
+        let marker = if lesson.compiles { " " } else { "x" };
+        println!("{:>2}. [{}] {:<22} {}", i + 1, marker, lesson.name, lesson.title);
+    }
+    println!("\n[x] marks lessons that don't compile.");
+}
+
+fn find(choice: &str) -> Option<&'static Lesson> {
+    if let Ok(number) = choice.parse::<usize>() {
+        return number.checked_sub(1).and_then(|i| LESSONS.get(i));
+    }
+    LESSONS.iter().find(|lesson| lesson.name == choice)
+}
+
+fn menu() {
+    let stdin = io::stdin();
+    loop {
+        list();
+        print!("\nPick a lesson (number or name, q to quit): ");
+        io::stdout().flush().expect("stdout should be writable");
+
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap_or(0) == 0 {
+            break;
+        }
+
+        let choice = line.trim();
+        if choice == "q" || choice == "quit" {
+            break;
+        }
+        match find(choice) {
+            Some(lesson) => run(lesson),
+            None => println!("No lesson called '{}'.", choice),
+        }
+        println!();
+    }
+}
+
+fn run(lesson: &Lesson) {
+    println!("\n== {} ==\n", lesson.title);
+    for line in lesson.items.lines() {
+        println!("    {}", line);
+    }
+    if !lesson.items.is_empty() {
+        println!();
+    }
+    for line in lesson.snippet.lines() {
+        println!("    {}", line);
+    }
+    println!();
+
+    match compile(lesson) {
+        Ok(result) => show(lesson, &result),
+        Err(e) => println!("(Couldn't run rustc: {}. Is it on your PATH?)\n", e),
+    }
+
+    println!("Why: {}", lesson.explanation);
+}
+
+struct CompileResult {
+    succeeded: bool,
+    diagnostics: String,
+    /// Program output when it compiled and ran.
+    output: Option<String>,
+}
+
+fn show(lesson: &Lesson, result: &CompileResult) {
+    match (lesson.compiles, result.succeeded) {
+        (false, false) => {
+            println!("rustc says:\n");
+            println!("{}", result.diagnostics.trim_end());
+            println!();
+        }
+        (true, true) => {
+            println!("Output:\n");
+            for line in result.output.as_deref().unwrap_or("").lines() {
+                println!("    {}", line);
+            }
+            println!();
+        }
+        // The lesson text is out of date with the compiler. Say so rather than hide it.
+        (expected, _) => {
+            println!(
+                "Expected this to {}compile, but rustc disagreed:\n",
+                if expected { "" } else { "not " }
+            );
+            println!("{}", result.diagnostics.trim_end());
+            println!();
+        }
+    }
+}
+
+fn compile(lesson: &Lesson) -> io::Result<CompileResult> {
+    // One directory per lesson, so lessons compiled side by side in the tests don't share one.
+    let dir = env::temp_dir().join(format!("lessons-{}-{}", process::id(), lesson.name));
+    fs::create_dir_all(&dir)?;
+    let result = compile_in(&dir, lesson);
+    let _ = fs::remove_dir_all(&dir);
+    result
+}
+
+fn compile_in(dir: &Path, lesson: &Lesson) -> io::Result<CompileResult> {
+    let file_name = format!("{}.rs", lesson.name.replace('-', "_"));
+    let mut source = String::new();
+    if !lesson.items.is_empty() {
+        source.push_str(lesson.items);
+        source.push_str("\n\n");
+    }
+    source.push_str("fn main() {\n");
+    for line in lesson.snippet.lines() {
+        source.push_str("    ");
+        source.push_str(line);
+        source.push('\n');
+    }
+    source.push_str("}\n");
+    fs::write(dir.join(&file_name), source)?;
+
+    let exe: PathBuf = dir.join(format!("{}{}", lesson.name, env::consts::EXE_SUFFIX));
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| String::from("rustc"));
+    let compiled = Command::new(rustc)
+        .current_dir(dir)
+        .args(["--edition", "2021", "--color", "never", "-A", "unused", "-o"])
+        .arg(&exe)
+        .arg(&file_name)
+        .output()?;
+
+    let diagnostics = String::from_utf8_lossy(&compiled.stderr).into_owned();
+    if !compiled.status.success() {
+        return Ok(CompileResult {
+            succeeded: false,
+            diagnostics,
+            output: None,
+        });
+    }
+
+    let ran = Command::new(&exe).output()?;
+    Ok(CompileResult {
+        succeeded: true,
+        diagnostics,
+        output: Some(String::from_utf8_lossy(&ran.stdout).into_owned()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Keeps the compiles flags honest as rustc changes.
+    #[test]
+    fn every_lesson_compiles_or_not_as_it_says() {
+        for lesson in LESSONS {
+            let result = compile(lesson).expect("rustc should run");
+            assert_eq!(
+                result.succeeded, lesson.compiles,
+                "lesson '{}':\n{}",
+                lesson.name, result.diagnostics
+            );
+            if lesson.compiles {
+                assert!(result.output.is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn lessons_are_found_by_name_or_number() {
+        assert_eq!(find("moves").map(|lesson| lesson.name), Some("moves"));
+        assert_eq!(find("1").map(|lesson| lesson.name), Some(LESSONS[0].name));
+        assert!(find("0").is_none());
+        assert!(find(&(LESSONS.len() + 1).to_string()).is_none());
+        assert!(find("nope").is_none());
+    }
+}
@@ -10,7 +10,7 @@ fn main() {
     }
     
     {
-        let s = "hello"; // String literal inside a specific scope. 
+        let _s = "hello"; // String literal inside a specific scope. 
         
     }
     // println!("{}", s); // <---This is no longer in scope and will cause an error. 
@@ -38,7 +38,7 @@ fn main() {
     */
     let s = String::from("Before Inside Scope");
     {
-        let s = String::from("Hello Inside Scope"); 
+        let _s = String::from("Hello Inside Scope"); 
     }
     // s inside the braces is deallocated automatically once the scope is over. 
     println!("{}", s);
@@ -103,15 +103,16 @@ fn main() {
     println!("\n\n Return Values and Scopes --------------------------------------\n");
 
         {
-            let s1 = gives_ownership();
+            let _s1 = gives_ownership();
             let s2 = String::from("hello");
-            let s3 = takes_and_gives_back(s2);
+            let _s3 = takes_and_gives_back(s2);
 
             // println!("{}", s2); // s2 is no longer valid.
 
             // s2 is moved into takes_and_gives_back and the moved into s3
         }
 
+        #[allow(clippy::let_and_return)]
         fn gives_ownership() -> String {
             let some_string = String::from("yours");
             some_string
@@ -147,6 +148,8 @@ fn main() {
             println!("The length of '{}' is {}.", s1, len);
         }
 
+        // The book takes &String here; &str comes with slices in the next section.
+        #[allow(clippy::ptr_arg)]
         fn calculate_length(s: &String)-> usize {
             s.len() // Have to remember to leave off the semi colon for return values in Rust. 
         }
@@ -195,7 +198,7 @@ fn main() {
 
         let mut s = String::from("hello, no race");
         {
-            let r1 = &mut s;
+            let _r1 = &mut s;
         }
         let r2 = &mut s;
 
@@ -203,6 +206,7 @@ fn main() {
 
         // Multiple burrows can be made in separate scopes. Just not together in the same scope.
 
+        #[allow(unused_mut)] // Needed again if the r3 line below is uncommented.
         let mut s = String::from("hello");
         let r1 = &s;
         let r2 = &s;
@@ -225,6 +229,7 @@ fn main() {
 
         */
 
+        #[allow(clippy::let_and_return)]
         fn no_dangle() -> String {
             let s = String::from("hello");
             s // This is ok because the ownership is being tranferred out of the scope. 
@@ -242,6 +247,17 @@ fn main() {
         let world = &s[6..];
         println!("{}:{}", hello, world);
 
+        // Those ranges count bytes. With "héllo", &s[..5] is only "héll" because 'é' takes two
+        // bytes, and &s[..2] panics because it cuts 'é' in half. Slicing by chars avoids both.
+        {
+            use learning::str_access::SafeSlice;
+
+            let s = String::from("héllo wörld");
+            println!("{:?}", s.char_slice(..5));
+            println!("{:?}", s.byte_slice(..2).map_err(|e| e.to_string()));
+            println!("first word: {}, second word: {:?}", s.first_word(), s.nth_word(1));
+        }
+
         println!("\n\nChapter 5--------------------------------------\n");
 
         println!("\n\n Defining and Instantiating Structs --------------------------------------\n");
@@ -263,6 +279,8 @@ fn main() {
 
             println!("Account Active: {}", user1.active);
 
+            // Written out in full before the book introduces the field init shorthand.
+            #[allow(clippy::redundant_field_names)]
             fn build_user(email: String, username: String) -> User {
                 User {
                     active: true, 
@@ -277,6 +295,8 @@ fn main() {
         }
 
         {
+            // Written out in full before the book introduces the field init shorthand.
+            #[allow(clippy::redundant_field_names)]
             fn build_user(email: String, username: String) -> User {
                 User {
                     active: true, 
@@ -298,12 +318,14 @@ fn main() {
         }
 
         {
+            #[allow(dead_code)]
             struct Color(i32, i32, i32);
+            #[allow(dead_code)]
             struct Point(i32, i32, i32);
 
  
             let black = Color(1, 0, 0);
-            let origin = Point(1, 0, 0);
+            let _origin = Point(1, 0, 0);
             
             println!("{}",black.0)
         }
@@ -336,17 +358,11 @@ fn main() {
 
         // With Struct
         
-        #[derive(Debug)]
-        struct Rectangle {
-            width: u32,
-            height: u32,
-        }
+        // Rectangle now lives in the learning crate, which also places it at an x, y position.
+        use learning::geometry::Rectangle;
 
         {
-            let rect1 = Rectangle {
-                width: 30, 
-                height: 50,
-            };
+            let rect1 = Rectangle::new(30, 50);
 
             println!(
                 "The area of the rectangle is {} square pixels.", area(&rect1)
@@ -382,19 +398,14 @@ fn main() {
         println!("\n\n match Control Flow Construct --------------------------------------\n");
 
         {
-            enum Coin {
-                Penny, 
-                Nickel, 
-                Dime, 
-                Quarter,
-            }
+            use learning::change::Coin;
 
             fn value_in_cents(coin:Coin) -> u8 {
                 match coin {
                     Coin::Penny => 1,
                     Coin::Nickel => 5, 
                     Coin::Dime => 10,
-                    Coin::Quarter => 25,
+                    Coin::Quarter(_) => 25,
                 }
             }
 
@@ -404,18 +415,8 @@ fn main() {
         }
 
         {
-            #[derive(Debug)]
-            enum UsState{
-                Alabama, 
-                Alaska,
-                NewYork
-            }
-            enum Coin {
-                Penny, 
-                Nickel, 
-                Dime, 
-                Quarter (UsState),
-            }
+            use learning::change::Coin;
+            use learning::quarters::UsState;
 
             fn values_incents2(coin: Coin) -> u8 {
                 match coin {
@@ -435,6 +436,8 @@ fn main() {
 
         println!("\n\n matching with option<t> --------------------------------------\n");
         {
+            // The book's first version; learning::numeric::plus_one is the one that can't overflow.
+            #[allow(dead_code, clippy::manual_map)]
             fn plus_one(x: Option<i32>) -> Option<i32> {
                 match x {
                     None => None, 